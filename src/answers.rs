@@ -0,0 +1,122 @@
+// The expected answers for the checked in inputs live in res/answers.toml.
+// The tests in here run every day on its input and compare against them,
+// so refactoring a solution can't silently change its answer.
+
+use crate::{Answer, ParseError, DAYS};
+use std::collections::BTreeMap;
+
+pub const ANSWERS_PATH: &str = "res/answers.toml";
+
+/// expected answers by (day, part)
+pub type Answers = BTreeMap<(usize, usize), Answer>;
+
+/// parse the answers file. It's just the small part of TOML that is needed here:
+/// a `[dayN]` table for each day with the keys `part1` and `part2`,
+/// where the values are integers or strings without escapes. Lines starting with `#` are comments.
+pub fn parse_answers(text: &str) -> Result<Answers, ParseError> {
+    let mut answers = Answers::new();
+    let mut day = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(table) = line.strip_prefix('[') {
+            let d = table.strip_suffix(']')
+                .and_then(|table| table.strip_prefix("day"))
+                .and_then(|d| d.parse::<usize>().ok())
+                .ok_or(ParseError::new(i + 1, "expected a table like [day1]"))?;
+            day = Some(d);
+            continue;
+        }
+        let day = day.ok_or(ParseError::new(i + 1, "expected a [dayN] table before the first answer"))?;
+        let (key, value) = line.split_once('=').ok_or(ParseError::new(i + 1, "expected \"partN = answer\""))?;
+        let part = match key.trim() {
+            "part1" => 1,
+            "part2" => 2,
+            key => return Err(ParseError::new(i + 1, format!("unknown key {key}, expected part1 or part2"))),
+        };
+        let value = value.trim();
+        let answer = if let Some(text) = value.strip_prefix('"') {
+            let text = text.strip_suffix('"').ok_or(ParseError::new(i + 1, "unterminated string"))?;
+            Answer::Text(text.to_string())
+        }
+        else {
+            Answer::Number(value.parse().map_err(|_| ParseError::new(i + 1, format!("invalid answer {value}")))?)
+        };
+        if answers.insert((day, part), answer).is_some() {
+            return Err(ParseError::new(i + 1, format!("duplicate answer for day {day} part {part}")));
+        }
+    }
+    Ok(answers)
+}
+
+/// read and parse res/answers.toml
+pub fn read_answers() -> Result<Answers, String> {
+    let text = std::fs::read_to_string(ANSWERS_PATH).map_err(|err| format!("failed to read {ANSWERS_PATH}: {err}"))?;
+    parse_answers(&text).map_err(|err| format!("{ANSWERS_PATH}: {err}"))
+}
+
+/// run all the (slow or not slow) parts on their inputs and collect every answer that doesn't match.
+pub fn check_answers(slow: bool) -> Vec<String> {
+    let expected = read_answers().unwrap_or_else(|err| panic!("{err}"));
+    let mut failures = vec![];
+    for (i, solution) in DAYS.iter().enumerate() {
+        let day = solution.day();
+        assert_eq!(day, i + 1, "the days are out of order");
+        if (1..=2).all(|part| solution.is_slow(part) != slow) {
+            continue;
+        }
+        let input = match solution.parse(&crate::read_input(day)) {
+            Ok(input) => input,
+            Err(err) => {
+                failures.push(format!("day {day}: failed to parse the input: {err}"));
+                continue;
+            }
+        };
+        for part in [1, 2] {
+            if solution.is_slow(part) != slow {
+                continue;
+            }
+            let answer = solution.run(part, input.as_ref());
+            match expected.get(&(day, part)) {
+                Some(expected) if *expected == answer => println!("day {day} part {part}: {answer}"),
+                Some(expected) => failures.push(format!("day {day} part {part}: expected {expected}, got {answer}")),
+                None => failures.push(format!("day {day} part {part}: no answer in {ANSWERS_PATH}, got {answer}")),
+            }
+        }
+    }
+    failures
+}
+
+#[test]
+pub fn answers() {
+    let failures = check_answers(false);
+    assert!(failures.is_empty(), "{} wrong answer(s):\n{}", failures.len(), failures.join("\n"));
+}
+
+#[test]
+#[ignore = "takes a long time on the full inputs"]
+pub fn slow_answers() {
+    let failures = check_answers(true);
+    assert!(failures.is_empty(), "{} wrong answer(s):\n{}", failures.len(), failures.join("\n"));
+}
+
+#[test]
+pub fn answers_format() {
+    let answers = parse_answers("# comment\n[day3]\npart1 = 42\npart2 = \"text\"\n\n[day4]\npart2 = -7\n").unwrap();
+    assert_eq!(answers.len(), 3);
+    assert_eq!(answers[&(3, 1)], Answer::Number(42));
+    assert_eq!(answers[&(3, 2)], Answer::Text("text".into()));
+    assert_eq!(answers[&(4, 2)], Answer::Number(-7));
+    assert_eq!(parse_answers("part1 = 1").unwrap_err().line, 1);
+    assert_eq!(parse_answers("[day1]\npart3 = 1").unwrap_err().line, 2);
+    assert_eq!(parse_answers("[day1]\npart1 = 1\npart1 = 2").unwrap_err().line, 3);
+    // every part of every day must have an answer
+    let answers = read_answers().unwrap();
+    for day in 1..=DAYS.len() {
+        for part in [1, 2] {
+            assert!(answers.contains_key(&(day, part)), "day {day} part {part} is missing in {ANSWERS_PATH}");
+        }
+    }
+}
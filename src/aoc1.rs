@@ -25,22 +25,49 @@ In this example, the calibration values of these four lines are 12, 38, 15, and
 Consider your entire calibration document. What is the sum of all of the calibration values?
 */
 
-#[test]
-pub fn part1() {
-    use std::io;
+use crate::{Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 1;
+    const TITLE: &'static str = "Trebuchet?!";
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
 
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    Ok(input.lines().take_while(|line| !line.is_empty()).map(|line| line.to_string()).collect())
+}
+
+pub fn part1(lines: &[String]) -> Answer {
     let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let first: u32 = input.chars().into_iter().filter(|c| char::is_numeric(*c)).next().unwrap_or('0').to_digit(10).unwrap();
-        let last: u32 = input.chars().into_iter().filter(|c| char::is_numeric(*c)).rev().next().unwrap_or('0').to_digit(10).unwrap();
+    for line in lines {
+        let first: u32 = line.chars().find(|c| char::is_numeric(*c)).unwrap_or('0').to_digit(10).unwrap();
+        let last: u32 = line.chars().rfind(|c| char::is_numeric(*c)).unwrap_or('0').to_digit(10).unwrap();
         sum += first * 10 + last;
     }
-    println!("The calibration sum is {sum}");
+    sum.into()
+}
+
+#[test]
+pub fn example_part1() {
+    let input = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+    assert_eq!(part1(&parse(input).unwrap()), 142.into());
 }
 
 /*
@@ -63,31 +90,35 @@ In this example, the calibration values are 29, 83, 13, 24, 42, 14, and 76. Addi
 What is the sum of all of the calibration values?
 */
 
-#[test]
-pub fn part2() {
-    use std::io;
-
+pub fn part2(lines: &[String]) -> Answer {
     let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
+    for line in lines {
         // just replacing is not a valid strategy because eightwo is a possible text where eight would be first and two would be last
         // however one can amend the numbers, such that no overlap can happen
-        input = input.replace("one", "o1ne")
-                     .replace("two", "tw2o")
-                     .replace("three", "th3ree")
-                     .replace("four", "fo4ur")
-                     .replace("five", "fi5ve")
-                     .replace("six", "six6")
-                     .replace("seven", "se7ven")
-                     .replace("eight", "eig8ht")
-                     .replace("nine", "ni9ne");
-        let first: u32 = input.chars().into_iter().filter(|c| char::is_numeric(*c)).next().unwrap_or('0').to_digit(10).unwrap();
-        let last: u32 = input.chars().into_iter().filter(|c| char::is_numeric(*c)).rev().next().unwrap_or('0').to_digit(10).unwrap();
+        let line = line.replace("one", "o1ne")
+                       .replace("two", "tw2o")
+                       .replace("three", "th3ree")
+                       .replace("four", "fo4ur")
+                       .replace("five", "fi5ve")
+                       .replace("six", "six6")
+                       .replace("seven", "se7ven")
+                       .replace("eight", "eig8ht")
+                       .replace("nine", "ni9ne");
+        let first: u32 = line.chars().find(|c| char::is_numeric(*c)).unwrap_or('0').to_digit(10).unwrap();
+        let last: u32 = line.chars().rfind(|c| char::is_numeric(*c)).unwrap_or('0').to_digit(10).unwrap();
         sum += first * 10 + last;
     }
-    println!("The calibration sum is {sum}");
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    let input = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+    assert_eq!(part2(&parse(input).unwrap()), 281.into());
+}
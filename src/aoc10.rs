@@ -105,22 +105,26 @@ Here are the distances for each tile on that loop:
 Find the single giant loop starting at S. How many steps along the loop does it take to get from the starting position to the point farthest from the starting position?
 */
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
+use crate::grid::{Direction, Grid, Point};
+use crate::{Answer, ParseError, Solution};
 
-impl Direction {
-    pub fn delta_coords(&self) -> (isize, isize) {
-        match self {
-            Self::North => (0, -1),
-            Self::East => (1, 0),
-            Self::South => (0, 1),
-            Self::West => (-1, 0),
-        }
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 10;
+    const TITLE: &'static str = "Pipe Maze";
+    type Input = Maze;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
@@ -136,9 +140,13 @@ pub enum Field {
     Start,
 }
 
-impl From<char> for Field {
-    fn from(value: char) -> Self {
-        match value {
+/// the characters of the fields, for error messages
+pub const FIELD_CHARS: &str = "one of | - L J 7 F . S";
+
+impl TryFrom<char> for Field {
+    type Error = ParseError;
+    fn try_from(value: char) -> Result<Self, ParseError> {
+        Ok(match value {
             '|' => Self::Vertical,
             '-' => Self::Horizontal,
             'L' => Self::NorthEast,
@@ -147,8 +155,8 @@ impl From<char> for Field {
             'F' => Self::SouthEast,
             '.' => Self::Ground,
             'S' => Self::Start,
-            _ => panic!("invalid char {value}"),
-        }
+            _ => return Err(ParseError::new(0, format!("unexpected \"{value}\"")).expecting(FIELD_CHARS)),
+        })
     }
 }
 
@@ -191,59 +199,41 @@ impl Field {
     }
 }
 
-#[test]
-pub fn part1() {
-    // idea: do a full readin of the data and then a processing step
+/// the pipe field and the start position.
+pub struct Maze {
+    pub field: Grid<Field>,
+    pub start: Point,
+}
 
-    use std::io;
+pub fn parse(input: &str) -> Result<Maze, ParseError> {
+    let field = Grid::parse(input, FIELD_CHARS, |c| Field::try_from(c).ok())?;
+    let start = field.position(|&f| f == Field::Start).ok_or(ParseError::new(0, "there is no start S in the field"))?;
+    Ok(Maze { field, start })
+}
 
-    let mut field = vec![];
-    let mut start = (0, 0);
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<Field> = input.chars().enumerate().map(|(i, c)| {
-            let f = c.into();
-            if f == Field::Start {
-                start = (i, field.len());
-            }
-            f
-        }).collect();
-        field.push(line);
-    }
-    let width = field[0].len() as isize;
-    let height = field.len() as isize;
+pub fn part1(maze: &Maze) -> Answer {
+    // idea: do a full readin of the data and then a processing step
+    let Maze { field, start } = maze;
+    let start = *start;
     // Now I got the start and the whole field.
     // First find the two directions that the animal could have run.
     let mut two_dirs = [Direction::North; 2];
     let mut index = 0;
-    for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
-        let delta = dir.delta_coords();
-        let next = (start.0 as isize + delta.0, start.1 as isize + delta.1);
-        if next.0 >= 0 && next.1 >= 0 && next.0 < width && next.1 < height {
-            // next field is inside the map
-            if let Some(_) = field[next.1 as usize][next.0 as usize].try_next(dir) {
-                two_dirs[index] = dir;
-                index += 1;
-            }
+    for (dir, next) in field.neighbors(start) {
+        if field[next].try_next(dir).is_some() {
+            two_dirs[index] = dir;
+            index += 1;
         }
     }
     assert!(index == 2);
-    println!("starting at {start:?} in the {width}x{height} field.");
-    println!("{two_dirs:?}");
     // now start going around the loop in both directions simulataneously and stop when the heads are equal again
     let mut heads = [start; 2];
     let mut count = 0;
     loop {
         for (i, dir) in two_dirs.into_iter().enumerate() {
-            let delta = dir.delta_coords();
-            let next = ((heads[i].0 as isize + delta.0) as usize, (heads[i].1 as isize + delta.1) as usize);
+            let next = heads[i] + dir.delta();
             // no need to check validity here, as the input should not lead us outside the field
-            two_dirs[i] = field[next.1][next.0].next(dir);
+            two_dirs[i] = field[next].next(dir);
             heads[i] = next;
         }
         count += 1;
@@ -253,7 +243,35 @@ pub fn part1() {
             break;
         }
     }
-    println!("The farthest point has distance {count}");
+    count.into()
+}
+
+#[test]
+pub fn example_part1() {
+    let square = ".....
+.S-7.
+.|.|.
+.L-J.
+.....";
+    assert_eq!(part1(&parse(square).unwrap()), 4.into());
+    let square_with_noise = "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+    assert_eq!(part1(&parse(square_with_noise).unwrap()), 4.into());
+    let complex = "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...";
+    assert_eq!(part1(&parse(complex).unwrap()), 8.into());
+    let complex_with_noise = "7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+    assert_eq!(part1(&parse(complex_with_noise).unwrap()), 8.into());
 }
 
 /*
@@ -358,70 +376,87 @@ In this last example, 10 tiles are enclosed by the loop.
 Figure out whether you have time to search for the nest by calculating the area within the loop. How many tiles are enclosed by the loop?
 */
 
-#[test]
-pub fn part2() {
+pub fn part2(maze: &Maze) -> Answer {
     // idea: use some trickery with overlapping rectangles to count the area
-
-    use std::io;
-
-    let mut field = vec![];
-    let mut start = (0, 0);
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<Field> = input.chars().enumerate().map(|(i, c)| {
-            let f = c.into();
-            if f == Field::Start {
-                start = (i, field.len());
-            }
-            f
-        }).collect();
-        field.push(line);
-    }
-    let width = field[0].len() as isize;
-    let height = field.len() as isize;
+    let Maze { field, start } = maze;
+    let start = *start;
     // Now I got the start and the whole field.
     // First find the two directions that the animal could have run.
     let mut current_dir = Direction::North;
-    for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
-        let delta = dir.delta_coords();
-        let next = (start.0 as isize + delta.0, start.1 as isize + delta.1);
-        if next.0 >= 0 && next.1 >= 0 && next.0 < width && next.1 < height {
-            // next field is inside the map
-            if let Some(_) = field[next.1 as usize][next.0 as usize].try_next(dir) {
-                current_dir = dir;
-                break;
-            }
+    for (dir, next) in field.neighbors(start) {
+        if field[next].try_next(dir).is_some() {
+            current_dir = dir;
+            break;
         }
     }
-    println!("starting at {start:?} in the {width}x{height} field.");
     // now start going around the loop in one direction
     let mut head = start;
     let mut count = 0;
     let mut area = 0;
     loop {
-        let delta = current_dir.delta_coords();
-        let next = ((head.0 as isize + delta.0) as usize, (head.1 as isize + delta.1) as usize);
+        let delta = current_dir.delta();
+        let next = head + delta;
         // no need to check validity here, as the input should not lead us outside the field
         head = next;
         count += 1;
         // for each horizontal step, add a rectangle
-        area += head.1 as isize * delta.0;
+        area += head.1 * delta.0;
         if head == start {
             // reached the farthest point!
             break;
         }
-        current_dir = field[next.1][next.0].next(current_dir);
+        current_dir = field[next].next(current_dir);
     }
     // could run around the loop in the wrong direction -> negative area
     area = area.abs();
     // the area above counts also part of the path
     // subtract that part of the path:
     area -= count / 2 - 1;
-    println!("The farthest point has distance {}", count/2);
-    println!("The loop contains an area of {area}");
-}
\ No newline at end of file
+    area.into()
+}
+
+#[test]
+pub fn example_part2() {
+    let input = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+    assert_eq!(part2(&parse(input).unwrap()), 4.into());
+    let squeezed = "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+    assert_eq!(part2(&parse(squeezed).unwrap()), 4.into());
+    let larger = ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+    assert_eq!(part2(&parse(larger).unwrap()), 8.into());
+    let with_junk = "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+    assert_eq!(part2(&parse(with_junk).unwrap()), 10.into());
+}
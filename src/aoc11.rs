@@ -97,6 +97,29 @@ In this example, after expanding the universe, the sum of the shortest path betw
 Expand the universe, then find the length of the shortest path between every pair of galaxies. What is the sum of these lengths?
 */
 
+use crate::grid::Grid;
+use crate::{Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 11;
+    const TITLE: &'static str = "Cosmic Expansion";
+    type Input = Image;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 pub fn stretch_space(pos: usize, empty_sorted: &[usize], stretch: usize) -> usize {
     pos + match empty_sorted.binary_search(&pos) {
         Ok(_) => panic!("can't expand on row/column {pos} which contains a galaxy"),
@@ -104,60 +127,42 @@ pub fn stretch_space(pos: usize, empty_sorted: &[usize], stretch: usize) -> usiz
     }
 }
 
-#[test]
-pub fn part1() {
+/// the galaxy positions (x, y) and the sorted empty rows and columns.
+pub struct Image {
+    pub galaxies: Vec<(usize, usize)>,
+    pub empty_rows: Vec<usize>,
+    pub empty_columns: Vec<usize>,
+}
+
+pub fn parse(input: &str) -> Result<Image, ParseError> {
     // idea: parse the data first
-    use std::io;
-    use std::collections::HashSet;
-
-    let mut galaxies = vec![];
-    let mut width = None;
-    let mut y = 0;
-    let mut empty_rows = vec![];
-    let mut empty_columns = HashSet::new();
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        if y == 0 {
-            width = Some(input.len());
-            // init empty_columns
-            empty_columns.extend(0..width.unwrap());
-        }
-        else {
-            assert_eq!(input.len(), width.unwrap(), "one line wasn't the same length as the previous.");
-        }
-        let len_before = galaxies.len();
-        galaxies.extend(input.chars().enumerate().filter_map(|(x, c)| {
-            match c {
-                '.' => None,
-                '#' => { empty_columns.remove(&x); Some((x, y)) },
-                _ => panic!("unknown character {c} in input"),
-            }
-        }));
-        if galaxies.len() == len_before {
-            // no galaxies in this row -> empty row
-            empty_rows.push(y);
-        }
-        y += 1;
-    }
-    // empty_rows is sorted by design
-    let mut empty_columns: Vec<_> = empty_columns.into_iter().collect();
-    empty_columns.sort(); // not sure which order the set outputs... -> sort
-    println!("empty rows: {empty_rows:?}");
-    println!("empty columns: {empty_columns:?}");
+    let grid = Grid::parse(input, "a galaxy # or empty space .", |c| match c {
+        '.' => Some(false),
+        '#' => Some(true),
+        _ => None,
+    })?;
+    let galaxies = grid.iter().filter(|(_, &galaxy)| galaxy).map(|(p, _)| (p.0 as usize, p.1 as usize)).collect();
+    // both are sorted by design
+    let empty_rows = (0..grid.height()).filter(|&y| !grid.row(y).contains(&true)).collect();
+    let empty_columns = (0..grid.width()).filter(|&x| !grid.column(x).any(|&galaxy| galaxy)).collect();
+    Ok(Image { galaxies, empty_rows, empty_columns })
+}
+
+pub fn part1(image: &Image) -> Answer {
     // at this point I already now which columns and rows are empty and where the galaxies are.
     // the shortest distance length is the manhatten distance |dx|+|dy|
     // There are two options to include the space stretching
     // 1. every shortest path which crosses an empty row/colum gets 1 longer
     // 2. reposition the galaxies before computing distances
     // obviously the second is better since for the 1. it would need to run the stretch detection O(n^2) times.
-    let galaxies: Vec<_> = galaxies.into_iter().map(|(x, y)|
-        (stretch_space(x, &empty_columns, 2),
-         stretch_space(y, &empty_rows, 2))
+    sum_of_distances(image, 2).into()
+}
+
+/// sum of the distances between all pairs of galaxies, after each empty row and column got `stretch` times larger.
+pub fn sum_of_distances(image: &Image, stretch: usize) -> usize {
+    let galaxies: Vec<_> = image.galaxies.iter().map(|&(x, y)|
+        (stretch_space(x, &image.empty_columns, stretch),
+         stretch_space(y, &image.empty_rows, stretch))
     ).collect();
     let mut sum = 0;
     for (i, &coord_a) in galaxies.iter().enumerate() {
@@ -165,7 +170,24 @@ pub fn part1() {
             sum += coord_a.0.abs_diff(coord_b.0) + coord_a.1.abs_diff(coord_b.1);
         }
     }
-    println!("The sum of the shortest paths between galaxies is {sum}");
+    sum
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 374.into());
 }
 
 /*
@@ -180,61 +202,15 @@ Now, instead of the expansion you did before, make each empty row or column one
 Starting with the same initial image, expand the universe according to these new rules, then find the length of the shortest path between every pair of galaxies. What is the sum of these lengths?
 */
 
-#[test]
-pub fn part2() {
+pub fn part2(image: &Image) -> Answer {
     // wow this was trivial!
-    use std::io;
-    use std::collections::HashSet;
-
-    let mut galaxies = vec![];
-    let mut width = None;
-    let mut y = 0;
-    let mut empty_rows = vec![];
-    let mut empty_columns = HashSet::new();
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        if y == 0 {
-            width = Some(input.len());
-            // init empty_columns
-            empty_columns.extend(0..width.unwrap());
-        }
-        else {
-            assert_eq!(input.len(), width.unwrap(), "one line wasn't the same length as the previous.");
-        }
-        let len_before = galaxies.len();
-        galaxies.extend(input.chars().enumerate().filter_map(|(x, c)| {
-            match c {
-                '.' => None,
-                '#' => { empty_columns.remove(&x); Some((x, y)) },
-                _ => panic!("unknown character {c} in input"),
-            }
-        }));
-        if galaxies.len() == len_before {
-            // no galaxies in this row -> empty row
-            empty_rows.push(y);
-        }
-        y += 1;
-    }
-    // empty_rows is sorted by design
-    let mut empty_columns: Vec<_> = empty_columns.into_iter().collect();
-    empty_columns.sort(); // not sure which order the set outputs... -> sort
-    println!("empty rows: {empty_rows:?}");
-    println!("empty columns: {empty_columns:?}");
     // just add 1000000 instead of 2 for each empty row. Easy!
-    let galaxies: Vec<_> = galaxies.into_iter().map(|(x, y)|
-        (stretch_space(x, &empty_columns, 1000000),
-         stretch_space(y, &empty_rows, 1000000))
-    ).collect();
-    let mut sum = 0;
-    for (i, &coord_a) in galaxies.iter().enumerate() {
-        for &coord_b in galaxies[i+1..].iter() {
-            sum += coord_a.0.abs_diff(coord_b.0) + coord_a.1.abs_diff(coord_b.1);
-        }
-    }
-    println!("The sum of the shortest paths between galaxies is {sum}");
+    sum_of_distances(image, 1000000).into()
+}
+
+#[test]
+pub fn example_part2() {
+    let image = parse(EXAMPLE).unwrap();
+    assert_eq!(sum_of_distances(&image, 10), 1030);
+    assert_eq!(sum_of_distances(&image, 100), 8410);
 }
\ No newline at end of file
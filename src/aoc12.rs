@@ -75,6 +75,28 @@ Adding all of the possible arrangement counts together produces a total of 21 ar
 For each row, count all of the different arrangements of operational and broken springs that meet the given criteria. What is the sum of those counts?
 */
 
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 12;
+    const TITLE: &'static str = "Hot Springs";
+    type Input = Vec<(String, Vec<usize>)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 /*
 additional testing data
 
@@ -85,7 +107,7 @@ additional testing data
 
 */
 
-use std::{collections::HashMap, cell::RefCell, sync::Mutex};
+use std::collections::HashMap;
 
 pub fn binom(n: usize, k: usize) -> u128 {
     let mut binom = 1;
@@ -116,38 +138,28 @@ pub fn test_just_questions() {
     }
 }
 
-static MEMO: Mutex<RefCell<Option<HashMap<(String, Vec<usize>), u128>>>> = Mutex::new(RefCell::new(None));
+/// the known counts for (line, numbers). Every solve starts with a new one.
+pub type Memo = HashMap<(String, Vec<usize>), u128>;
 
 // memoization helped very much!!!
-pub fn dfs_memo(line: &str, numbers: &[usize]) -> u128 {
+pub fn dfs_memo(memo: &mut Memo, line: &str, numbers: &[usize]) -> u128 {
     let line = line.trim_matches('.');
     if numbers.iter().sum::<usize>() + numbers.len() > line.len() + 1 {
         return 0; // the numbers can't fit (anymore)
     }
     // check memoized results first
     let key = (line.to_string(), numbers.to_vec());
-    {
-        let guard = MEMO.lock().expect("failed to lock memo mutex.");
-        let mut map = (*guard).borrow_mut();
-        if let Some(map) = &*map {
-            if let Some(value) = map.get(&key) {
-                return *value;
-            }
-        }
-        else {
-            *map = Some(HashMap::new());
-        }
+    if let Some(value) = memo.get(&key) {
+        return *value;
     }
-    let count = dfs(line, numbers);
-    // add to the memo{
-    let guard = MEMO.lock().expect("failed to lock memo mutex.");
-    let mut map = (*guard).borrow_mut();
-    (*map).as_mut().unwrap().insert(key, count);
+    let count = dfs(memo, line, numbers);
+    // add to the memo
+    memo.insert(key, count);
     count
 }
 
 // depth first search
-pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
+pub fn dfs(memo: &mut Memo, line: &str, numbers: &[usize]) -> u128 {
     // start at the first ? and try both options (copying the string twice)
     // but first, write a checker for inputs without ? to get the code started.
     let line = line.trim_matches('.');
@@ -157,7 +169,7 @@ pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
 
     // after the first #, there needs to be a fixed number of # following determined by number
     if let Some((first_index, _)) = line.chars().enumerate().find(|(_, c)| c == &'#') {
-        if numbers.len() == 0 {
+        if numbers.is_empty() {
             return 0; // wrong line, not matching numbers, too many #
         }
         // check for question marks and make sure they are filled up, up to first_index
@@ -200,7 +212,7 @@ pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
                         }
                     }
                     let left = if start > 0 && first_q_index < start-1 {
-                        dfs_memo(&next_line[first_q_index..start-1], &numbers[..i])
+                        dfs_memo(memo, &next_line[first_q_index..start-1], &numbers[..i])
                     }
                     else if i == 0 {
                         1
@@ -209,7 +221,7 @@ pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
                         0
                     };
                     let right = if end+1 < next_line.len() {
-                        dfs_memo(&next_line[end+1..], &numbers[i+1..])
+                        dfs_memo(memo, &next_line[end+1..], &numbers[i+1..])
                     }
                     else if i == numbers.len()-1 {
                         1
@@ -237,9 +249,9 @@ pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
             return 0; // group was longer than expected
         }
         // remove the group and recurse
-        dfs_memo(&line[(next_empty+1).min(line.len())..], &numbers[1..])
+        dfs_memo(memo, &line[(next_empty+1).min(line.len())..], &numbers[1..])
     }
-    else if numbers.len() == 0 {
+    else if numbers.is_empty() {
         1 // in these case, following ? need to be ., so only 1 solution
     }
     else if let Some((first_q_index, _)) = line.chars().enumerate().find(|(_, c)| c == &'?') {
@@ -248,11 +260,11 @@ pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
             let dot_index = first_q_index+1+dot_index;
             // split the rest by . and then go through all combinations in which numbers can be distributed on the sections
             // each of the only ??? sections can be computed directly and then multiplied together.
-            let mut count = dfs_memo(&line[dot_index+1..], numbers);
+            let mut count = dfs_memo(memo, &line[dot_index+1..], numbers);
             for i in 1..=numbers.len() {
                 let left = just_questions(dot_index, &numbers[..i]);
                 if left != 0 {
-                    let right = dfs_memo(&line[dot_index+1..], &numbers[i..]);
+                    let right = dfs_memo(memo, &line[dot_index+1..], &numbers[i..]);
                     count += left * right;
                 }
             }
@@ -272,7 +284,7 @@ pub fn dfs(line: &str, numbers: &[usize]) -> u128 {
 pub fn test_dfs() {
     for numbers in [[1, 1, 1], [1, 1, 2], [1, 2, 3]] {
         for len in 0..20 {
-            assert_eq!(dfs(&"?".repeat(len), &numbers),
+            assert_eq!(dfs(&mut Memo::new(), &"?".repeat(len), &numbers),
                        dfs2(&"?".repeat(len), &numbers),
                        "failed for len {len} and numbers {numbers:?}");
         }
@@ -289,7 +301,7 @@ pub fn dfs2(line: &str, numbers: &[usize]) -> u128 {
     }
     // after the first #, there needs to be a fixed number of # following determined by number
     if let Some((first_index, _)) = line.chars().enumerate().find(|(_, c)| c == &'#') {
-        if numbers.len() == 0 {
+        if numbers.is_empty() {
             return 0; // wrong line, not matching numbers, too many #
         }
         // check for question marks and make sure they are filled up, up to first_index
@@ -303,7 +315,7 @@ pub fn dfs2(line: &str, numbers: &[usize]) -> u128 {
             for c in ['.', '#'] {
                 let mut next_line = line[first_q_index+1..].to_string();
                 next_line.insert(0, c);
-                count += dfs2(&next_line, &numbers);
+                count += dfs2(&next_line, numbers);
             }
             return count;
         }
@@ -323,7 +335,7 @@ pub fn dfs2(line: &str, numbers: &[usize]) -> u128 {
         // remove the group and recurse
         dfs2(&line[(next_empty+1).min(line.len())..], &numbers[1..])
     }
-    else if numbers.len() == 0 {
+    else if numbers.is_empty() {
         1 // in these case, following ? need to be ., so only 1 solution
     }
     else if let Some((first_q_index, _)) = line.chars().enumerate().find(|(_, c)| c == &'?') {
@@ -347,7 +359,7 @@ pub fn dfs2(line: &str, numbers: &[usize]) -> u128 {
                 0
             };
             // or don't place it yet
-            count += dfs2(&line[1..], &numbers);
+            count += dfs2(&line[1..], numbers);
             count
         }
     }
@@ -356,32 +368,56 @@ pub fn dfs2(line: &str, numbers: &[usize]) -> u128 {
     }
 }
 
-#[test]
-pub fn part1() {
+/// parse the condition records with their group sizes.
+pub fn parse(input: &str) -> Result<Vec<(String, Vec<usize>)>, ParseError> {
+    let mut records = vec![];
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
+            break;
+        }
+        let (record, numbers) = line.trim().split_once(' ').ok_or(ParseError::new(i + 1, "failed to split line in two"))?;
+        if let Some((j, c)) = record.char_indices().find(|(_, c)| !matches!(c, '.' | '#' | '?')) {
+            return Err(ParseError::unexpected(i + 1, line, &record[j..j + c.len_utf8()], "a spring . # or ?"));
+        }
+        let numbers = numbers.split(',').map(|n| parse_number(i + 1, line, n)).collect::<Result<Vec<usize>, _>>()?;
+        records.push((record.to_string(), numbers));
+    }
+    Ok(records)
+}
+
+pub fn part1(records: &[(String, Vec<usize>)]) -> Answer {
     // idea: parse line after line and heavily use combinatorics!
     // actually... use DFS!
 
     // solution was 7032
 
-    use std::io;
-    use std::time::*;
-    let start = Instant::now();
-
+    let mut memo = Memo::new();
     let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let (record, numbers) = input.trim().split_once(' ').expect("failed to split line in two");
-        let numbers: &[usize] = &numbers.split(',').map(|n| n.parse::<usize>().ok().expect("failed to parse number")).collect::<Vec<_>>();
-        let combinations = dfs(record, numbers);
-        println!("arrangements: {combinations}");
+    for (record, numbers) in records {
+        let combinations = dfs(&mut memo, record, numbers);
         sum += combinations;
     }
-    println!("The sum of the different arrangements is {sum}");
-    println!("Time: {:?}", Instant::now() - start);
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+#[test]
+pub fn example_part1() {
+    let records = parse(EXAMPLE).unwrap();
+    for ((record, numbers), expected) in records.iter().zip([1, 4, 1, 1, 4, 10]) {
+        assert_eq!(dfs(&mut Memo::new(), record, numbers), expected, "failed for {record} {numbers:?}");
+        assert_eq!(dfs2(record, numbers), expected, "failed for {record} {numbers:?}");
+    }
+    assert_eq!(part1(&records), 21.into());
+    // my additional testing data
+    assert_eq!(dfs(&mut Memo::new(), "..??#?#.#?#???#??#?", &[5, 5, 1, 1]), 1);
 }
 
 /*
@@ -417,8 +453,7 @@ After unfolding, adding all of the possible arrangement counts together produces
 Unfold your condition records; what is the new sum of possible arrangement counts?
 */
 
-#[test]
-pub fn part2() {
+pub fn part2(records: &[(String, Vec<usize>)]) -> Answer {
     // try not to complicate things and just try the naive solution, I don't like this task.
     // this isn't working good enough...
     // idea: combine the simpler solutions here
@@ -430,31 +465,28 @@ pub fn part2() {
     // turns out I had an additional question mark at the end...
     // now I got 1493340882140
 
-    use std::io;
-    use std::time::*;
-    let start = Instant::now();
-
     const REPEATS: usize = 5;
 
+    let mut memo = Memo::new();
     let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let (record, numbers) = input.trim().split_once(' ').expect("failed to split line in two");
-        let numbers: &[usize] = &numbers.split(',').map(|n| n.parse::<usize>().ok().expect("failed to parse number")).collect::<Vec<_>>();
-        let mut record = record.to_string();
+    for (record, numbers) in records {
+        let mut record = record.clone();
         record.push('?');
         let record = &record.repeat(REPEATS);
         let record = &record[..record.len()-1];
         //println!("{record}");
         let numbers = &numbers.repeat(REPEATS);
-        let combinations = dfs_memo(record, numbers);
-        println!("arrangements: {combinations}");
+        let combinations = dfs_memo(&mut memo, record, numbers);
         sum += combinations;
     }
-    println!("The sum of the different arrangements is {sum}");
-    println!("Time: {:?}", Instant::now() - start);
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    let records = parse(EXAMPLE).unwrap();
+    for (record, expected) in records.iter().zip([1, 16384, 1, 16, 2500, 506250]) {
+        assert_eq!(part2(std::slice::from_ref(record)), expected.into(), "failed for {} {:?}", record.0, record.1);
+    }
+    assert_eq!(part2(&records), 525152.into());
+}
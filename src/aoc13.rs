@@ -64,7 +64,30 @@ To summarize your pattern notes, add up the number of columns to the left of eac
 Find the line of reflection in each of the patterns in your notes. What number do you get after summarizing all of your notes?
 */
 
-pub fn check_mirror<T: Eq>(vec: &Vec<T>, mirror_index: usize) -> bool {
+use crate::grid::Grid;
+use crate::{Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 13;
+    const TITLE: &'static str = "Point of Incidence";
+    type Input = Vec<Grid<bool>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+pub fn check_mirror<T: Eq>(vec: &[T], mirror_index: usize) -> bool {
     if mirror_index*2 <= vec.len() {
         vec.iter().zip(vec[mirror_index..2*mirror_index].iter().rev()).all(|(a, b)| a == b)
     }
@@ -73,62 +96,89 @@ pub fn check_mirror<T: Eq>(vec: &Vec<T>, mirror_index: usize) -> bool {
     }
 }
 
-#[test]
-pub fn part1() {
-    use std::io;
-    use std::time::*;
-    let start = Instant::now();
-
-    let mut sum = 0;
+/// parse the patterns, which are separated by empty lines. `true` is rock.
+pub fn parse(input: &str) -> Result<Vec<Grid<bool>>, ParseError> {
+    let mut fields = vec![];
     let mut field: Vec<Vec<bool>> = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            // new field, compute the stuff for last field
-            if field.len() == 0 {
-                if read_bytes == 0 {
-                    break; // Ctrl + D to end input
-                }
-                field.clear();
-                continue;
-            }
-            let width = field[0].len();
-            let height = field.len();
-
-            let columns: &Vec<_> = &mut (0..width).map(|i| field.iter().map(|row| row[i].clone()).collect::<Vec<_>>()).collect();
-            let rows = &field;
-            
-            let mut row = 0;
-            for i in 1..height {
-                if check_mirror(rows, i) {
-                    row = i;
-                    break;
-                }
+    for (i, raw_line) in input.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() {
+            // new field
+            if !field.is_empty() {
+                fields.push(Grid::from_rows(field).unwrap());
+                field = vec![];
             }
-            let mut column = 0;
-            for i in 1..width {
-                if check_mirror(columns, i) {
-                    column = i;
-                    break;
-                }
-            }
-
-            assert_ne!(column != 0, row != 0, "ambiguous configuration (column: {column}, row: {row})");
-            println!("mirror on {row}/{column}");
-            sum += row * 100 + column;
-            field.clear();
+            continue;
         }
-        if read_bytes == 0 {
-            break; // Ctrl + D to end input
+        if field.first().is_some_and(|row| row.len() != line.len()) {
+            return Err(ParseError::new(i + 1, "one line wasn't the same length as the previous."));
+        }
+        let row = line.char_indices().map(|(j, c)| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(ParseError::unexpected(i + 1, raw_line, &line[j..j + c.len_utf8()], "# or .")),
+        }).collect::<Result<_, _>>()?;
+        field.push(row);
+    }
+    if !field.is_empty() {
+        fields.push(Grid::from_rows(field).unwrap());
+    }
+    Ok(fields)
+}
+
+pub fn part1(fields: &[Grid<bool>]) -> Answer {
+    let mut sum = 0;
+    for field in fields {
+        let width = field.width();
+        let height = field.height();
+
+        let transposed = field.transpose();
+        let columns: &Vec<_> = &transposed.rows().collect();
+        let rows: &Vec<_> = &field.rows().collect();
+
+        let mut row = 0;
+        for i in 1..height {
+            if check_mirror(rows, i) {
+                row = i;
+                break;
+            }
         }
-        let line = input.trim().chars().map(|c| c == '#').collect::<Vec<_>>();
-        if line.len() > 0 {
-            field.push(line);
+        let mut column = 0;
+        for i in 1..width {
+            if check_mirror(columns, i) {
+                column = i;
+                break;
+            }
         }
+
+        assert_ne!(column != 0, row != 0, "ambiguous configuration (column: {column}, row: {row})");
+        sum += row * 100 + column;
     }
-    println!("The sum of the row/column indices is {sum}");
-    println!("Time: {:?}", Instant::now() - start);
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 405.into());
+    let err = parse("#.#\n\n##.\n.o#").unwrap_err();
+    assert_eq!((err.line, err.column, err.expected), (4, 2, Some("# or .".to_string())));
 }
 
 /*
@@ -187,7 +237,7 @@ Summarize your notes as before, but instead use the new different reflection lin
 In each pattern, fix the smudge and find the different line of reflection. What number do you get after summarizing the new reflection line in each pattern in your notes?
 */
 
-pub fn mirror_error<T: Eq>(vec: &Vec<Vec<T>>, mirror_index: usize) -> usize {
+pub fn mirror_error<T: Eq>(vec: &[&[T]], mirror_index: usize) -> usize {
     if mirror_index*2 <= vec.len() {
         vec.iter().zip(vec[mirror_index..2*mirror_index].iter().rev()).map(|(a, b)| a.iter().zip(b.iter()).map(|(a, b)| (a != b) as usize).sum::<usize>()).sum::<usize>()
     }
@@ -196,64 +246,43 @@ pub fn mirror_error<T: Eq>(vec: &Vec<Vec<T>>, mirror_index: usize) -> usize {
     }
 }
 
-#[test]
-pub fn part2() {
+pub fn part2(fields: &[Grid<bool>]) -> Answer {
     // So this task seems to be only about creating a new mirror plane
     // it doesn't care about the existing planes.
     // E.g. in the first example after fixing the smudge there are 2 mirror planes.
     // So I have to detect "almost mirror planes"
 
-    use std::io;
-    use std::time::*;
-    let start = Instant::now();
-
     let mut sum = 0;
-    let mut field: Vec<Vec<bool>> = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            // new field, compute the stuff for last field
-            if field.len() == 0 {
-                if read_bytes == 0 {
-                    break; // Ctrl + D to end input
-                }
-                field.clear();
-                continue;
-            }
-            let width = field[0].len();
-            let height = field.len();
-
-            let columns: &Vec<_> = &mut (0..width).map(|i| field.iter().map(|row| row[i].clone()).collect::<Vec<_>>()).collect();
-            let rows = &field;
-            
-            let mut row = 0;
-            for i in 1..height {
-                if mirror_error(rows, i) == 1 {
-                    row = i;
-                    break;
-                }
+    for field in fields {
+        let width = field.width();
+        let height = field.height();
+
+        let transposed = field.transpose();
+        let columns: &Vec<_> = &transposed.rows().collect();
+        let rows: &Vec<_> = &field.rows().collect();
+
+        let mut row = 0;
+        for i in 1..height {
+            if mirror_error(rows, i) == 1 {
+                row = i;
+                break;
             }
-            let mut column = 0;
-            for i in 1..width {
-                if mirror_error(columns, i) == 1 {
-                    column = i;
-                    break;
-                }
-            }
-
-            assert_ne!(column != 0, row != 0, "ambiguous configuration (column: {column}, row: {row})");
-            sum += row * 100 + column;
-            field.clear();
-        }
-        if read_bytes == 0 {
-            break; // Ctrl + D to end input
         }
-        let line = input.trim().chars().map(|c| c == '#').collect::<Vec<_>>();
-        if line.len() > 0 {
-            field.push(line);
+        let mut column = 0;
+        for i in 1..width {
+            if mirror_error(columns, i) == 1 {
+                column = i;
+                break;
+            }
         }
+
+        assert_ne!(column != 0, row != 0, "ambiguous configuration (column: {column}, row: {row})");
+        sum += row * 100 + column;
     }
-    println!("The sum of the row/column indices is {sum}");
-    println!("Time: {:?}", Instant::now() - start);
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 400.into());
+}
@@ -55,26 +55,44 @@ The total load is the sum of the load caused by all of the rounded rocks. In thi
 Tilt the platform so that the rounded rocks all roll north. Afterward, what is the total load on the north support beams?
 */
 
-#[test]
-pub fn part1() {
-    use std::io;
-    use std::time::*;
-    let start = Instant::now();
+use crate::cycle;
+use crate::grid::{Direction, Grid, Point};
+use crate::{Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 14;
+    const TITLE: &'static str = "Parabolic Reflector Dish";
+    type Input = Grid<char>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
 
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+/// read the platform with rounded rocks O, cube rocks # and empty spaces.
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input, "a rounded rock O, a cube rock # or empty space .", |c| matches!(c, '#' | 'O' | '.').then_some(c))
+}
+
+pub fn part1(field: &Grid<char>) -> Answer {
     let mut load = vec![];
     let mut obstacle = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        if obstacle.len() == 0 {
-            obstacle = [1].repeat(input.len());
+    for line in field.rows() {
+        if obstacle.is_empty() {
+            obstacle = [1].repeat(line.len());
         }
         load.push(0);
-        for (i, c) in input.trim().chars().enumerate() {
+        for (i, &c) in line.iter().enumerate() {
             match c {
                 '#' => {
                     obstacle[i] = 0;
@@ -93,8 +111,24 @@ pub fn part1() {
         }
     }
     let sum = load.into_iter().rev().enumerate().map(|(i, load)| (i+1) * load).sum::<usize>();
-    println!("The sum of the load on the north support beams is {sum}");
-    println!("Time: {:?}", Instant::now() - start);
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 136.into());
 }
 
 /*
@@ -149,104 +183,270 @@ In the above example, after 1000000000 cycles, the total load on the north suppo
 Run the spin cycle for 1000000000 cycles. Afterward, what is the total load on the north support beams?
 */
 
-pub fn tilt<I: Iterator<Item=I2>, I2: Iterator<Item=char>>(src: I) -> Vec<Vec<char>> {
-    let mut field = vec![];
-    let mut obstacle = vec![];
-    for line in src {
-        let len = line.size_hint().0; // trust size_hint!
-        if obstacle.len() == 0 {
-            obstacle = [1].repeat(len);
-        }
-        field.push(['.'].repeat(len));
-        for (i, c) in line.enumerate() {
+/// the rounded rocks on a platform, one bit per cell, row by row.
+/// This is the only thing that changes when tilting, so it's all the cycle detection needs to hash.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rocks {
+    bits: Vec<u64>,
+}
+
+/// the layout of a platform: its size and the cube rocks, which never move.
+/// The rounded rocks are kept separately in [Rocks]. Both are bitsets with a few u64 words per row.
+pub struct Platform {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    cubes: Vec<u64>,
+    // The stretches without cube rocks in every row.
+    // Tilting west or east moves all rounded rocks of a stretch to one of its ends, so counting them is all there is to do.
+    // Most of them are inside one word, those are (word index, mask of the stretch),
+    // the others which cross a word boundary are (y, x_start, x_end).
+    short_segments: Vec<(usize, u64)>,
+    long_segments: Vec<(usize, usize, usize)>,
+}
+
+/// the bits of the word with the given index, which are in the range start..end of the row.
+fn range_mask(word: usize, start: usize, end: usize) -> u64 {
+    let low = start.saturating_sub(word * 64).min(64);
+    let high = end.saturating_sub(word * 64).min(64);
+    if low >= high {
+        0
+    }
+    else if high == 64 {
+        !0 << low
+    }
+    else {
+        ((1 << high) - 1) & (!0 << low)
+    }
+}
+
+impl Platform {
+    /// split the field into the fixed layout and the rounded rocks.
+    pub fn from_grid(field: &Grid<char>) -> (Platform, Rocks) {
+        let (width, height) = (field.width(), field.height());
+        let words_per_row = width.div_ceil(64);
+        let mut platform = Platform { width, height, words_per_row, cubes: vec![], short_segments: vec![], long_segments: vec![] };
+        let mut cubes = vec![0; words_per_row * height];
+        let mut rocks = Rocks { bits: vec![0; words_per_row * height] };
+        for (p, &c) in field.iter() {
+            let (x, y) = (p.0 as usize, p.1 as usize);
             match c {
-                '#' => {
-                    obstacle[i] = 0;
-                    field.last_mut().unwrap()[i] = '#';
-                },
-                'O' => {
-                    let index = field.len() - obstacle[i];
-                    obstacle[i] -= 1;
-                    field[index][i] = 'O';
-                },
-                '.' => (),
-                _ => panic!("invalid input char {c}"),
+                '#' => platform.set(&mut cubes, x, y),
+                'O' => platform.set(&mut rocks.bits, x, y),
+                _ => (),
             }
         }
-        for x in obstacle.iter_mut() {
-            *x += 1;
+        platform.cubes = cubes;
+        for (y, row) in field.rows().enumerate() {
+            let mut start = 0;
+            for x in 0..=width {
+                if row.get(x).is_none_or(|&c| c == '#') {
+                    if start < x && start / 64 == (x - 1) / 64 {
+                        let word = start / 64;
+                        platform.short_segments.push((y * words_per_row + word, range_mask(word, start, x)));
+                    }
+                    else if start < x {
+                        platform.long_segments.push((y, start, x));
+                    }
+                    start = x + 1;
+                }
+            }
         }
+        (platform, rocks)
     }
-    field
-}
 
-#[test]
-pub fn part2() {
-    // as expected I need to reimplement everything, but that's fine.
-    // To run it for 1000000000 cycles, there will need to be a cycle detection,
-    // detecting when 4 tilts don't make a difference anymore -> abort there.
-    // turns out there can be cycles of longer length...
-    // find the cycle length!
-
-    use std::io;
-    use std::collections::HashMap;
-    use std::time::*;
-    let start = Instant::now();
-
-    let mut field: Vec<Vec<char>> = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
+    /// the platform as a grid of chars again, like in the puzzle.
+    pub fn to_grid(&self, rocks: &Rocks) -> Grid<char> {
+        let mut field = Grid::new(self.width, self.height, '.');
+        for (x, y) in self.cells(&self.cubes) {
+            field[Point(x as i64, y as i64)] = '#';
         }
-        let line = input.trim().chars().collect::<Vec<_>>();
-        if line.len() > 0 {
-            field.push(line);
+        for (x, y) in self.cells(&rocks.bits) {
+            field[Point(x as i64, y as i64)] = 'O';
         }
+        field
     }
-    // the first cycle is special as it doesn't need the rotation.
-    field = tilt(field.into_iter().map(|row| row.into_iter()));
-    let mut cycle_detect: HashMap<Vec<Vec<char>>, u64> = HashMap::new();
-    let mut cycles = 1000000000 - 1;
-    let mut cycle_detected = false;
-    let mut i = 0u64;
-    while i < cycles {
-        // save for cycle detection
-        if !cycle_detected && (i + 4).is_power_of_two() {
-            cycle_detect.insert(field.clone(), i);
-        }
-        // now use the tilt function to tilt west, south, east, north
-        for j in 0..4 {
-            if j == 3 {
-                // debug rendering
-                if false {
-                    for row in &field {
-                        println!("{}", String::from_iter(row.iter()));
+
+    fn set(&self, bits: &mut [u64], x: usize, y: usize) {
+        bits[y * self.words_per_row + x / 64] |= 1 << (x % 64);
+    }
+
+    /// the (x, y) positions of all set bits.
+    fn cells<'a>(&self, bits: &'a [u64]) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let words_per_row = self.words_per_row;
+        bits.iter().enumerate().flat_map(move |(i, &word)| {
+            let (y, x0) = (i / words_per_row, i % words_per_row * 64);
+            std::iter::successors((word != 0).then_some(word), |&w| (w & (w - 1) != 0).then_some(w & (w - 1)))
+                .map(move |w| (x0 + w.trailing_zeros() as usize, y))
+        })
+    }
+
+    /// tilt the platform, so all the rounded rocks roll in the direction until they hit something.
+    pub fn tilt(&self, rocks: &mut Rocks, direction: Direction) {
+        let words = self.words_per_row;
+        let bits = &mut rocks.bits;
+        match direction {
+            Direction::North | Direction::South => {
+                // Go through the rows starting at the side the rocks roll to. The rows before are done already,
+                // so all rocks of a word can roll on together, until each of them hits something.
+                let north = direction == Direction::North;
+                let rows: Vec<usize> = if north {
+                    (1..self.height).collect()
+                }
+                else {
+                    (0..self.height.saturating_sub(1)).rev().collect()
+                };
+                for y in rows {
+                    for w in 0..words {
+                        let mut moving = bits[y * words + w];
+                        let mut row = y;
+                        while moving != 0 {
+                            let next = if north { row.checked_sub(1) } else { (row + 1 < self.height).then_some(row + 1) };
+                            let Some(next) = next else {
+                                break;
+                            };
+                            moving &= !(bits[next * words + w] | self.cubes[next * words + w]);
+                            bits[row * words + w] &= !moving;
+                            bits[next * words + w] |= moving;
+                            row = next;
+                        }
                     }
-                    println!();
                 }
-            }
-            // rotate in the iterator
-            field = tilt((0..field[0].len()).map(|i| field.iter().rev().map(move |row| row[i])));
+            },
+            Direction::West | Direction::East => {
+                for &(i, mask) in &self.short_segments {
+                    let count = (bits[i] & mask).count_ones();
+                    // the lowest or highest count bits of the mask
+                    let rolled = if direction == Direction::West {
+                        mask & !mask.checked_shl(count).unwrap_or(0)
+                    }
+                    else {
+                        mask & !mask.checked_shr(count).unwrap_or(0)
+                    };
+                    bits[i] = (bits[i] & !mask) | rolled;
+                }
+                for &(y, start, end) in &self.long_segments {
+                    let row = &mut bits[y * words..(y + 1) * words];
+                    let (first, last) = (start / 64, (end - 1) / 64);
+                    let count = (first..=last).map(|w| (row[w] & range_mask(w, start, end)).count_ones() as usize).sum::<usize>();
+                    let (from, to) = if direction == Direction::West { (start, start + count) } else { (end - count, end) };
+                    for (w, word) in row.iter_mut().enumerate().take(last + 1).skip(first) {
+                        *word = (*word & !range_mask(w, start, end)) | range_mask(w, from, to);
+                    }
+                }
+            },
+        }
+    }
+
+    /// one spin cycle: tilt north, west, south and east.
+    pub fn spin(&self, rocks: &mut Rocks) {
+        for direction in [Direction::North, Direction::West, Direction::South, Direction::East] {
+            self.tilt(rocks, direction);
+        }
+    }
+
+    pub fn north_load(&self, rocks: &Rocks) -> usize {
+        rocks.bits.chunks(self.words_per_row).enumerate().map(|(y, row)| {
+            (self.height - y) * row.iter().map(|w| w.count_ones() as usize).sum::<usize>()
+        }).sum()
+    }
+}
+
+#[test]
+pub fn example_tilt() {
+    let tilted = "OOOO.#.O..
+OO..#....#
+OO..O##..O
+O..#.OO...
+........#.
+..#....#.#
+..O..#.O.O
+..O.......
+#....###..
+#....#....";
+    let (platform, mut rocks) = Platform::from_grid(&parse(EXAMPLE).unwrap());
+    platform.tilt(&mut rocks, Direction::North);
+    assert_eq!(platform.to_grid(&rocks), parse(tilted).unwrap());
+    assert_eq!(platform.north_load(&rocks), 136);
+}
+
+#[test]
+pub fn tilt_all_directions() {
+    // compare with the simple way of tilting, one rock at a time, on a pseudo random platform
+    // which is wider than one word per row
+    let mut random = crate::Random::new(7);
+    let field = Grid::from_rows((0..37).map(|_| (0..150).map(|_| {
+        match random.below(8) {
+            0 => '#',
+            1..=2 => 'O',
+            _ => '.',
         }
-        i += 1;
-        if let Some(last_index) = cycle_detect.get(&field) {
-            // found a cycle!
-            let cycle_length = i - last_index;
-            println!("cycle of length {cycle_length} detected");
-            cycles -= i;
-            i = 0;
-            cycles %= cycle_length;
-            cycle_detected = true;
+    }).collect()).collect()).unwrap();
+    let (platform, rocks) = Platform::from_grid(&field);
+    assert_eq!(platform.to_grid(&rocks), field);
+    for direction in Direction::ALL {
+        let mut expected = field.clone();
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for p in expected.points().collect::<Vec<_>>() {
+                let next = p + direction.delta();
+                if expected[p] == 'O' && expected.get(next) == Some(&'.') {
+                    expected[p] = '.';
+                    expected[next] = 'O';
+                    moved = true;
+                }
+            }
         }
+        let mut tilted = rocks.clone();
+        platform.tilt(&mut tilted, direction);
+        assert_eq!(platform.to_grid(&tilted), expected, "tilting {direction:?}");
     }
-    // rotate until the east tilt is reached
+}
+
+pub fn part2(field: &Grid<char>) -> Answer {
+    // as expected I need to reimplement everything, but that's fine.
+    // To run it for 1000000000 cycles, there will need to be a cycle detection,
+    // detecting when 4 tilts don't make a difference anymore -> abort there.
+    // turns out there can be cycles of longer length...
+    // find the cycle length! Only the rounded rocks are part of the state, as bits they are cheap to hash.
+    let (platform, rocks) = Platform::from_grid(field);
+    let rocks = cycle::state_after(rocks, |rocks| {
+        let mut rocks = rocks.clone();
+        platform.spin(&mut rocks);
+        rocks
+    }, 1000000000);
+    platform.north_load(&rocks).into()
+}
+
+#[test]
+pub fn example_spin() {
+    let after_3 = ".....#....
+....#...O#
+.....##...
+..O#......
+.....OOO#.
+.O#...O#.#
+....O#...O
+.......OOO
+#...O###.O
+#.OOO#...O";
+    let (platform, mut rocks) = Platform::from_grid(&parse(EXAMPLE).unwrap());
+    let spin = |rocks: &Rocks| {
+        let mut rocks = rocks.clone();
+        platform.spin(&mut rocks);
+        rocks
+    };
+    // the spins start repeating after 3 cycles, every 7 cycles
+    assert_eq!(cycle::find_cycle(rocks.clone(), spin), (3, 7));
+    assert_eq!(cycle::brent(rocks.clone(), spin), (3, 7));
     for _ in 0..3 {
-        field = tilt((0..field[0].len()).map(|i| field.iter().rev().map(move |row| row[i])));
+        platform.spin(&mut rocks);
     }
-    // calculate load, but remember that the field is still rotated
-    let load = field.into_iter().map(|row| row.into_iter().rev().enumerate().map(|(i, c)| (i+1) * (c == 'O') as usize).sum::<usize>()).sum::<usize>();
-    println!("The sum of the load on the north support beams is {load}");
-    println!("Time: {:?}", Instant::now() - start);
-}
\ No newline at end of file
+    assert_eq!(platform.to_grid(&rocks), parse(after_3).unwrap());
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 64.into());
+}
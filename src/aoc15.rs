@@ -69,6 +69,28 @@ In this example, the sum of these results is 1320. Unfortunately, the reindeer h
 Run the HASH algorithm on each step in the initialization sequence. What is the sum of the results? (The initialization sequence is one long line; be careful when copy-pasting it.)
 */
 
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 15;
+    const TITLE: &'static str = "Lens Library";
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 pub fn hash(instruction: &str) -> u8 {
     let mut hash = 0u8;
     for c in instruction.chars() {
@@ -79,17 +101,44 @@ pub fn hash(instruction: &str) -> u8 {
 }
 
 #[test]
-pub fn part1() {
-    use std::io;
+pub fn example_hash() {
+    assert_eq!(hash("HASH"), 52);
+    let steps = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7".split(',');
+    let hashes = [30, 253, 97, 47, 14, 180, 9, 197, 48, 214, 231];
+    for (step, expected) in steps.zip(hashes) {
+        assert_eq!(hash(step), expected, "failed for {step}");
+    }
+}
+
+/// split the initialization sequence into its steps.
+pub fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    // newlines are ignored
+    let line: String = input.chars().filter(|&c| c != '\n' && c != '\r').collect();
+    let mut steps = vec![];
+    for step in line.trim().split(',') {
+        if step.strip_suffix('-').is_none() {
+            let (_, value) = step.split_once('=').ok_or(ParseError::unexpected(1, &line, &step[step.len()..], "= or -"))?;
+            parse_number::<u64>(1, &line, value)?;
+        }
+        steps.push(step.to_string());
+    }
+    Ok(steps)
+}
 
+pub fn part1(steps: &[String]) -> Answer {
     let mut sum = 0;
-    let mut input = String::new();
-    // just one line this time
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-    for instruction in input.trim().split(',') {
+    for instruction in steps {
         sum += hash(instruction) as u64;
     }
-    println!("The sum of the hashes is {sum}");
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 1320.into());
 }
 
 /*
@@ -190,6 +239,12 @@ pub struct HashMap<'a, V> {
     pub boxes: [Vec<(&'a str, V)>; 256],
 }
 
+impl<'a, V> Default for HashMap<'a, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, V> HashMap<'a, V> {
     pub fn new() -> Self {
         HashMap { boxes: [(); 256].map(|_| vec![]) }
@@ -210,37 +265,30 @@ impl<'a, V> HashMap<'a, V> {
     pub fn remove(&mut self, key: &'a str) -> Option<V> {
         let hash = hash(key);
         let hashbox = &mut self.boxes[hash as usize];
-        if let Some(i) = hashbox.iter().enumerate().find_map(|(i, entry)| if entry.0 == key { Some(i) } else { None }) {
-            Some(hashbox.remove(i).1)
-        }
-        else {
-            None
-        }
+        hashbox.iter().enumerate().find_map(|(i, entry)| if entry.0 == key { Some(i) } else { None }).map(|i| hashbox.remove(i).1)
     }
 }
 
-#[test]
-pub fn part2() {
-    use std::io;
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-    
+pub fn part2(steps: &[String]) -> Answer {
     let mut hashmap = HashMap::new();
 
-    for instruction in input.trim().split(',') {
-        if instruction.ends_with('-') {
-            let key = &instruction[..instruction.len() - 1];
+    for instruction in steps {
+        if let Some(key) = instruction.strip_suffix('-') {
             hashmap.remove(key);
         }
         else {
             let (key, value) = instruction.split_once('=').expect("invalid instruction, expected = or -");
-            hashmap.insert(key, value.parse::<u64>().ok().expect("after the = there needs to be an integer"));
+            hashmap.insert(key, value.parse::<u64>().expect("after the = there needs to be an integer"));
         }
     }
 
     let sum = hashmap.boxes.iter().enumerate().map(|(i, hashbox)| (i + 1) as u64
             * hashbox.iter().enumerate().map(|(i, entry)| (i + 1) as u64 * entry.1).sum::<u64>()).sum::<u64>();
 
-    println!("focusing power: {sum}");
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 145.into());
+}
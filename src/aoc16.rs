@@ -62,44 +62,26 @@ Ultimately, in this example, 46 tiles become energized.
 The light isn't energizing enough tiles to produce lava; to debug the contraption, you need to start by analyzing the current situation. With the beam starting in the top-left heading right, how many tiles end up being energized?
 */
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Direction {
-    North = 0,
-    East = 1,
-    South = 2,
-    West = 3,
-}
+use crate::grid::{Direction, Grid, Point};
+use crate::{Answer, ParseError, Solution};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct Point(pub isize, pub isize);
+pub struct Day;
 
-impl std::ops::Add for Point {
-    type Output = Point;
-    fn add(self, rhs: Self) -> Self::Output {
-        Point(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-impl std::ops::Sub for Point {
-    type Output = Point;
-    fn sub(self, rhs: Self) -> Self::Output {
-        Point(self.0 - rhs.0, self.1 - rhs.1)
+impl Solution for Day {
+    const DAY: usize = 16;
+    const TITLE: &'static str = "The Floor Will Be Lava";
+    type Input = Grid<Field>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-}
 
-impl Point {
-    pub fn in_bounds(&self, width: usize, height: usize) -> bool {
-        self.0 >= 0 && self.1 >= 0 && (self.0 as usize) < width && (self.1 as usize) < height
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
     }
-}
 
-impl Direction {
-    pub fn delta(&self) -> Point {
-        match self {
-            Self::North => Point(0, -1),
-            Self::East => Point(1, 0),
-            Self::South => Point(0, 1),
-            Self::West => Point(-1, 0),
-        }
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
@@ -112,16 +94,20 @@ pub enum Field {
     Empty,
 }
 
-impl From<char> for Field {
-    fn from(value: char) -> Self {
-        match value {
+/// the characters of the fields, for error messages
+pub const FIELD_CHARS: &str = "one of | - / \\ .";
+
+impl TryFrom<char> for Field {
+    type Error = ParseError;
+    fn try_from(value: char) -> Result<Self, ParseError> {
+        Ok(match value {
             '|' => Self::Vertical,
             '-' => Self::Horizontal,
             '/' => Self::Mirror,
             '\\' => Self::BackMirror,
             '.' => Self::Empty,
-            _ => panic!("invalid char {value}"),
-        }
+            _ => return Err(ParseError::new(0, format!("unexpected \"{value}\"")).expecting(FIELD_CHARS)),
+        })
     }
 }
 
@@ -153,64 +139,67 @@ impl Field {
     }
 }
 
-pub fn shine(field: &Vec<Vec<Field>>, start: (Point, Direction)) -> usize {
-    let width = field[0].len();
-    let height = field.len();
+pub fn shine(field: &Grid<Field>, start: (Point, Direction)) -> usize {
     // setup the field for the light
-    let mut light: Vec<_> = (0..height).map(|_| [[false; 4]].repeat(width)).collect();
+    let mut light = Grid::new(field.width(), field.height(), [false; 4]);
     let mut heads = vec![start];
     loop {
         let mut new_heads = vec![];
         for (point, dir) in heads {
             // check if the head is valid
-            if !point.in_bounds(width, height) {
+            if !field.in_bounds(point) {
                 continue;
             }
             // check if a head has been here before
-            if !light[point.1 as usize][point.0 as usize][dir as usize] {
+            if !light[point][dir as usize] {
                 // mark light
-                light[point.1 as usize][point.0 as usize][dir as usize] = true;
+                light[point][dir as usize] = true;
                 // get new heads
-                let (next1, optional_next2) = field[point.1 as usize][point.0 as usize].next(dir);
+                let (next1, optional_next2) = field[point].next(dir);
                 new_heads.push((point + next1.delta(), next1));
                 if let Some(next2) = optional_next2 {
                     new_heads.push((point + next2.delta(), next2));
                 }
             }
         }
-        if new_heads.len() == 0 {
+        if new_heads.is_empty() {
             break;
         }
         heads = new_heads;
     }
 
     // Now evaluate the board
-    light.into_iter().flatten().map(|light_dir| light_dir.into_iter().any(|x| x) as usize).sum::<usize>()
+    light.cells().iter().map(|light_dir| light_dir.iter().any(|&x| x) as usize).sum::<usize>()
 }
 
-#[test]
-pub fn part1() {
+pub fn parse(input: &str) -> Result<Grid<Field>, ParseError> {
     // idea: do a full readin of the data and then a processing step
     // for the processing, save for each tile which directions have light moving in it
     // do a stepwise "simulation" to fill up the grid with light
+    Grid::parse(input, FIELD_CHARS, |c| Field::try_from(c).ok())
+}
 
-    use std::io;
-
-    let mut field = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<Field> = input.chars().map(|c| c.into()).collect();
-        field.push(line);
-    }
-    
+pub fn part1(field: &Grid<Field>) -> Answer {
     // Now I got the whole field. Time to shine!
-    let sum = shine(&field, (Point(0, 0), Direction::East));
-    println!("A total of {sum} squares are covered by light.");
+    let sum = shine(field, (Point(0, 0), Direction::East));
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 46.into());
 }
 
 /*
@@ -249,37 +238,23 @@ Using this configuration, 51 tiles are energized:
 Find the initial beam configuration that energizes the largest number of tiles; how many tiles are energized in that configuration?
 */
 
-#[test]
-pub fn part2() {
-    // idea: do a full readin of the data and then a processing step
-    // for the processing, save for each tile which directions have light moving in it
-    // do a stepwise "simulation" to fill up the grid with light
-
-    use std::io;
-
-    let mut field = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<Field> = input.chars().map(|c| c.into()).collect();
-        field.push(line);
-    }
-    
+pub fn part2(field: &Grid<Field>) -> Answer {
     // Now I got the whole field. Time to shine for real!
-    let width = field[0].len();
-    let height = field.len();
+    let width = field.width() as i64;
+    let height = field.height() as i64;
     let mut max = 0;
     for i in 0..height {
-        max = max.max(shine(&field, (Point(0, i as isize), Direction::East)));
-        max = max.max(shine(&field, (Point((width-1) as isize, i as isize), Direction::West)));
+        max = max.max(shine(field, (Point(0, i), Direction::East)));
+        max = max.max(shine(field, (Point(width-1, i), Direction::West)));
     }
     for i in 0..width {
-        max = max.max(shine(&field, (Point(i as isize, 0), Direction::South)));
-        max = max.max(shine(&field, (Point(i as isize, (height-1) as isize), Direction::North)));
+        max = max.max(shine(field, (Point(i, 0), Direction::South)));
+        max = max.max(shine(field, (Point(i, height-1), Direction::North)));
     }
-    println!("A total of {max} squares can be covered light.");
-}
\ No newline at end of file
+    max.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 51.into());
+}
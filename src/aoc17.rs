@@ -54,117 +54,63 @@ This path never moves more than three consecutive blocks in the same direction a
 Directing the crucible from the lava pool to the machine parts factory, but not moving more than three consecutive blocks in the same direction, what is the least heat loss it can incur?
 */
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub enum Direction {
-    North = 0,
-    East = 1,
-    West = 2,
-    South = 3,
-}
+use crate::grid::{Direction, Grid, Point};
+use crate::pathfinding::{astar, Path};
+use crate::{Answer, ParseError, Solution};
 
-impl Direction {
-    pub fn is_opposite(&self, other: &Direction) -> bool {
-        // Trick: I ordered the Direction names such that this becomes simple
-        (*self as u8 + *other as u8) == 3
-    }
-}
+pub struct Day;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub struct Point(pub isize, pub isize);
+impl Solution for Day {
+    const DAY: usize = 17;
+    const TITLE: &'static str = "Clumsy Crucible";
+    type Input = Grid<u32>;
 
-impl std::ops::Add for Point {
-    type Output = Point;
-    fn add(self, rhs: Self) -> Self::Output {
-        Point(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-impl std::ops::Sub for Point {
-    type Output = Point;
-    fn sub(self, rhs: Self) -> Self::Output {
-        Point(self.0 - rhs.0, self.1 - rhs.1)
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-}
 
-impl Point {
-    pub fn in_bounds(&self, width: usize, height: usize) -> bool {
-        self.0 >= 0 && self.1 >= 0 && (self.0 as usize) < width && (self.1 as usize) < height
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
     }
-}
 
-impl Direction {
-    pub fn delta(&self) -> Point {
-        match self {
-            Self::North => Point(0, -1),
-            Self::East => Point(1, 0),
-            Self::South => Point(0, 1),
-            Self::West => Point(-1, 0),
-        }
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum PathLength {
-    GreaterThan(u32), // lower bound
-    Exactly(u32), // best path known
-    Blocked,
-}
-
-pub struct Memo {
-    width: usize,
-    grid: Vec<Vec<((Direction, u32), (PathLength, Option<(Direction, u32)>))>>,
-}
-
-impl Memo {
-    pub fn new(width: usize, height: usize) -> Memo {
-        Memo { width, grid: (0..width*height).map(|_| Vec::with_capacity(8)).collect() }
-    }
-
-    pub fn get(&self, key: &CrucibleState) -> Option<&(PathLength, Option<(Direction, u32)>)> {
-        let bucket = &self.grid[key.pos.1 as usize * self.width + key.pos.0 as usize];
-        // search for a fitting condition in the bucket
-        for (b_key, b_value) in bucket {
-            if b_key.0 == key.dir && b_key.1 == key.momentum {
-                return Some(b_value);
-            }
-        }
-        None
-    }
-
-    /// insert a key-value pair into the memo.
-    /// NOTE: make sure to always insert the key with the maximal allowed momentum.
-    pub fn insert(&mut self, key: &CrucibleState, value: (PathLength, Option<(Direction, u32)>)) {
-        //assert!(momentum_range.contains(&key.momentum));
-        let bucket = &mut self.grid[key.pos.1 as usize * self.width + key.pos.0 as usize];
-        let new_b_key = (key.dir, key.momentum);
-        for (b_key, b_value) in bucket.iter_mut() {
-            if b_key == &new_b_key {
-                *b_value = value;
-                return;
-            }
-        }
-        bucket.push((new_b_key, value));
-    }
+/// position, direction and the number of blocks the crucible went straight in that direction.
+/// With momentum 0 the crucible just started and can go anywhere.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct CrucibleState {
+    pub pos: Point,
+    pub dir: Direction,
+    pub momentum: u32,
 }
 
-pub struct Crucible {
+/// A crucible on the city blocks, which has to go at least `min_steps_to_turn` and
+/// at most `max_steps_to_turn` blocks in one direction before it turns.
+pub struct Crucible<'a> {
     pub min_steps_to_turn: u32,
     pub max_steps_to_turn: u32,
-    pub grid: Vec<Vec<u32>>,
-    pub memo: Memo,
+    pub grid: &'a Grid<u32>,
     pub end: Point,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub struct CrucibleState {
-    pos: Point,
-    dir: Direction,
-    momentum: u32,
-}
+impl<'a> Crucible<'a> {
+    /// crucible which goes to the bottom right corner of the grid.
+    pub fn new(grid: &'a Grid<u32>, min_steps_to_turn: u32, max_steps_to_turn: u32) -> Self {
+        let end = Point(grid.width() as i64 - 1, grid.height() as i64 - 1);
+        Crucible { min_steps_to_turn, max_steps_to_turn, grid, end }
+    }
+
+    /// the crucible at the top left corner, ready to start.
+    pub fn start(&self) -> CrucibleState {
+        CrucibleState { pos: Point(0, 0), dir: Direction::East, momentum: 0 }
+    }
 
-impl Crucible {
     pub fn next(&self, state: &CrucibleState, dir: &Direction) -> Option<CrucibleState> {
         // the crucible can't turn around
-        if dir.is_opposite(&state.dir) {
+        if *dir == state.dir.opposite() {
             return None;
         }
         // The crucible can't move more than a couple times in one direction
@@ -181,163 +127,116 @@ impl Crucible {
         })
     }
 
-    pub fn has_next_options(&self, state: &CrucibleState) -> bool {
-        // There is this one case where there is guaranteed only one next direction, so I don't need to keep memos for this
-        !(state.momentum != 0 && state.momentum < self.min_steps_to_turn)
+    /// the states after one more block, with the heat lost on that block.
+    pub fn successors(&self, state: &CrucibleState) -> Vec<(CrucibleState, u64)> {
+        [Direction::South, Direction::East, Direction::West, Direction::North].iter()
+            .filter_map(|dir| self.next(state, dir))
+            // the crucible can't go out of bounds (would be funny if it could)
+            .filter_map(|next| self.grid.get(next.pos).map(|&heat_loss| (next, heat_loss as u64)))
+            .collect()
     }
 
-    pub fn find_shortest_path(&mut self, start: &CrucibleState, max_length: u32) -> (PathLength, Option<(Direction, u32)>) {
-        let mut distance = max_length;
-        let use_memo = self.has_next_options(&start);
-        // check if the zero path (start == end) is requested
-        if start.pos == self.end {
-            if start.momentum >= self.min_steps_to_turn {
-                return (PathLength::Exactly(0), Some((start.dir, 0)));
-            }
-            else {
-                // this makes the crucible crash/overshoot
-                return (PathLength::Blocked, None);
-            }
-        }
-        // check memoized results first
-        if use_memo {
-            if let Some(value) = self.memo.get(start) {
-                if let PathLength::GreaterThan(max_computed_path_len) = value.0 {
-                    if max_computed_path_len >= distance {
-                        return *value;
-                    }
-                }
-                else {
-                    return *value;
-                }
-            }
-        }
-        // add to memoization table to mark it as being currently processed
-        // (if a path has a perfect state loop, it is not the shortest!)
-        if use_memo {
-            self.memo.insert(start, (PathLength::Blocked, None));
-        }
-        // calculate distance
-        let width = self.grid[0].len();
-        let height = self.grid.len();
-        // best_dir contains the best direction and the length of the run without changing direction afterwards
-        let mut best_dir = None;
-        // find directions to explore
-        for dir in [Direction::South, Direction::East, Direction::West, Direction::North] {
-            // make the step
-            if let Some(next) = self.next(start, &dir) {
-                // the crucible can't go out of bounds (would be funny if it could)
-                if !next.pos.in_bounds(width, height) {
-                    continue;
-                }
-                // check if the crucible reaches the end
-                let dir_length = self.grid[next.pos.1 as usize][next.pos.0 as usize];
-                // heuristic for minimal length to the end
-                let to_end = next.pos - self.end;
-                let min_length = dir_length + (to_end.0.abs() + to_end.1.abs()) as u32;
-                // if there is a chance for improving the current best path, try it.
-                if min_length < distance {
-                    let (path_len, next_dir) = self.find_shortest_path(&next, 
-                        distance - dir_length);
-                    if let PathLength::Exactly(mut d) = path_len {
-                        let next_dir = next_dir.unwrap();
-                        d = d + dir_length;
-                        if d <= distance {
-                            distance = d;
-                            best_dir = Some((dir, if next_dir.0 == dir { next_dir.1 + 1 } else { 1 }));
-                        }
-                    }
-                }
-            }
-        }
-        let result = if best_dir.is_some() {
-            (PathLength::Exactly(distance), best_dir)
-        }
-        else {
-            // In this case the algorithm decided to not find the best path,
-            // so the only thing known is, that this is not part of the problem at this stage.
-            (PathLength::GreaterThan(distance), best_dir)
-        };
-        if use_memo {
-            self.memo.insert(start, result);
-        }
-        result
+    /// the crucible can only stop at the end, if it went far enough in the last direction.
+    pub fn is_goal(&self, state: &CrucibleState) -> bool {
+        state.pos == self.end && state.momentum >= self.min_steps_to_turn
+    }
+
+    /// the path with the least heat loss from the start to the end.
+    pub fn find_shortest_path(&self) -> Option<Path<CrucibleState>> {
+        // each block loses at least the smallest digit, so this never overestimates the heat loss
+        let min_loss = self.grid.cells().iter().copied().min().unwrap_or(0) as u64;
+        astar(self.start(), |state| self.successors(state), |state| state.pos.manhattan(&self.end) * min_loss, |state| self.is_goal(state))
     }
 
-    pub fn print_shortest_path(&mut self, start: &CrucibleState) {
-        let mut state = *start;
-        loop {
-            let (len, best_dir) = self.find_shortest_path(&state, u32::MAX);
-            let best_dir = best_dir.unwrap().0;
-            if let PathLength::Exactly(len) = len {
-                println!("{:?} {best_dir:?} {len}", state.pos);
-                state = self.next(&state, &best_dir).unwrap();
-                if state.pos == self.end {
-                    break;
-                }
-            }
-            else {
-                panic!("walked unknown path...");
-            }
+    /// the states along the path with the least heat loss, starting with the start state.
+    pub fn shortest_route(&self) -> Option<Vec<CrucibleState>> {
+        self.find_shortest_path().map(|path| path.states)
+    }
+
+    /// draw the route over the heat loss digits like in the puzzle text,
+    /// with an arrow for the direction that the crucible entered each block.
+    pub fn render_route(&self, route: &[CrucibleState]) -> String {
+        let mut drawing = self.grid.map(|&heat_loss| char::from_digit(heat_loss, 10).unwrap_or('?'));
+        // the start block is not entered, so it keeps its digit
+        for state in route.iter().skip(1) {
+            drawing[state.pos] = state.dir.arrow();
         }
+        drawing.to_string()
     }
 }
 
-#[test]
-pub fn part1() {
+/// read the heat loss digits of the city blocks.
+pub fn parse(input: &str) -> Result<Grid<u32>, ParseError> {
+    Grid::parse(input, "a digit", |c| c.to_digit(10))
+}
+
+pub fn part1(grid: &Grid<u32>) -> Answer {
     // This is (almost) a classical graph problem. The goal is finding the shortest path.
     // There is just one complication! The options for where to go next depend on the past of the path!
     // This is not allowed for classical path finding algorithms.
-    // So instead do another dfs with memoization.
-    // This will result in a very similar algorithm to the classical flood fill path finding.
-    // I can even add a heuristic into the dfs to sort the search directions to prefer going to the goal.
-    // NOTE: the memo depends on the grid, so I won't put it into a static!
+    // My first solution was another dfs with memoization of bounds for the path lengths.
 
     // What I missed:
     // Dijkstra does still work.
     // One can still step carefully from the start, always taking the step that produces the smallest total pathlength.
     // Then one will still find the end first in the smallest path.
     // The only difference to the classical path finding is, that the nodes visited will include direction and momentum.
-    // I'm not going to change my solution now though. This worked.
+    // So now it's just A* from the pathfinding module with that state.
 
     // my solution was 1138
 
-    use std::io;
 
-    let mut grid = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<u32> = input.chars().map(|c| c.to_digit(10).expect("expected number")).collect();
-        grid.push(line);
-    }
     // do the shortest path calculation
-    let width = grid[0].len();
-    let height = grid.len();
-    let end = Point(width as isize - 1, height as isize - 1);
-    let mut crucible = Crucible {
-        max_steps_to_turn: 3,
-        min_steps_to_turn: 1,
-        end,
-        grid,
-        memo: Memo::new(width, height),
-    };
-    let start = CrucibleState { pos: Point(0, 0), dir: Direction::East, momentum: 0 };
-    let (shortest, _) = crucible.find_shortest_path(&start, u32::MAX);
-    // stuff in the memo:
-    println!("area: {}", width * height);
-    /*for ((point, dir, momentum), (d, best_dir)) in memo.iter() {
-        if let PathLength::Exactly(d) = d {
-            println!("{point:?} {dir:?} {momentum} -> {d:?} {best_dir:?}")
-        }
-    }*/
-    println!("The shortest path looses {shortest:?} arbitrary heat units.");
-    // The path is
-    //crucible.print_shortest_path(start);
+    let crucible = Crucible::new(grid, 1, 3);
+    let path = crucible.find_shortest_path().expect("there is no path to the bottom right corner");
+    path.cost.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 102.into());
+}
+
+#[test]
+pub fn example_route() {
+    let grid = parse(EXAMPLE).unwrap();
+    let crucible = Crucible::new(&grid, 1, 3);
+    let route = crucible.shortest_route().unwrap();
+    assert_eq!(route[0], crucible.start());
+    assert!(crucible.is_goal(route.last().unwrap()));
+    let heat_loss: u32 = route[1..].iter().map(|state| grid[state.pos]).sum();
+    assert_eq!(heat_loss, 102);
+    // the same route as drawn in the puzzle
+    assert_eq!(crucible.render_route(&route), "\
+2>>34^>>>1323
+32v>>>35v5623
+32552456v>>54
+3446585845v52
+4546657867v>6
+14385987984v4
+44578769877v6
+36378779796v>
+465496798688v
+456467998645v
+12246868655<v
+25465488877v5
+43226746555v>
+");
 }
 
 /*
@@ -405,37 +304,68 @@ flipped example
 11111
 */
 
-#[test]
-pub fn part2() {
+pub fn part2(grid: &Grid<u32>) -> Answer {
     // my solution is 1312
-    use std::io;
-
-    let mut grid = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<u32> = input.chars().map(|c| c.to_digit(10).expect("expected number")).collect();
-        grid.push(line);
-    }
+
     // do the shortest path calculation
-    let width = grid[0].len();
-    let height = grid.len();
-    let end = Point(width as isize - 1, height as isize - 1);
-    let mut crucible = Crucible {
-        max_steps_to_turn: 10,
-        min_steps_to_turn: 4,
-        end,
-        grid,
-        memo: Memo::new(width, height),
-    };
-    let start = CrucibleState { pos: Point(0, 0), dir: Direction::East, momentum: 0 };
-    let (shortest, _) = crucible.find_shortest_path(&start, u32::MAX);
-    // stuff in the memo:
-    println!("area: {}", width * height);
-    println!("The shortest path looses {shortest:?} arbitrary heat units.");
-    //crucible.print_shortest_path(start);
-}
\ No newline at end of file
+    let crucible = Crucible::new(grid, 4, 10);
+    let path = crucible.find_shortest_path().expect("there is no path to the bottom right corner");
+    path.cost.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 94.into());
+    let unfortunate = "111111111111
+999999999991
+999999999991
+999999999991
+999999999991";
+    assert_eq!(part2(&parse(unfortunate).unwrap()), 71.into());
+    // my flipped version of the same example
+    let flipped = "19999
+19999
+19999
+19999
+19999
+19999
+19999
+19999
+19999
+19999
+19999
+11111";
+    assert_eq!(part2(&parse(flipped).unwrap()), 71.into());
+}
+
+#[test]
+pub fn example_route_part2() {
+    // with the ultra crucible the route can't turn after less than 4 blocks
+    let grid = parse(EXAMPLE).unwrap();
+    let crucible = Crucible::new(&grid, 4, 10);
+    assert_eq!(crucible.render_route(&crucible.shortest_route().unwrap()), "\
+2>>>>>>>>1323
+32154535v5623
+32552456v4254
+34465858v5452
+45466578v>>>>
+143859879845v
+445787698776v
+363787797965v
+465496798688v
+456467998645v
+122468686556v
+254654888773v
+432267465553v
+");
+    let grid = parse("111111111111\n999999999991\n999999999991\n999999999991\n999999999991").unwrap();
+    let crucible = Crucible::new(&grid, 4, 10);
+    assert_eq!(crucible.render_route(&crucible.shortest_route().unwrap()), "\
+1>>>>>>>1111
+9999999v9991
+9999999v9991
+9999999v9991
+9999999v>>>>
+");
+}
+
@@ -53,73 +53,78 @@ Now, the lagoon can contain a much more respectable 62 cubic meters of lava. Whi
 The Elves are concerned the lagoon won't be large enough; if they follow their dig plan, how many cubic meters of lava could it hold?
 */
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub enum Direction {
-    North = 0,
-    East = 1,
-    West = 2,
-    South = 3,
-}
+use crate::grid::{Direction, Point};
+use crate::{parse_number, Answer, ParseError, Solution};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub struct Point(pub i64, pub i64);
+pub struct Day;
 
-impl std::ops::Add for Point {
-    type Output = Point;
-    fn add(self, rhs: Self) -> Self::Output {
-        Point(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-impl std::ops::Mul<i64> for Point {
-    type Output = Point;
-    fn mul(self, rhs: i64) -> Self::Output {
-        Point(self.0 * rhs, self.1 * rhs)
+impl Solution for Day {
+    const DAY: usize = 18;
+    const TITLE: &'static str = "Lavaduct Lagoon";
+    type Input = Vec<[(Direction, i64); 2]>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-}
 
-impl Direction {
-    pub fn delta(&self) -> Point {
-        match self {
-            Self::North => Point(0, -1),
-            Self::East => Point(1, 0),
-            Self::South => Point(0, 1),
-            Self::West => Point(-1, 0),
-        }
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
     }
-}
 
-impl From<char> for Direction {
-    fn from(value: char) -> Self {
-        match value {
-            'U' => Self::North,
-            'R' => Self::East,
-            'D' => Self::South,
-            'L' => Self::West,
-            _ => panic!("invalid char {value}"),
-        }
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
-#[test]
-pub fn part1() {
+/// parse the dig plan. For every line, there is the step from the plan
+/// and the step, which is hidden in the color hex code.
+pub fn parse(input: &str) -> Result<Vec<[(Direction, i64); 2]>, ParseError> {
     // idea: do a full readin of the data and then a processing step
+    let mut plan = vec![];
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
+            break;
+        }
+        let line = line.trim();
+        let error = |message: &str| ParseError::new(i + 1, message);
+        let (a, b) = line.split_once('(').ok_or(error("missing color in ()"))?;
+        let (dir, steps) = a.trim().split_once(' ').ok_or(error("missing space between direction and steps"))?;
+        let dir = match dir {
+            "U" => Direction::North,
+            "R" => Direction::East,
+            "D" => Direction::South,
+            "L" => Direction::West,
+            _ => return Err(ParseError::unexpected(i + 1, line, dir, "a direction U, R, D or L")),
+        };
+        let steps = parse_number(i + 1, line, steps)?;
 
-    use std::io;
+        // silly elves, lets quickly do the conversion then
+        let color = b.trim_start_matches('#').trim_end_matches(')');
+        if color.len() != 6 || !color.is_char_boundary(5) {
+            return Err(ParseError::unexpected(i + 1, line, color, "a color hex code with 6 digits"));
+        }
+        let color_steps: i64 = i64::from_str_radix(&color[..5], 16).map_err(|_| ParseError::unexpected(i + 1, line, &color[..5], "5 hex digits for the steps"))?;
+        let color_dir: u32 = u32::from_str_radix(&color[5..], 16).map_err(|_| ParseError::unexpected(i + 1, line, &color[5..], "a hex digit for the direction"))?;
+        // 0 means R means East
+        // 1 means D means South
+        // 2 means L means West
+        // 3 means U means North
+        let color_dir = match color_dir {
+            0 => Direction::East,
+            1 => Direction::South,
+            2 => Direction::West,
+            3 => Direction::North,
+            _ => return Err(ParseError::unexpected(i + 1, line, &color[5..], "a direction 0, 1, 2 or 3")),
+        };
+        plan.push([(dir, steps), (color_dir, color_steps)]);
+    }
+    Ok(plan)
+}
 
+pub fn part1(plan: &[[(Direction, i64); 2]]) -> Answer {
     let mut polygon = vec![Point(0, 0)];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        // Not using the color here...
-        let (a, _) = input.split_once('(').unwrap();
-        let (dir, steps) = a.trim().split_once(' ').unwrap();
-        assert_eq!(dir.len(), 1, "Direction needs to be one letter");
-        let dir: Direction = dir.chars().next().unwrap().into();
-        let steps = steps.parse().expect("can't parse step number");
+    // Not using the color here...
+    for &[(dir, steps), _] in plan {
         polygon.push(*polygon.last().unwrap() + dir.delta() * steps);
     }
     assert_eq!(polygon.last(), polygon.first(), "The loop wasn't closed");
@@ -128,10 +133,31 @@ pub fn part1() {
     for w in polygon.windows(2) {
         let (start, end) = (w[0], w[1]);
         area += start.0 * (end.1 - start.1);
-        length += start.0.abs_diff(end.0) + start.1.abs_diff(end.1);
+        length += start.manhattan(&end);
     }
     area += (length / 2) as i64 + 1; // to also include the full boundary
-    println!("The area is {area} square meters");
+    area.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 62.into());
 }
 
 /*
@@ -165,36 +191,9 @@ Digging out this loop and its interior produces a lagoon that can hold an impres
 Convert the hexadecimal color codes into the correct instructions; if the Elves follow this new dig plan, how many cubic meters of lava could the lagoon hold?
 */
 
-#[test]
-pub fn part2() {
-    // idea: do a full readin of the data and then a processing step
-
-    use std::io;
-
+pub fn part2(plan: &[[(Direction, i64); 2]]) -> Answer {
     let mut polygon = vec![Point(0, 0)];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let (_, b) = input.split_once('(').unwrap();
-        // silly elves, lets quickly do the conversion then
-        let color = b.trim_start_matches('#').trim_end_matches(')');
-        let steps: i64 = i64::from_str_radix(&color[..5], 16).expect("Failed to parse color hex code for steps");
-        let dir: u32 = u32::from_str_radix(&color[5..], 16).expect("Failed to parse color hex code for direction");
-        // 0 means R means East
-        // 1 means D means South
-        // 2 means L means West
-        // 3 means U means North
-        let dir = match dir {
-            0 => Direction::East,
-            1 => Direction::South,
-            2 => Direction::West,
-            3 => Direction::North,
-            _ => panic!("invalid direction {dir}"),
-        };
+    for &[_, (dir, steps)] in plan {
         polygon.push(*polygon.last().unwrap() + dir.delta() * steps);
     }
     assert_eq!(polygon.last(), polygon.first(), "The loop wasn't closed");
@@ -203,8 +202,22 @@ pub fn part2() {
     for w in polygon.windows(2) {
         let (start, end) = (w[0], w[1]);
         area += (start.0 as i128) * ((end.1 - start.1) as i128);
-        length += start.0.abs_diff(end.0) + start.1.abs_diff(end.1);
+        length += start.manhattan(&end);
     }
     area += (length / 2) as i128 + 1; // to also include the full boundary
-    println!("The area is {area} square meters");
-}
\ No newline at end of file
+    area.into()
+}
+
+#[test]
+pub fn example_part2() {
+    use Direction::*;
+    let plan = parse(EXAMPLE).unwrap();
+    let colors: Vec<_> = plan.iter().map(|[_, color]| *color).collect();
+    assert_eq!(colors, [
+        (East, 461937), (South, 56407), (East, 356671), (South, 863240),
+        (East, 367720), (South, 266681), (West, 577262), (North, 829975),
+        (West, 112010), (South, 829975), (West, 491645), (North, 686074),
+        (West, 5411), (North, 500254),
+    ]);
+    assert_eq!(part2(&plan), 952408144115i64.into());
+}
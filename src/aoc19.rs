@@ -56,12 +56,250 @@ Ultimately, three parts are accepted. Adding up the x, m, a, and s rating for ea
 Sort through all of the parts you've been given; what do you get if you add together all of the rating numbers for all of the parts that ultimately get accepted?
 */
 
-use std::cmp::Ordering;
-use std::ops::*;
-use std::time::*;
-use std::collections::HashMap;
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 19;
+    const TITLE: &'static str = "Aplenty";
+    type Input = System;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+use crate::hyperbox::{BoxSet, HyperBox};
+use crate::interval::IntervalSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// The workflows are a small language: `name{a<2006:qkq,m>2090:A,rfg}`.
+// A line is split into tokens first, which remember where they are in the input,
+// so the parser and the checks after it can point at the exact place of a problem.
+
+/// a place in the input: the line and the columns start..end, all starting at 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// an error, which points at the start of the span.
+    pub fn error(self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, message).at_column(self.start)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Name,
+    Number(u32),
+    Less,
+    Greater,
+    Equal,
+    Colon,
+    Comma,
+    Open,
+    Close,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+/// split a line into tokens. Names are made of letters, numbers of digits and spaces are skipped.
+pub fn lex(line_number: usize, line: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = vec![];
+    // (column - 1, (byte index, char))
+    let mut chars = line.char_indices().enumerate().peekable();
+    while let Some((column, (i, c))) = chars.next() {
+        let mut end = i + c.len_utf8();
+        let mut end_column = column + 1;
+        let kind = match c {
+            '<' => TokenKind::Less,
+            '>' => TokenKind::Greater,
+            '=' => TokenKind::Equal,
+            ':' => TokenKind::Colon,
+            ',' => TokenKind::Comma,
+            '{' => TokenKind::Open,
+            '}' => TokenKind::Close,
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_alphabetic() || c.is_ascii_digit() => {
+                // the rest of the word
+                while let Some(&(column, (j, d))) = chars.peek() {
+                    if d.is_ascii_alphabetic() != c.is_ascii_alphabetic() || !d.is_ascii_alphanumeric() {
+                        break;
+                    }
+                    end = j + d.len_utf8();
+                    end_column = column + 1;
+                    chars.next();
+                }
+                if c.is_ascii_alphabetic() {
+                    TokenKind::Name
+                }
+                else {
+                    TokenKind::Number(parse_number(line_number, line, &line[i..end])?)
+                }
+            },
+            c => return Err(ParseError::new(line_number, format!("unexpected character '{c}'")).at_column(column + 1)),
+        };
+        tokens.push(Token { kind, text: &line[i..end], span: Span { line: line_number, start: column + 1, end: end_column + 1 } });
+    }
+    Ok(tokens)
+}
+
+/// recursive descent parser for the tokens of one line.
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    /// the place right after the line, for errors about a line that ends too early.
+    end: Span,
+}
+
+impl<'a> Parser<'a> {
+    fn new(line_number: usize, line: &'a str) -> Result<Self, ParseError> {
+        let column = line.chars().count() + 1;
+        Ok(Parser { tokens: lex(line_number, line)?, position: 0, end: Span { line: line_number, start: column, end: column } })
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => token.span.error(format!("unexpected \"{}\"", token.text)).expecting(expected),
+            None => self.end.error("unexpected end").expecting(expected),
+        }
+    }
+
+    /// the next token, if it is of the given kind.
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<Token<'a>, ParseError> {
+        match self.peek() {
+            Some(token) if std::mem::discriminant(&token.kind) == std::mem::discriminant(&kind) => {
+                self.position += 1;
+                Ok(token)
+            },
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn finish(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(_) => Err(self.unexpected("the end of the line")),
+            None => Ok(()),
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, ParseError> {
+        match self.expect(TokenKind::Number(0), "a number")?.kind {
+            TokenKind::Number(n) => Ok(n),
+            _ => unreachable!(),
+        }
+    }
+
+    /// one of x, m, a, s as an index.
+    fn rating(&mut self) -> Result<(u8, Token<'a>), ParseError> {
+        match self.peek().and_then(|token| Some((char_to_index(token.text)?, token))) {
+            Some(rating) => {
+                self.position += 1;
+                Ok(rating)
+            },
+            None => Err(self.unexpected("one of x, m, a, s")),
+        }
+    }
+
+    /// `{a<2006:qkq,m>2090:A,rfg}`, the last rule has no condition.
+    fn rules(&mut self) -> Result<Workflow, ParseError> {
+        self.expect(TokenKind::Open, "{")?;
+        let mut workflow = Workflow { conditions: vec![], next: vec![], span: Span::default(), rule_spans: vec![] };
+        loop {
+            let first = self.expect(TokenKind::Name, "a rule")?;
+            let condition = match self.peek().map(|token| token.kind) {
+                Some(TokenKind::Less | TokenKind::Greater | TokenKind::Equal) => {
+                    self.position -= 1;
+                    let (c, _) = self.rating()?;
+                    let op = self.expect(TokenKind::Less, "<, > or =").or_else(|_| self.expect(TokenKind::Greater, "<, > or ="))
+                        .or_else(|_| self.expect(TokenKind::Equal, "<, > or ="))?;
+                    let value = self.number()?;
+                    self.expect(TokenKind::Colon, ":")?;
+                    match op.kind {
+                        TokenKind::Less => Condition::Less(c, value),
+                        TokenKind::Greater => Condition::Greater(c, value),
+                        _ => Condition::Equal(c, value),
+                    }
+                },
+                _ => {
+                    self.position -= 1;
+                    Condition::True
+                },
+            };
+            let target = self.expect(TokenKind::Name, "the name of a workflow, A or R")?;
+            workflow.conditions.push(condition);
+            workflow.next.push(target.text.to_string());
+            workflow.rule_spans.push(Span { end: target.span.end, ..first.span });
+            let end = if let Condition::True = condition {
+                self.expect(TokenKind::Close, "}")?
+            }
+            else {
+                self.expect(TokenKind::Comma, ",")?;
+                continue;
+            };
+            workflow.span.end = end.span.end;
+            return Ok(workflow);
+        }
+    }
+
+    /// `px{a<2006:qkq,m>2090:A,rfg}`
+    fn workflow(&mut self) -> Result<(String, Workflow), ParseError> {
+        let name = self.expect(TokenKind::Name, "the name of a workflow")?;
+        let mut workflow = self.rules()?;
+        workflow.span.line = name.span.line;
+        workflow.span.start = name.span.start;
+        self.finish()?;
+        Ok((name.text.to_string(), workflow))
+    }
+
+    /// `{x=787,m=2655,a=1222,s=2876}`
+    fn part(&mut self) -> Result<Part, ParseError> {
+        self.expect(TokenKind::Open, "{")?;
+        let mut xmas = [None; 4];
+        loop {
+            let (c, token) = self.rating()?;
+            if xmas[c as usize].is_some() {
+                return Err(token.span.error(format!("{} is given twice", token.text)));
+            }
+            self.expect(TokenKind::Equal, "=")?;
+            xmas[c as usize] = Some(self.number()?);
+            if self.expect(TokenKind::Comma, ",").is_err() {
+                break;
+            }
+        }
+        let close = self.expect(TokenKind::Close, ", or }")?;
+        self.finish()?;
+        let missing = |c| close.span.error(format!("no {c} found"));
+        let [x, m, a, s] = xmas;
+        Ok(Part { x: x.ok_or_else(|| missing('x'))?, m: m.ok_or_else(|| missing('m'))?, a: a.ok_or_else(|| missing('a'))?, s: s.ok_or_else(|| missing('s'))? })
+    }
+}
 
 // a general type that captures all the conditions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Condition {
     Less(u8, u32),
     Equal(u8, u32),
@@ -78,11 +316,74 @@ impl Condition {
             Condition::True => true,
         }
     }
+
+    /// the ratings from 1 to 4000, for which the condition holds.
+    pub fn region(&self) -> HyperBox<4> {
+        let mut region = HyperBox::new([1; 4], [4001; 4]);
+        match *self {
+            Condition::Less(c, value) => region.end[c as usize] = value as i64,
+            Condition::Greater(c, value) => region.start[c as usize] = value as i64 + 1,
+            Condition::Equal(c, value) => {
+                region.start[c as usize] = value as i64;
+                region.end[c as usize] = value as i64 + 1;
+            },
+            Condition::True => (),
+        }
+        region
+    }
+
+    /// split the ratings into the ones, for which the condition holds, and the rest.
+    pub fn split(&self, ratings: &[IntervalSet<u32>; 4]) -> ([IntervalSet<u32>; 4], [IntervalSet<u32>; 4]) {
+        let mut holds = ratings.clone();
+        let mut rest = ratings.clone();
+        match *self {
+            Condition::Equal(c, value) => {
+                holds[c as usize].retain(value..=value);
+                rest[c as usize].remove(value..=value);
+            },
+            Condition::Less(c, value) => {
+                holds[c as usize].remove(value..);
+                rest[c as usize].remove(..value);
+            },
+            Condition::Greater(c, value) => {
+                holds[c as usize].remove(..=value);
+                rest[c as usize].retain(..=value);
+            },
+            Condition::True => rest = std::array::from_fn(|_| IntervalSet::new()),
+        }
+        (holds, rest)
+    }
 }
 
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (c, op, value) = match *self {
+            Condition::Less(c, value) => (c, '<', value),
+            Condition::Greater(c, value) => (c, '>', value),
+            Condition::Equal(c, value) => (c, '=', value),
+            Condition::True => return write!(f, "true"),
+        };
+        write!(f, "{}{op}{value}", ["x", "m", "a", "s"][c as usize])
+    }
+}
+
+/// all ratings from 1 to 4000.
+pub fn all_ratings() -> [IntervalSet<u32>; 4] {
+    std::array::from_fn(|_| IntervalSet::from(1..=4000))
+}
+
+fn is_empty_region(ratings: &[IntervalSet<u32>; 4]) -> bool {
+    ratings.iter().any(IntervalSet::is_empty)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Workflow {
     pub conditions: Vec<Condition>,
     pub next: Vec<String>,
+    /// the whole workflow including the name, default for workflows, which weren't parsed.
+    pub span: Span,
+    /// the span of each rule, like `a<2006:qkq`.
+    pub rule_spans: Vec<Span>,
 }
 
 impl Workflow {
@@ -94,43 +395,44 @@ impl Workflow {
         }
         None
     }
+
+    pub fn remove_rule(&mut self, rule: usize) {
+        self.conditions.remove(rule);
+        self.next.remove(rule);
+        if rule < self.rule_spans.len() {
+            self.rule_spans.remove(rule);
+        }
+    }
+
+    /// keep only the first `len` rules.
+    pub fn truncate(&mut self, len: usize) {
+        self.conditions.truncate(len);
+        self.next.truncate(len);
+        self.rule_spans.truncate(len);
+    }
+
+    /// the span of a rule, or of the workflow if the rule doesn't have one.
+    pub fn rule_span(&self, rule: usize) -> Span {
+        self.rule_spans.get(rule).copied().unwrap_or(self.span)
+    }
 }
 
-fn char_to_index(xmas_char: char) -> u8 {
-    "xmas".find(xmas_char).expect("invalid char (not from xmas)") as u8
+fn char_to_index(xmas_char: &str) -> Option<u8> {
+    ["x", "m", "a", "s"].iter().position(|c| *c == xmas_char).map(|i| i as u8)
 }
 
-impl From<&str> for Workflow {
-    fn from(value: &str) -> Self {
-        let value = value.trim_end_matches('}').trim_start_matches('{');
-        let mut conditions = vec![];
-        let mut next = vec![];
-        for instruction in value.split(',') {
-            if let Some((condition, next_name)) = instruction.split_once(':') {
-                if let Some((var, value)) = condition.split_once('<') {
-                    conditions.push(Condition::Less(char_to_index(var.chars().next().unwrap()), value.parse().expect("failed to parse number")));
-                }
-                else if let Some((var, value)) = condition.split_once('>') {
-                    conditions.push(Condition::Greater(char_to_index(var.chars().next().unwrap()), value.parse().expect("failed to parse number")));
-                }
-                else if let Some((var, value)) = condition.split_once('=') {
-                    conditions.push(Condition::Equal(char_to_index(var.chars().next().unwrap()), value.parse().expect("failed to parse number")));
-                }
-                else {
-                    panic!("The condition wasn't <, >, =");
-                }
-                next.push(next_name.to_string());
-            }
-            else {
-                // end condition
-                conditions.push(Condition::True);
-                next.push(instruction.to_string());
-            }
-        }
-        Workflow { conditions, next }
+impl TryFrom<&str> for Workflow {
+    type Error = ParseError;
+    /// the rules of a workflow without the name, like `{a<2006:qkq,m>2090:A,rfg}`.
+    fn try_from(text: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser::new(0, text)?;
+        let workflow = parser.rules()?;
+        parser.finish()?;
+        Ok(workflow)
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Part {
     pub x: u32,
     pub m: u32,
@@ -150,62 +452,401 @@ impl Part {
     }
 }
 
-impl From<&str> for Part {
-    fn from(value: &str) -> Self {
-        let value = value.trim_end_matches('}').trim_start_matches('{');
-        let xmas: Vec<_> = value.split(',').collect();
-        let x = xmas.iter().find_map(|eq| eq.split_once('=').map_or(None, |(a, b)| if a == "x" { Some(b.parse::<u32>().expect("couldn't parse number")) } else { None })).expect("no x found");
-        let m = xmas.iter().find_map(|eq| eq.split_once('=').map_or(None, |(a, b)| if a == "m" { Some(b.parse::<u32>().expect("couldn't parse number")) } else { None })).expect("no m found");
-        let a = xmas.iter().find_map(|eq| eq.split_once('=').map_or(None, |(a, b)| if a == "a" { Some(b.parse::<u32>().expect("couldn't parse number")) } else { None })).expect("no a found");
-        let s = xmas.iter().find_map(|eq| eq.split_once('=').map_or(None, |(a, b)| if a == "s" { Some(b.parse::<u32>().expect("couldn't parse number")) } else { None })).expect("no s found");
-        Part { x , m , a , s }
+impl TryFrom<&str> for Part {
+    type Error = ParseError;
+    fn try_from(text: &str) -> Result<Self, ParseError> {
+        Parser::new(0, text)?.part()
     }
 }
 
-#[test]
-pub fn part1() {
-    // I call it the xmas algorithmus
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{x={},m={},a={},s={}}}", self.x, self.m, self.a, self.s)
+    }
+}
 
-    use std::io;
+/// the workflows by name and the part ratings.
+pub struct System {
+    pub workflows: HashMap<String, Workflow>,
+    pub parts: Vec<Part>,
+}
 
+/// parse the workflows and parts and check the workflows with [validate].
+/// Problems, which would make the parts go around forever or get lost, are errors, the rest is ignored.
+pub fn parse(input: &str) -> Result<System, ParseError> {
     let mut workflows = HashMap::<String, Workflow>::new();
     let mut parts = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if read_bytes == 0 {
-            break;
+    for (i, line) in input.lines().enumerate() {
+        if line.trim_start().starts_with('{') {
+            parts.push(Parser::new(i + 1, line)?.part()?);
         }
-        let input = input.trim();
-        if input.starts_with('{') {
-            // part
-            parts.push(Part::from(input));
+        else if !line.trim().is_empty() {
+            let (name, workflow) = Parser::new(i + 1, line)?.workflow()?;
+            if let Some(earlier) = workflows.get(&name) {
+                return Err(workflow.span.error(format!("the workflow {name} is already defined in line {}", earlier.span.line)));
+            }
+            workflows.insert(name, workflow);
         }
-        else if input.len() != 0 {
-            // workflow
-            let (name, workflow) = input.split_at(input.find('{').expect("line invalid"));
-            workflows.insert(name.to_string(), Workflow::from(workflow));
+    }
+    if let Some(error) = validate(&workflows).iter().find(|diagnostic| diagnostic.is_error()) {
+        return Err(error.to_error());
+    }
+    Ok(System { workflows, parts })
+}
+
+/// a problem with the workflows, which is found by [validate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// there is no workflow named in, where all parts start.
+    MissingIn,
+    /// a rule sends parts to a workflow, which doesn't exist.
+    UnknownTarget { workflow: String, rule: usize, target: String, span: Span },
+    /// the rules before this one already take all parts, which could get to it.
+    UnreachableRule { workflow: String, rule: usize, span: Span },
+    /// no part can get to the workflow from in.
+    UnusedWorkflow { workflow: String, span: Span },
+    /// the workflows send parts around in a circle, the first workflow is repeated at the end.
+    Cycle { workflows: Vec<String>, span: Span },
+}
+
+impl Diagnostic {
+    /// errors make the parts get lost or go around forever, the others are only warnings.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Diagnostic::MissingIn | Diagnostic::UnknownTarget { .. } | Diagnostic::Cycle { .. })
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Diagnostic::MissingIn => Span::default(),
+            Diagnostic::UnknownTarget { span, .. } | Diagnostic::UnreachableRule { span, .. }
+            | Diagnostic::UnusedWorkflow { span, .. } | Diagnostic::Cycle { span, .. } => *span,
         }
     }
 
-    let mut sum = 0;
-    for part in parts {
-        let mut workflow_name = "in";
-        loop {
-            let workflow = workflows.get(workflow_name).unwrap_or_else(|| panic!("no workflow named {}", workflow_name));
-            workflow_name = workflow.next(&part).unwrap();
-            if workflow_name == "R" {
-                // reject
-                break;
+    /// the diagnostic as an error pointing at its place in the input, which can be annotated with the input.
+    pub fn to_error(&self) -> ParseError {
+        let message = match self {
+            Diagnostic::MissingIn => "there is no workflow named in".to_string(),
+            Diagnostic::UnknownTarget { target, .. } => format!("the workflow {target} is used, but never defined"),
+            Diagnostic::UnreachableRule { workflow, rule, .. } => format!("rule {} of {workflow} never applies, the rules before it take all parts", rule + 1),
+            Diagnostic::UnusedWorkflow { workflow, .. } => format!("no part can get to the workflow {workflow}"),
+            Diagnostic::Cycle { workflows, .. } => format!("the parts can go around in a cycle {}", workflows.join(" -> ")),
+        };
+        let span = self.span();
+        ParseError::new(span.line, message).at_column(span.start)
+    }
+}
+
+/// check the workflows for unknown targets, rules which never apply, workflows which are never used and cycles.
+/// The diagnostics are sorted by their place in the input.
+pub fn validate(workflows: &HashMap<String, Workflow>) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    // go through the workflows in the order of the input, so the cycles are always found the same way
+    let names = input_order(workflows);
+    // the rules which can apply for some ratings, and where they send the parts
+    let mut targets = HashMap::<&str, Vec<&str>>::new();
+    for &name in &names {
+        let workflow = &workflows[name];
+        let mut rest = all_ratings();
+        for (rule, (condition, next)) in workflow.conditions.iter().zip(&workflow.next).enumerate() {
+            if !matches!(next.as_str(), "A" | "R") && !workflows.contains_key(next) {
+                let span = workflow.rule_span(rule);
+                let target_span = Span { start: span.end.saturating_sub(next.chars().count()).max(span.start), ..span };
+                diagnostics.push(Diagnostic::UnknownTarget { workflow: name.clone(), rule, target: next.clone(), span: target_span });
             }
-            if workflow_name == "A" {
-                // accept
-                sum += part.x + part.m + part.a + part.s;
+            let (holds, remaining) = condition.split(&rest);
+            if is_empty_region(&holds) {
+                diagnostics.push(Diagnostic::UnreachableRule { workflow: name.clone(), rule, span: workflow.rule_span(rule) });
+            }
+            else if workflows.contains_key(next) {
+                targets.entry(name).or_default().push(next);
+            }
+            rest = remaining;
+        }
+    }
+    if !workflows.contains_key("in") {
+        diagnostics.push(Diagnostic::MissingIn);
+    }
+    else {
+        let mut used = HashSet::from(["in"]);
+        let mut todo = vec!["in"];
+        while let Some(name) = todo.pop() {
+            for &next in targets.get(name).into_iter().flatten() {
+                if used.insert(next) {
+                    todo.push(next);
+                }
+            }
+        }
+        for &name in &names {
+            if !used.contains(name.as_str()) {
+                diagnostics.push(Diagnostic::UnusedWorkflow { workflow: name.clone(), span: workflows[name].span });
+            }
+        }
+    }
+    // depth first search, a cycle is an edge back to a workflow on the current path
+    let mut done = HashSet::new();
+    for &name in &names {
+        let mut path = vec![];
+        find_cycles(name, &targets, &mut path, &mut done, &mut |cycle| {
+            let span = workflows[cycle[0]].span;
+            diagnostics.push(Diagnostic::Cycle { workflows: cycle.iter().map(|name| name.to_string()).collect(), span });
+        });
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span().line, diagnostic.span().start));
+    diagnostics
+}
+
+/// the names of the workflows sorted by their line in the input, and by name if they have the same line.
+fn input_order(workflows: &HashMap<String, Workflow>) -> Vec<&String> {
+    let mut names: Vec<&String> = workflows.keys().collect();
+    names.sort_by_key(|name| (workflows[*name].span.line, *name));
+    names
+}
+
+fn find_cycles<'a>(name: &'a str, targets: &HashMap<&'a str, Vec<&'a str>>, path: &mut Vec<&'a str>, done: &mut HashSet<&'a str>, found: &mut impl FnMut(Vec<&'a str>)) {
+    if let Some(i) = path.iter().position(|&w| w == name) {
+        let mut cycle = path[i..].to_vec();
+        cycle.push(name);
+        found(cycle);
+        return;
+    }
+    if done.contains(name) {
+        return;
+    }
+    path.push(name);
+    for &next in targets.get(name).into_iter().flatten() {
+        find_cycles(next, targets, path, done, found);
+    }
+    path.pop();
+    done.insert(name);
+}
+
+/// a rule which was checked for a part on its way through the workflows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step<'a> {
+    pub workflow: &'a str,
+    pub rule: usize,
+    pub condition: Condition,
+    pub target: &'a str,
+    pub applies: bool,
+}
+
+impl fmt::Display for Step<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = if self.applies { "applies" } else { "doesn't apply" };
+        match self.condition {
+            Condition::True => write!(f, "{} rule {}: {} {result}", self.workflow, self.rule + 1, self.target),
+            condition => write!(f, "{} rule {}: {condition}:{} {result}", self.workflow, self.rule + 1, self.target),
+        }
+    }
+}
+
+/// why a part didn't make it to A or R.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RunError {
+    UnknownWorkflow(String),
+    /// none of the rules of the workflow applied.
+    NoRuleApplies(String),
+    /// the part went through the same workflows again, the first one is repeated at the end.
+    Loop(Vec<String>),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::UnknownWorkflow(name) => write!(f, "no workflow named {name}"),
+            RunError::NoRuleApplies(name) => write!(f, "none of the rules of {name} applies"),
+            RunError::Loop(workflows) => write!(f, "the part goes around forever: {}", workflows.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+/// send the part through the workflows starting at in and return where it ends up, "A" or "R".
+/// Every rule, which is checked on the way, is given to `on_step`.
+pub fn run<'a>(workflows: &'a HashMap<String, Workflow>, part: &Part, mut on_step: impl FnMut(Step<'a>)) -> Result<&'a str, RunError> {
+    let mut name = "in";
+    let mut path = vec![name];
+    loop {
+        let workflow = workflows.get(name).ok_or_else(|| RunError::UnknownWorkflow(name.to_string()))?;
+        let mut next = None;
+        for (rule, (&condition, target)) in workflow.conditions.iter().zip(&workflow.next).enumerate() {
+            let applies = condition.check(part);
+            on_step(Step { workflow: name, rule, condition, target, applies });
+            if applies {
+                next = Some(target.as_str());
                 break;
             }
         }
+        name = next.ok_or_else(|| RunError::NoRuleApplies(name.to_string()))?;
+        if name == "A" || name == "R" {
+            return Ok(name);
+        }
+        if let Some(i) = path.iter().position(|&w| w == name) {
+            let mut workflows: Vec<String> = path[i..].iter().map(|w| w.to_string()).collect();
+            workflows.push(name.to_string());
+            return Err(RunError::Loop(workflows));
+        }
+        path.push(name);
+    }
+}
+
+/// the way of a part through the workflows, rule by rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace<'a> {
+    pub steps: Vec<Step<'a>>,
+    /// "A" or "R"
+    pub result: &'a str,
+}
+
+pub fn trace<'a>(workflows: &'a HashMap<String, Workflow>, part: &Part) -> Result<Trace<'a>, RunError> {
+    let mut steps = vec![];
+    let result = run(workflows, part, |step| steps.push(step))?;
+    Ok(Trace { steps, result })
+}
+
+impl<'a> Trace<'a> {
+    /// the workflows the part went through, ending with A or R.
+    pub fn workflows(&self) -> Vec<&'a str> {
+        let mut workflows: Vec<&str> = self.steps.iter().filter(|step| step.applies).map(|step| step.workflow).collect();
+        workflows.push(self.result);
+        workflows
+    }
+}
+
+impl fmt::Display for Trace<'_> {
+    /// like in the puzzle: in -> qqz -> qs -> lnx -> A
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.workflows().join(" -> "))
+    }
+}
+
+pub fn part1(system: &System) -> Answer {
+    // I call it the xmas algorithmus
+    let System { workflows, parts } = system;
+
+    let mut sum = 0;
+    for part in parts {
+        if run(workflows, part, |_| ()).unwrap_or_else(|err| panic!("{part}: {err}")) == "A" {
+            sum += part.x + part.m + part.a + part.s;
+        }
     }
-    println!("The accepted checksum is {sum}");
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 19114.into());
+}
+
+#[test]
+pub fn example_trace() {
+    let system = parse(EXAMPLE).unwrap();
+    let expected = [
+        "{x=787,m=2655,a=1222,s=2876}: in -> qqz -> qs -> lnx -> A",
+        "{x=1679,m=44,a=2067,s=496}: in -> px -> rfg -> gd -> R",
+        "{x=2036,m=264,a=79,s=2244}: in -> qqz -> hdj -> pv -> A",
+        "{x=2461,m=1339,a=466,s=291}: in -> px -> qkq -> crn -> R",
+        "{x=2127,m=1623,a=2188,s=1013}: in -> px -> rfg -> A",
+    ];
+    for (part, expected) in system.parts.iter().zip(expected) {
+        assert_eq!(format!("{part}: {}", trace(&system.workflows, part).unwrap()), expected);
+    }
+    let steps: Vec<String> = trace(&system.workflows, &system.parts[0]).unwrap().steps.iter().map(|step| step.to_string()).collect();
+    assert_eq!(steps, [
+        "in rule 1: s<1351:px doesn't apply",
+        "in rule 2: qqz applies",
+        "qqz rule 1: s>2770:qs applies",
+        "qs rule 1: s>3448:A doesn't apply",
+        "qs rule 2: lnx applies",
+        "lnx rule 1: m>1548:A applies",
+    ]);
+    // workflows, which weren't checked by parse
+    let workflows = HashMap::from([
+        ("in".to_string(), Workflow::try_from("{x<10:a,b}").unwrap()),
+        ("a".to_string(), Workflow::try_from("{m>5:in,R}").unwrap()),
+        ("b".to_string(), Workflow::try_from("{x>2000:A,c}").unwrap()),
+    ]);
+    let part = |x, m| Part { x, m, a: 1, s: 1 };
+    assert_eq!(run(&workflows, &part(5, 5), |_| ()), Ok("R"));
+    assert_eq!(run(&workflows, &part(5000, 5), |_| ()), Ok("A"));
+    assert_eq!(run(&workflows, &part(5, 6), |_| ()), Err(RunError::Loop(vec!["in".into(), "a".into(), "in".into()])));
+    assert_eq!(run(&workflows, &part(50, 5), |_| ()), Err(RunError::UnknownWorkflow("c".into())));
+}
+
+#[test]
+pub fn parse_errors() {
+    let error = |input: &str| {
+        let err = parse(input).err().unwrap();
+        (err.line, err.column, err.message, err.expected)
+    };
+    let expected = |line, column, message: &str, expected: Option<&str>| (line, column, message.to_string(), expected.map(str::to_string));
+    assert_eq!(error("in{x<10:A,R}\n\n{x=1,m=2,a=3}"), expected(3, 13, "no s found", None));
+    assert_eq!(error("in{x<10:A,R}\n\n{x=1,x=2}"), expected(3, 6, "x is given twice", None));
+    assert_eq!(error("in{x<10:A,R}\n\n{x=1 m=2}"), expected(3, 6, "unexpected \"m\"", Some(", or }")));
+    assert_eq!(error("in{q<10:A,R}"), expected(1, 4, "unexpected \"q\"", Some("one of x, m, a, s")));
+    assert_eq!(error("in{x<10:A,R"), expected(1, 12, "unexpected end", Some("}")));
+    assert_eq!(error("in{x<10:A}"), expected(1, 10, "unexpected \"}\"", Some(",")));
+    assert_eq!(error("in{x<ten:A,R}"), expected(1, 6, "unexpected \"ten\"", Some("a number")));
+    assert_eq!(error("in{x<99999999999:A,R}"), expected(1, 6, "unexpected \"99999999999\"", Some("a number")));
+    assert_eq!(error("in{x#10:A,R}"), expected(1, 5, "unexpected character '#'", None));
+    assert_eq!(error("in{R} x"), expected(1, 7, "unexpected \"x\"", Some("the end of the line")));
+    assert_eq!(error("in{A}\nin{R}"), expected(2, 1, "the workflow in is already defined in line 1", None));
+    assert_eq!(error("in{x<10:A,qq}"), expected(1, 11, "the workflow qq is used, but never defined", None));
+    assert_eq!(error("px{A}"), expected(0, 0, "there is no workflow named in", None));
+    // spaces are fine
+    assert!(parse("in { x < 10 : A , R }\n\n { x = 1, m = 2, a = 3, s = 4 }").is_ok());
+}
+
+#[test]
+pub fn validate_workflows() {
+    let input = "in{x<10:a,x<5:R,b}
+a{m>5:c,A}
+b{s=7:A,s>3:R,s<2:R,a}
+c{a>0:b,in}
+d{R}";
+    let mut workflows = HashMap::new();
+    for (i, line) in input.lines().enumerate() {
+        let (name, workflow) = Parser::new(i + 1, line).unwrap().workflow().unwrap();
+        workflows.insert(name, workflow);
+    }
+    let diagnostics = validate(&workflows);
+    let span = |line, start, end| Span { line, start, end };
+    assert_eq!(diagnostics, [
+        Diagnostic::UnreachableRule { workflow: "in".into(), rule: 1, span: span(1, 11, 16) },
+        Diagnostic::Cycle { workflows: vec!["a".into(), "c".into(), "b".into(), "a".into()], span: span(2, 1, 11) },
+        Diagnostic::UnreachableRule { workflow: "c".into(), rule: 1, span: span(4, 9, 11) },
+        Diagnostic::UnusedWorkflow { workflow: "d".into(), span: span(5, 1, 5) },
+    ]);
+    assert_eq!(diagnostics.iter().filter(|d| d.is_error()).count(), 1);
+    let err = parse(input).err().unwrap();
+    assert_eq!(err.annotate(input), "line 2, column 1: the parts can go around in a cycle a -> c -> b -> a
+  |
+2 | a{m>5:c,A}
+  | ^
+");
+    // the example is fine
+    assert_eq!(validate(&parse(EXAMPLE).unwrap().workflows), []);
 }
 
 /*
@@ -222,8 +863,7 @@ In the above example, there are 167409079868000 distinct combinations of ratings
 Consider only your list of workflows; the list of part ratings that the Elves wanted you to sort is no longer relevant. How many distinct combinations of ratings will be accepted by the Elves' workflows?
 */
 
-#[test]
-pub fn part2() {
+pub fn part2(system: &System) -> Answer {
     // combinatorics again eeh?
     // Idea 1: the part ratings act as a kinda guide in the workflow graph.
     // However, some ratings are equivalent in how they guide the workflow.
@@ -247,35 +887,27 @@ pub fn part2() {
     // as conditions quickly cancel and become unsatisfiable for long paths.
     // This can be already considered while finding all paths!
 
-    use std::io;
-    
-    let start = Instant::now();
-
-    let mut workflows = HashMap::<String, Workflow>::new();
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        if input.starts_with('{') {
-            // part (ignore)
-        }
-        else if input.len() != 0 {
-            // workflow
-            let (name, workflow) = input.split_at(input.find('{').expect("line invalid"));
-            workflows.insert(name.to_string(), Workflow::from(workflow));
-        }
-    }
+    // the parts are ignored
+    //let combinations = representants_approach(&system.workflows); // gave me 116738260946855
+    let combinations = paths_approach(&system.workflows).volume(); // gave me 116738260946855 as well!
 
-    //representants_approach(workflows); // gave me 116738260946855
-    paths_approach(workflows); // gave me 116738260946855 as well!
+    combinations.into()
+}
 
-    println!("Time: {:?}", Instant::now() - start);
+#[test]
+pub fn example_part2() {
+    let system = parse(EXAMPLE).unwrap();
+    assert_eq!(part2(&system), 167409079868000u64.into());
+    assert_eq!(representants_approach(&system.workflows), 167409079868000);
+    assert_eq!(paths_approach(&system.workflows).volume(), 167409079868000);
+    // conditions at 0 can't be met
+    let system = parse("in{x<0:R,m=0:R,a<1:R,A}").unwrap();
+    assert_eq!(representants_approach(&system.workflows), 4000u64.pow(4));
+    assert_eq!(paths_approach(&system.workflows).volume(), 4000u128.pow(4));
 }
 
-pub fn representants_approach(workflows: HashMap<String, Workflow>) {
+/// count the accepted combinations by running every combination of equivalence class representants through the workflows.
+pub fn representants_approach(workflows: &HashMap<String, Workflow>) -> u64 {
     // collect all values for which the next higher value would lead to a change in the condition
     const MAX_VALUE: u32 = 4000; // start counting at 1!
     let mut representants = [vec![MAX_VALUE], vec![MAX_VALUE], vec![MAX_VALUE], vec![MAX_VALUE]];
@@ -283,7 +915,8 @@ pub fn representants_approach(workflows: HashMap<String, Workflow>) {
         for condition in &workflow.conditions {
             match *condition {
                 Condition::Less(c, v) => {
-                    if v <= MAX_VALUE {
+                    // nothing is below 0, so x<0 never changes anything
+                    if (1..=MAX_VALUE).contains(&v) {
                         representants[c as usize].push(v - 1);
                     }
                 }
@@ -294,8 +927,8 @@ pub fn representants_approach(workflows: HashMap<String, Workflow>) {
                 }
                 Condition::Equal(c, v) => {
                     let dst = &mut representants[c as usize];
-                    if v <= MAX_VALUE {
-                        dst.push(v-1);
+                    if (1..=MAX_VALUE).contains(&v) {
+                        dst.push(v - 1);
                     }
                     if v < MAX_VALUE {
                         dst.push(v);
@@ -321,18 +954,16 @@ pub fn representants_approach(workflows: HashMap<String, Workflow>) {
     });
     // For all combinations of these inputs, do the workflow procedure
     // and figure out the exact list of accepted parts (don't save it)
-    println!("checking a list of {} combinations", representants.iter().map(|v| v.len()).product::<usize>());
     let mut combinations = 0;
-    let start = Instant::now();
     // obviously parallelizing with rayon would be awesome here, but I'm going without libraries for this project.
-    for (i, &(x, size_x)) in representants[0].iter().enumerate() {
-        let size = size_x as u128;
+    for &(x, size_x) in representants[0].iter() {
+        let size = size_x as u64;
         for &(m, size_m) in representants[1].iter() {
-            let size = size * size_m as u128;
+            let size = size * size_m as u64;
             for &(a, size_a) in representants[2].iter() {
-                let size = size * size_a as u128;
+                let size = size * size_a as u64;
                 for &(s, size_s) in representants[3].iter() {
-                    let size = size * size_s as u128;
+                    let size = size * size_s as u64;
                     let part = Part { x, m, a, s };
                     let mut workflow_name = "in";
                     loop {
@@ -351,187 +982,291 @@ pub fn representants_approach(workflows: HashMap<String, Workflow>) {
                 }
             }
         }
-        let total = representants[0].len() as u32;
-        let processed = (i+1) as u32;
-        let remaining = total - processed;
-        println!("({processed}/{total}) avg per step: {:?}, remaining: {:?}", (Instant::now() - start) / processed, ((Instant::now() - start) / processed) * remaining);
     }
-    println!("There are {combinations} combinations");
+    combinations
 }
 
-
-/// Create a range, if it is not empty, otherwise return None
-pub fn checked_range<T: Ord>(start: T, end: T) -> Option<Range<T>> {
-    if start < end {
-        Some(start..end)
+/// the boxes of ratings (x, m, a, s), which are allowed by the ranges of a path condition.
+pub fn condition_boxes(condition: &[IntervalSet<u32>; 4]) -> Vec<HyperBox<4>> {
+    let mut boxes = vec![HyperBox::new([0; 4], [0; 4])];
+    for (d, ranges) in condition.iter().enumerate() {
+        boxes = boxes.iter().flat_map(|b| ranges.ranges().map(move |range| {
+            let mut b = *b;
+            b.start[d] = *range.start() as i64;
+            b.end[d] = *range.end() as i64 + 1;
+            b
+        })).collect();
     }
-    else {
-        None
+    boxes
+}
+
+/// follow all paths from the workflow `start` and collect the ratings that reach each workflow
+/// (and "A" and "R") in `regions`. The path condition holds the ratings which reach `start`.
+pub fn find_paths(workflows: &HashMap<String, Workflow>, start: &str, mut path_condition: [IntervalSet<u32>; 4], regions: &mut HashMap<String, Vec<HyperBox<4>>>) {
+    regions.entry(start.to_string()).or_default().extend(condition_boxes(&path_condition));
+    let Some(workflow) = workflows.get(start) else {
+        // "A" or "R"
+        return;
+    };
+    for (cond, next) in workflow.conditions.iter().zip(&workflow.next) {
+        // make a new path condition by combining the new condition with the existing path
+        let (path_condition2, path_anti_condition) = cond.split(&path_condition);
+        if !is_empty_region(&path_condition2) {
+            find_paths(workflows, next, path_condition2, regions);
+        }
+        if is_empty_region(&path_anti_condition) {
+            break;
+        }
+        path_condition = path_anti_condition;
     }
 }
 
-/// Subtract a range from this one.
-/// This can result in up to 2 ranges, which are returned in order.
-pub fn sub_range<T: Ord + Clone>(lhs: Range<T>, rhs: Range<T>) ->[Option<Range<T>>; 2] {
-    [checked_range(lhs.start.clone(), rhs.start.min(lhs.end.clone())),
-        checked_range(rhs.end.max(lhs.start), lhs.end)]
+/// the ratings which go through each of the workflows, "A" and "R" included.
+/// important insight:
+/// if two parts have gone different paths, they have different ratings!
+pub fn workflow_regions(workflows: &HashMap<String, Workflow>) -> HashMap<String, BoxSet<4>> {
+    let mut regions = HashMap::new();
+    find_paths(workflows, "in", all_ratings(), &mut regions);
+    // two parts, which went different paths, have different ratings, so the boxes are disjoint
+    regions.into_iter().map(|(name, boxes)| (name, BoxSet::from_disjoint(boxes))).collect()
 }
 
-#[derive(Clone)]
-pub struct RangeVec<T: Ord> {
-    ranges: Vec<Range<T>>,
+/// the region of all accepted ratings, found by following all paths to "A".
+pub fn paths_approach(workflows: &HashMap<String, Workflow>) -> BoxSet<4> {
+    workflow_regions(workflows).remove("A").unwrap_or_default()
 }
 
-impl<T: Ord> RangeVec<T> {
-    pub fn contains(&self, value: &T) -> bool {
-        let start = match self.ranges.binary_search_by(|x| if &x.end <= value { Ordering::Less } else { Ordering::Greater }) {
-            Ok(index) => index,
-            Err(index) => index,
-        };
-        self.ranges.get(start).map_or(false, |range| range.contains(value))
+#[test]
+pub fn accepted_region() {
+    let system = parse(EXAMPLE).unwrap();
+    let regions = workflow_regions(&system.workflows);
+    let all = 4000u128.pow(4);
+    assert_eq!(regions["in"].volume(), all);
+    assert_eq!(regions["A"].volume() + regions["R"].volume(), all);
+    assert!(regions["A"].intersection(&regions["R"]).is_empty());
+    // the accepted parts from part 1 are the ones in the accepted region
+    let sum: u32 = system.parts.iter()
+        .filter(|part| regions["A"].contains(&[part.x, part.m, part.a, part.s].map(|v| v as i64)))
+        .map(|part| part.x + part.m + part.a + part.s).sum();
+    assert_eq!(sum, 19114);
+    // how many accepted combinations have x > 2000
+    let accepted = paths_approach(&system.workflows);
+    let x_above_2000 = BoxSet::from(HyperBox::from_ranges([2001..4001, 1..4001, 1..4001, 1..4001]));
+    let mut start = all_ratings();
+    start[0] = IntervalSet::from(2001..=4000);
+    let mut expected = HashMap::new();
+    find_paths(&system.workflows, "in", start, &mut expected);
+    assert_eq!(accepted.intersection(&x_above_2000).volume(), expected["A"].iter().map(HyperBox::volume).sum());
+    // which workflows overlap: in splits by s, so px and qqz never see the same part, but lnx only gets parts from qs
+    assert!(regions["px"].intersection(&regions["qqz"]).is_empty());
+    assert_eq!(regions["lnx"].difference(&regions["qs"]), BoxSet::new());
+}
+
+
+// The workflows form a graph, where every rule is an edge to the next workflow.
+// It can be drawn with graphviz and made smaller with the same range analysis as in find_paths,
+// without changing where any of the parts end up.
+
+/// the workflows as a graphviz digraph with an edge for every rule, labelled with its condition.
+/// The workflows are in the order of the input.
+pub fn to_dot(workflows: &HashMap<String, Workflow>) -> String {
+    let mut dot = "digraph workflows {\n".to_string();
+    dot += "    \"in\" [shape=doublecircle];\n";
+    dot += "    \"A\" [shape=box, color=green];\n";
+    dot += "    \"R\" [shape=box, color=red];\n";
+    for name in input_order(workflows) {
+        let workflow = &workflows[name];
+        for (rule, (condition, next)) in workflow.conditions.iter().zip(&workflow.next).enumerate() {
+            let label = match condition {
+                Condition::True if rule == 0 => String::new(),
+                Condition::True => " [label=\"else\"]".to_string(),
+                condition => format!(" [label=\"{condition}\"]"),
+            };
+            dot += &format!("    \"{name}\" -> \"{next}\"{label};\n");
+        }
     }
+    dot += "}\n";
+    dot
 }
 
-impl<T: Ord + Clone> RangeVec<T> {
-    pub fn remove_range<B: RangeBounds<T>>(&mut self, range: B) {
-        // find the range in last_locations, which gets mapped
-        let start = match range.start_bound() {
-            Bound::Included(start) => {
-                Some((match self.ranges.binary_search_by(|x| if &x.end <= start { Ordering::Less } else { Ordering::Greater }) {
-                    Ok(index) => index,
-                    Err(index) => index,
-                }, start.clone()))
-            },
-            Bound::Unbounded => None,
-            _ => panic!("unimplemented bound type"),
-        };
-        let end = match range.end_bound() {
-            Bound::Excluded(end) => {
-                Some((match self.ranges.binary_search_by(|x| if &x.start < end { Ordering::Less } else { Ordering::Greater }) {
-                    Ok(index) => index,
-                    Err(index) => index,
-                }, end.clone()))
-            },
-            Bound::Unbounded => None,
-            _ => panic!("unimplemented bound type"),
+/// make the workflows smaller, until none of these apply anymore:
+/// - rules, which never apply to the parts that get to the workflow, are dropped,
+///   and a rule which always applies becomes the last one.
+/// - rules at the end, which go to the same place as the last rule, are dropped.
+/// - workflows with only one rule are skipped, everything goes to their target directly.
+/// - a workflow, which is only used by the last rule of another workflow, is inlined there.
+/// - workflows, which no part gets to, are removed.
+pub fn simplify(workflows: &HashMap<String, Workflow>) -> HashMap<String, Workflow> {
+    let mut workflows = workflows.clone();
+    loop {
+        let mut changed = drop_conditions(&mut workflows);
+        changed |= merge_last_rules(&mut workflows);
+        changed |= skip_single_rules(&mut workflows);
+        changed |= inline_single_use(&mut workflows);
+        changed |= remove_unused(&mut workflows);
+        if !changed {
+            return workflows;
+        }
+    }
+}
+
+fn drop_conditions(workflows: &mut HashMap<String, Workflow>) -> bool {
+    let regions = workflow_regions(workflows);
+    let mut changed = false;
+    for (name, workflow) in workflows.iter_mut() {
+        // workflows which no part gets to are removed later
+        let Some(mut region) = regions.get(name).cloned() else {
+            continue;
         };
-        // 4 possibilities now
-        if let Some((start, start_elem)) = start {
-            if let Some((end, end_elem)) = end {
-                let range = start_elem..end_elem;
-                // remove all ranges in this region and check the first and last
-                let mut removed_ranges = self.ranges.drain(start..end);
-                let mut splits = vec![];
-                if let Some(removed) = removed_ranges.next() {
-                    splits.push(sub_range(removed, range.clone()));
-                }
-                if let Some(removed) = removed_ranges.last() {
-                    splits.push(sub_range(removed, range));
-                }
-                // put the splits back in
-                self.ranges.splice(start..start, splits.into_iter().flatten().filter_map(|i| i));
+        let mut rule = 0;
+        while rule < workflow.conditions.len() {
+            let condition = BoxSet::from(workflow.conditions[rule].region());
+            if workflow.conditions[rule] != Condition::True && region.intersection(&condition).is_empty() {
+                // always false
+                workflow.remove_rule(rule);
+                changed = true;
+                continue;
             }
-            else {
-                if start < self.ranges.len() {
-                    let start_ref = &mut self.ranges[start].end;
-                    if *start_ref > start_elem {
-                        *start_ref = start_elem;
-                    }
-                    if self.ranges[start].start == self.ranges[start].end {
-                        self.ranges.drain(start..);
-                    }
-                    else {
-                        self.ranges.drain(start+1..);
-                    }
-                }
+            region = region.difference(&condition);
+            if region.is_empty() {
+                // always true, so the rest is never used
+                changed |= workflow.conditions[rule] != Condition::True || rule + 1 < workflow.conditions.len();
+                workflow.conditions[rule] = Condition::True;
+                workflow.truncate(rule + 1);
+                break;
             }
+            rule += 1;
         }
-        else {
-            if let Some((end, end_elem)) = end {
-                if end > 0 {
-                    let end_ref = &mut self.ranges[end-1].start;
-                    if *end_ref < end_elem {
-                        *end_ref = end_elem;
-                    }
-                    if self.ranges[end-1].start == self.ranges[end-1].end {
-                        self.ranges.drain(..end);
-                    }
-                    else {
-                        self.ranges.drain(..end-1);
-                    }
-                }
-            }
-            else {
-                // remove everything (clear)
-                self.ranges.clear();
+    }
+    changed
+}
+
+fn merge_last_rules(workflows: &mut HashMap<String, Workflow>) -> bool {
+    let mut changed = false;
+    for workflow in workflows.values_mut() {
+        while let [.., second_last, last] = &workflow.next[..] {
+            if second_last != last {
+                break;
             }
+            workflow.remove_rule(workflow.next.len() - 2);
+            changed = true;
         }
     }
+    changed
 }
-impl<T: Ord + Default> RangeVec<T>
-where for<'a> &'a T: Add<Output = T> + Sub<Output = T> {
-    pub fn volume(&self) -> T {
-        self.ranges.iter().map(|range| &range.end - &range.start).reduce(|sum, len| &sum + &len).unwrap_or_default()
+
+fn skip_single_rules(workflows: &mut HashMap<String, Workflow>) -> bool {
+    // in is where the parts start, so it always stays
+    let single: HashMap<String, String> = workflows.iter()
+        .filter(|(name, workflow)| *name != "in" && workflow.next.len() == 1)
+        .map(|(name, workflow)| (name.clone(), workflow.next[0].clone()))
+        .collect();
+    let mut changed = false;
+    for workflow in workflows.values_mut() {
+        for next in &mut workflow.next {
+            // follow chains of single rules, but not around in a cycle
+            for _ in 0..single.len() {
+                let Some(target) = single.get(next) else {
+                    break;
+                };
+                *next = target.clone();
+                changed = true;
+            }
+        }
     }
+    changed
 }
 
-impl<T: Ord> From<Range<T>> for RangeVec<T> {
-    fn from(value: Range<T>) -> Self {
-        RangeVec { ranges: vec![value] }
+fn inline_single_use(workflows: &mut HashMap<String, Workflow>) -> bool {
+    let mut changed = false;
+    loop {
+        let mut uses = HashMap::<&str, usize>::new();
+        for workflow in workflows.values() {
+            for next in &workflow.next {
+                *uses.entry(next).or_default() += 1;
+            }
+        }
+        // a workflow can only be put in place of a rule without condition, as there is no "and"
+        let inline = input_order(workflows).into_iter().find_map(|name| {
+            let target = workflows[name].next.last()?;
+            (uses.get(target.as_str()) == Some(&1) && target != name && target != "in" && workflows.contains_key(target))
+                .then(|| (name.clone(), target.clone()))
+        });
+        let Some((name, target)) = inline else {
+            return changed;
+        };
+        let inlined = workflows.remove(&target).unwrap();
+        let workflow = workflows.get_mut(&name).unwrap();
+        workflow.remove_rule(workflow.next.len() - 1);
+        workflow.conditions.extend(inlined.conditions);
+        workflow.next.extend(inlined.next);
+        workflow.rule_spans.extend(inlined.rule_spans);
+        changed = true;
     }
 }
 
-pub fn find_paths(workflows: &HashMap<String, Workflow>, start: &str, mut path_condition: [RangeVec<u32>; 4], paths: &mut Vec<[RangeVec<u32>; 4]>) {
-    let workflow = workflows.get(start).unwrap();
-    for (cond, next) in workflow.conditions.iter().zip(&workflow.next) {
-        // make a new path condition by combining the new condition with the existing path
-        let mut path_condition2 = path_condition.clone();
-        let mut path_anti_condition = path_condition.clone();
-        match *cond {
-            Condition::Equal(c, value) => {
-                let pcond = &mut path_condition2[c as usize];
-                let anti = &mut path_anti_condition[c as usize];
-                *pcond = (value..value+1).into();
-                anti.remove_range(value..value+1);
-            },
-            Condition::Less(c, value) => {
-                let pcond = &mut path_condition2[c as usize];
-                let anti = &mut path_anti_condition[c as usize];
-                pcond.remove_range(value..);
-                anti.remove_range(..value);
-            },
-            Condition::Greater(c, value) => {
-                let pcond = &mut path_condition2[c as usize];
-                let anti = &mut path_anti_condition[c as usize];
-                pcond.remove_range(..value+1);
-                anti.remove_range(value+1..);
-            },
-            Condition::True => (),
-        }
-        if path_condition2.iter().map(|cond| cond.volume() as u128).product::<u128>() > 0 {
-            if next == "A" {
-                paths.push(path_condition2.clone());
-            }
-            else if next != "R" {
-                find_paths(workflows, next, path_condition2.clone(), paths);
+fn remove_unused(workflows: &mut HashMap<String, Workflow>) -> bool {
+    let mut used = HashSet::from(["in".to_string()]);
+    let mut todo = vec!["in".to_string()];
+    while let Some(name) = todo.pop() {
+        for next in workflows.get(&name).into_iter().flat_map(|workflow| &workflow.next) {
+            if used.insert(next.clone()) {
+                todo.push(next.clone());
             }
         }
-        if let Condition::True = cond {
-            break;
-        }
-        path_condition = path_anti_condition;
     }
+    let count = workflows.len();
+    workflows.retain(|name, _| used.contains(name));
+    workflows.len() != count
 }
 
-pub fn paths_approach(workflows: HashMap<String, Workflow>) {
-    // find all paths from "in" to "A", which are possible for a part
-    let mut paths = vec![];
-    find_paths(&workflows, "in", [(1..4001).into(), (1..4001).into(), (1..4001).into(), (1..4001).into()], &mut paths);
-    // important insight:
-    // if two parts have gone different paths, they have different ratings!
-    // Therefore we can do the following sum without double counting:
-    let combinations = paths.iter().map(|path_condition| {
-        path_condition.iter().map(|cond| cond.volume() as u128).product::<u128>()
-    }).sum::<u128>();
-    println!("There are {combinations} combinations");
-}
\ No newline at end of file
+#[test]
+pub fn example_graph() {
+    let system = parse(EXAMPLE).unwrap();
+    let simplified = simplify(&system.workflows);
+    // gd, lnx and qs send everything to the same place, the others are inlined
+    assert_eq!(to_dot(&simplified), r#"digraph workflows {
+    "in" [shape=doublecircle];
+    "A" [shape=box, color=green];
+    "R" [shape=box, color=red];
+    "px" -> "qkq" [label="a<2006"];
+    "px" -> "A" [label="m>2090"];
+    "px" -> "R" [label="s<537"];
+    "px" -> "R" [label="x>2440"];
+    "px" -> "A" [label="else"];
+    "qkq" -> "A" [label="x<1416"];
+    "qkq" -> "A" [label="x>2662"];
+    "qkq" -> "R" [label="else"];
+    "in" -> "px" [label="s<1351"];
+    "in" -> "A" [label="s>2770"];
+    "in" -> "hdj" [label="m<1801"];
+    "in" -> "R" [label="else"];
+    "hdj" -> "A" [label="m>838"];
+    "hdj" -> "R" [label="a>1716"];
+    "hdj" -> "A" [label="else"];
+}
+"#);
+    assert_eq!(paths_approach(&simplified).volume(), 167409079868000);
+    for part in &system.parts {
+        assert_eq!(run(&simplified, part, |_| ()), run(&system.workflows, part, |_| ()));
+    }
+}
+
+#[test]
+pub fn simplify_input() {
+    let input = std::fs::read_to_string("res/aoc19.txt").unwrap();
+    let system = parse(&input).unwrap();
+    let simplified = simplify(&system.workflows);
+    assert!(simplified.len() < system.workflows.len());
+    assert_eq!(validate(&simplified), []);
+    assert_eq!(paths_approach(&simplified).volume(), paths_approach(&system.workflows).volume());
+    // every part still ends up in the same place
+    let mut random = crate::Random::new(19);
+    let mut random = || random.below(4000) as u32 + 1;
+    for _ in 0..10000 {
+        let part = Part { x: random(), m: random(), a: random(), s: random() };
+        assert_eq!(run(&simplified, &part, |_| ()), run(&system.workflows, &part, |_| ()), "{part}");
+    }
+    // and the simplified workflows are as simple as they get
+    assert!(simplify(&simplified) == simplified);
+}
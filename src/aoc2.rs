@@ -28,63 +28,98 @@ In the example above, games 1, 2, and 5 would have been possible if the bag had
 Determine which games would have been possible if the bag had been loaded with only 12 red cubes, 13 green cubes, and 14 blue cubes. What is the sum of the IDs of those games?
 */
 
-#[test]
-pub fn part1() {
-    use std::io;
+use crate::{parse_number, Answer, ParseError, Solution};
 
-    const ALLOWED_PER_COLOR: [u32; 3] = [12, 13, 14];
-    
-    let mut sum = 0;
-    'mainloop: loop {
-        let mut game = String::new();
-        let read_bytes = io::stdin().read_line(&mut game).expect("Failed to read line");
-        if game.len() <= 1 || read_bytes == 0 {
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 2;
+    const TITLE: &'static str = "Cube Conundrum";
+    type Input = Vec<Game>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+/// One game with the cubes shown in each event, counted per color (red, green, blue).
+pub struct Game {
+    pub id: u32,
+    pub events: Vec<[u32; 3]>,
+}
+
+pub fn parse(input: &str) -> Result<Vec<Game>, ParseError> {
+    let mut games = vec![];
+    for (line_index, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let mut id: Option<u32> = None;
-        let mut max_per_color: [u32; 3] = [0; 3];
+        let line_number = line_index + 1;
         // first split by : to find the game ID
-        for (i, s) in game.split(":").enumerate() {
-            assert!(i < 2);
-            if i == 0 {
-                // check the start and get the game id from the end of the string
-                id = s.trim_start_matches("Game ").trim().parse().ok();
-            }
-            else if i == 1 {
-                // split by ; for the individual events
-                'events: for event in s.split(";") {
-                    // split by , for the individual colors
-                    for num_color in event.split(",") {
-                        let num_color = num_color.trim();
-                        let mut spliterator = num_color.split(" ").filter(|x| x.len() > 0);
-                        if let Some(num) = spliterator.next() {
-                            let error = &format!("line invalid {num_color}");
-                            let num: u32 = num.trim().parse().ok().expect(error);
-                            let color = spliterator.next().expect(error);
-                            let index = match color {
-                                "red" => 0,
-                                "green" => 1,
-                                "blue" => 2,
-                                c => panic!("invalid color {c}"),
-                            };
-                            max_per_color[index] = max_per_color[index].max(num);
-                            if ALLOWED_PER_COLOR[index] < max_per_color[index] {
-                                break 'events;
-                            }
-                        }
-                    }
+        let (id, events) = line.split_once(':').ok_or(ParseError::new(line_number, "missing : after the game id"))?;
+        // check the start and get the game id from the end of the string
+        let id = id.trim_start_matches("Game ").trim();
+        let id = id.parse().map_err(|_| ParseError::unexpected(line_number, line, id, "a game id"))?;
+        let mut game = Game { id, events: vec![] };
+        // split by ; for the individual events
+        for event in events.split(';') {
+            let mut count_per_color = [0; 3];
+            // split by , for the individual colors
+            for num_color in event.split(',') {
+                let num_color = num_color.trim();
+                let mut spliterator = num_color.split(' ').filter(|x| !x.is_empty());
+                if let Some(num) = spliterator.next() {
+                    let num: u32 = parse_number(line_number, line, num)?;
+                    let color = spliterator.next().ok_or(ParseError::unexpected(line_number, line, &num_color[num_color.len()..], "a color"))?;
+                    let index = match color {
+                        "red" => 0,
+                        "green" => 1,
+                        "blue" => 2,
+                        c => return Err(ParseError::unexpected(line_number, line, c, "red, green or blue")),
+                    };
+                    count_per_color[index] += num;
                 }
             }
+            game.events.push(count_per_color);
         }
-        for (count, &allowed) in max_per_color.into_iter().zip(&ALLOWED_PER_COLOR) {
-            if count > allowed {
-                continue 'mainloop;
-            }
+        games.push(game);
+    }
+    Ok(games)
+}
+
+pub fn part1(games: &[Game]) -> Answer {
+    const ALLOWED_PER_COLOR: [u32; 3] = [12, 13, 14];
+
+    let mut sum = 0;
+    for game in games {
+        let possible = game.events.iter().all(|event| {
+            event.iter().zip(&ALLOWED_PER_COLOR).all(|(count, allowed)| count <= allowed)
+        });
+        if possible {
+            sum += game.id as u64;
         }
-        // the game was possible
-        sum += id.expect("The game had no id") as u64;
     }
-    println!("The sum of possible games is {sum}");
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 8.into());
 }
 
 /*
@@ -113,51 +148,21 @@ The power of a set of cubes is equal to the numbers of red, green, and blue cube
 For each game, find the minimum set of cubes that must have been present. What is the sum of the power of these sets?
 */
 
-#[test]
-pub fn part2() {
-    use std::io;
-
+pub fn part2(games: &[Game]) -> Answer {
     let mut sum = 0;
-    loop {
-        let mut game = String::new();
-        let read_bytes = io::stdin().read_line(&mut game).expect("Failed to read line");
-        if game.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let mut id: Option<u32> = None;
+    for game in games {
         let mut max_per_color: [u32; 3] = [0; 3];
-        // first split by : to find the game ID
-        for (i, s) in game.split(":").enumerate() {
-            assert!(i < 2);
-            if i == 0 {
-                // check the start and get the game id from the end of the string
-                id = s.trim_start_matches("Game ").trim().parse().ok();
-            }
-            else if i == 1 {
-                // split by ; for the individual events
-                for event in s.split(";") {
-                    // split by , for the individual colors
-                    for num_color in event.split(",") {
-                        let num_color = num_color.trim();
-                        let mut spliterator = num_color.split(" ").filter(|x| x.len() > 0);
-                        if let Some(num) = spliterator.next() {
-                            let error = &format!("line invalid {num_color}");
-                            let num: u32 = num.trim().parse().ok().expect(error);
-                            let color = spliterator.next().expect(error);
-                            let index = match color {
-                                "red" => 0,
-                                "green" => 1,
-                                "blue" => 2,
-                                c => panic!("invalid color {c}"),
-                            };
-                            max_per_color[index] = max_per_color[index].max(num);
-                        }
-                    }
-                }
+        for event in &game.events {
+            for (max, &count) in max_per_color.iter_mut().zip(event) {
+                *max = (*max).max(count);
             }
         }
-        id.expect("The game had no id");
         sum += max_per_color[0] as u64 * max_per_color[1] as u64 * max_per_color[2] as u64;
     }
-    println!("The sum of powers of the games is {sum}");
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 2286.into());
+}
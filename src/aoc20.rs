@@ -113,15 +113,44 @@ In the second example, after pushing the button 1000 times, 4250 low pulses and
 Consult your module configuration; determine the number of low pulses and high pulses that would be sent after pushing the button 1000 times, waiting for all pulses to be fully handled after each push of the button. What do you get if you multiply the total number of low pulses sent by the total number of high pulses sent?
 */
 
+use crate::{Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 20;
+    const TITLE: &'static str = "Pulse Propagation";
+    type Input = Network;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+use crate::bitset::BitSet;
+use crate::cycle;
+use crate::modular::{lcm, Overflow};
 use std::collections::{VecDeque, HashMap};
+use std::fmt;
 
 // a general type that captures all the conditions
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum ModuleType {
     Broadcaster,
     FlipFlop(bool),
-    Conjunction(u128),
+    /// the inputs which sent a high pulse last, by their position in the inputs.
+    Conjunction(BitSet),
 }
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Module {
     pub module_type: ModuleType,
     pub inputs: Vec<usize>,
@@ -129,25 +158,26 @@ pub struct Module {
 }
 
 impl Module {
-    pub fn translate_from_str(value: &str, translation_map: &HashMap<String, usize>, default: usize) -> Module {
-        // a bit hacky but for valid input, it's valid.
-        let module_type = match value.chars().next().expect("got empty string") {
-            '%' => ModuleType::FlipFlop(false),
-            '&' => ModuleType::Conjunction(0),
-            'b' => ModuleType::Broadcaster,
-            _ => panic!("invalid module type"),
+    /// read a module from a line like "%a -> b, c". Outputs which aren't in the translation map get the default index.
+    pub fn translate_from_str(value: &str, translation_map: &HashMap<String, usize>, default: usize) -> Result<Module, ParseError> {
+        let first = &value[..value.chars().next().map_or(0, char::len_utf8)];
+        let module_type = match first {
+            "%" => ModuleType::FlipFlop(false),
+            "&" => ModuleType::Conjunction(BitSet::new()),
+            "b" => ModuleType::Broadcaster,
+            _ => return Err(ParseError::unexpected(0, value, first, "a module type % or & or the broadcaster")),
         };
-        let outputs = value.split_once('>')
-            .expect("line had no ->").1
+        let outputs = value.split_once("->")
+            .ok_or(ParseError::unexpected(0, value, &value[value.len()..], "->"))?.1
             .split(',')
             .map(|s| *translation_map.get(s.trim())
             .unwrap_or(&default))
             .collect();
-        Module {
+        Ok(Module {
             module_type,
             inputs: vec![],
             outputs,
-        }
+        })
     }
 
     pub fn passive() -> Module {
@@ -161,13 +191,9 @@ impl Module {
             },
             ModuleType::Conjunction(state) => {
                 let index = self.inputs.iter().enumerate().find_map(|(i, x)| if x == &src { Some(i) } else { None }).expect("input from unknown source.");
-                if value {
-                    *state |= 1 << index;
-                }
-                else {
-                    *state &= !(1 << index);
-                }
-                Some(*state != (1 << self.inputs.len()) - 1)
+                state.set(index, value);
+                // all high, if every input is set
+                Some(state.count_ones() != self.inputs.len())
             },
             ModuleType::FlipFlop(state) => {
                 if !value {
@@ -182,31 +208,45 @@ impl Module {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Network {
     pub modules: Vec<Module>,
     pub broadcaster: usize,
+    /// the module names by index
+    pub names: Vec<String>,
 }
 
 impl Network {
-    pub fn broadcast<F: FnMut(usize, bool)>(&mut self, value: bool, mut pulse_event: F) {
-        assert!(if let ModuleType::Broadcaster = self.modules[self.broadcaster].module_type { true } else { false }, "broadcaster needs to be module type broadcaster");
-        let mut queue = VecDeque::<(usize, bool)>::new();
-        pulse_event(self.broadcaster, value);
-        queue.push_back((self.broadcaster, self.modules[self.broadcaster].push_pulse(value, self.broadcaster).unwrap()));
-        loop {
-            let (index, pulse) = queue.pop_front().unwrap();
-            for output_index in self.modules[index].outputs.clone() {
-                pulse_event(output_index, pulse);
-                if let Some(output) = self.modules[output_index].push_pulse(pulse, index) {
-                    queue.push_back((output_index, output));
-                }
-            }
-            if queue.len() == 0 {
-                break;
-            }
+    /// press the button once. `pulse_event` gets (from, to, pulse) for every pulse that is sent,
+    /// the pulse from the button comes from the broadcaster itself.
+    pub fn broadcast<F: FnMut(usize, usize, bool)>(&mut self, value: bool, mut pulse_event: F) {
+        assert!(matches!(self.modules[self.broadcaster].module_type, ModuleType::Broadcaster), "broadcaster needs to be module type broadcaster");
+        let mut queue = VecDeque::from([(self.broadcaster, self.broadcaster, value)]);
+        while let Some((from, to, pulse)) = queue.pop_front() {
+            pulse_event(from, to, pulse);
+            self.deliver(from, to, pulse, &mut queue);
+        }
+    }
+
+    /// deliver a pulse to a module and queue the pulses it sends in return as (from, to, pulse).
+    fn deliver(&mut self, from: usize, to: usize, pulse: bool, queue: &mut VecDeque<(usize, usize, bool)>) {
+        if let Some(output) = self.modules[to].push_pulse(pulse, from) {
+            queue.extend(self.modules[to].outputs.iter().map(|&next| (to, next, output)));
         }
     }
 
+    /// the index of the module with the name.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// the network after one more button press, for the cycle detection.
+    pub fn pressed(&self) -> Network {
+        let mut network = self.clone();
+        network.broadcast(false, |_, _, _| ());
+        network
+    }
+
     pub fn connect_modules(&mut self) {
         for module in &mut self.modules {
             module.inputs.clear(); // reset the inputs vec
@@ -219,19 +259,142 @@ impl Network {
         }
     }
 
+    /// modules which only show up as outputs, like rx. They don't have a line in the input.
+    pub fn is_passive(&self, index: usize) -> bool {
+        index != self.broadcaster && matches!(self.modules[index].module_type, ModuleType::Broadcaster)
+    }
+
+    /// the module name with the % or & in front, like in the input.
+    pub fn full_name(&self, index: usize) -> String {
+        let prefix = match self.modules[index].module_type {
+            ModuleType::FlipFlop(_) => "%",
+            ModuleType::Conjunction(_) => "&",
+            ModuleType::Broadcaster => "",
+        };
+        format!("{prefix}{}", self.names[index])
+    }
+
+    /// the groups of modules, in which pulses can go around in circles (strongly connected components).
+    /// Each group is sorted by index and the groups are sorted by their first module.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        // tarjan's algorithm
+        struct Search<'a> {
+            modules: &'a [Module],
+            /// the order in which the modules were visited
+            order: Vec<Option<usize>>,
+            visited: usize,
+            low: Vec<usize>,
+            stack: Vec<usize>,
+            on_stack: Vec<bool>,
+            cycles: Vec<Vec<usize>>,
+        }
+        impl Search<'_> {
+            fn visit(&mut self, i: usize) {
+                let order = self.visited;
+                self.visited += 1;
+                self.order[i] = Some(order);
+                self.low[i] = order;
+                self.stack.push(i);
+                self.on_stack[i] = true;
+                for &next in &self.modules[i].outputs {
+                    match self.order[next] {
+                        None => {
+                            self.visit(next);
+                            self.low[i] = self.low[i].min(self.low[next]);
+                        },
+                        Some(order) if self.on_stack[next] => self.low[i] = self.low[i].min(order),
+                        Some(_) => (),
+                    }
+                }
+                if self.low[i] == order {
+                    let start = self.stack.iter().rposition(|&j| j == i).unwrap();
+                    let mut component = self.stack.split_off(start);
+                    for &j in &component {
+                        self.on_stack[j] = false;
+                    }
+                    // a single module is only a cycle if it goes to itself
+                    if component.len() > 1 || self.modules[i].outputs.contains(&i) {
+                        component.sort_unstable();
+                        self.cycles.push(component);
+                    }
+                }
+            }
+        }
+        let n = self.modules.len();
+        let mut search = Search { modules: &self.modules, order: vec![None; n], visited: 0, low: vec![0; n], stack: vec![], on_stack: vec![false; n], cycles: vec![] };
+        for i in 0..n {
+            if search.order[i].is_none() {
+                search.visit(i);
+            }
+        }
+        let mut cycles = search.cycles;
+        cycles.sort();
+        cycles
+    }
+
+    /// the network as a graphviz digraph. Flip-flops are boxes, conjunctions are diamonds
+    /// and the broadcaster is a double circle.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph network {\n".to_string();
+        for (i, module) in self.modules.iter().enumerate() {
+            let shape = match module.module_type {
+                _ if self.is_passive(i) => "plaintext",
+                ModuleType::Broadcaster => "doublecircle",
+                ModuleType::FlipFlop(_) => "box",
+                ModuleType::Conjunction(_) => "diamond",
+            };
+            dot += &format!("    \"{}\" [shape={shape}];\n", self.names[i]);
+        }
+        for (i, module) in self.modules.iter().enumerate() {
+            for &output in &module.outputs {
+                dot += &format!("    \"{}\" -> \"{}\";\n", self.names[i], self.names[output]);
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+
+    /// a table of all modules with their type and how many modules go in and out,
+    /// followed by the groups of modules, which pulses can go around in.
+    pub fn netlist(&self) -> String {
+        let rows: Vec<[String; 4]> = (0..self.modules.len()).map(|i| {
+            let module = &self.modules[i];
+            let module_type = match module.module_type {
+                _ if self.is_passive(i) => "output",
+                ModuleType::Broadcaster => "broadcaster",
+                ModuleType::FlipFlop(_) => "flip-flop",
+                ModuleType::Conjunction(_) => "conjunction",
+            };
+            [self.full_name(i), module_type.to_string(), module.inputs.len().to_string(), module.outputs.len().to_string()]
+        }).collect();
+        let header = ["module", "type", "fan-in", "fan-out"].map(str::to_string);
+        let widths: Vec<usize> = (0..4).map(|c| rows.iter().chain([&header]).map(|row| row[c].len()).max().unwrap()).collect();
+        let mut text = String::new();
+        for row in [&header].into_iter().chain(&rows) {
+            text += &format!("{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}\n", row[0], row[1], row[2], row[3], w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+        }
+        let cycles = self.cycles();
+        text += &format!("\n{} cycles\n", cycles.len());
+        for cycle in cycles {
+            text += &cycle.iter().map(|&i| self.names[i].as_str()).collect::<Vec<_>>().join(", ");
+            text += "\n";
+        }
+        text
+    }
+
     pub fn state_str(&self) -> String {
         let mut state = String::new();
         for module in &self.modules {
-            match module.module_type {
+            match &module.module_type {
                 ModuleType::Broadcaster => (),
                 ModuleType::Conjunction(module_state) => {
                     for i in 0..module.inputs.len() {
-                        state.push(if (module_state >> i) & 1 != 0 { '1' } else { '0' });
+                        state.push(if module_state.get(i) { '1' } else { '0' });
                     }
                     state.push(' ');
                 },
                 ModuleType::FlipFlop(module_state) => {
-                    state.push(if module_state { 'H' } else { 'L' });
+                    state.push(if *module_state { 'H' } else { 'L' });
                 }
             }
         }
@@ -239,17 +402,159 @@ impl Network {
     }
 }
 
-pub fn get_module_name(value: &str) -> &str {
-    if value.starts_with('b') {
-        value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pulse {
+    Low,
+    High,
+}
+
+impl From<bool> for Pulse {
+    fn from(high: bool) -> Self {
+        if high { Pulse::High } else { Pulse::Low }
     }
-    else {
-        &value[1..]
-    }.split_once('-').expect("line had no ->").0.trim()
 }
 
-#[test]
-pub fn part1() {
+impl fmt::Display for Pulse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", if *self == Pulse::High { "high" } else { "low" })
+    }
+}
+
+/// a pulse, which was sent in the simulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event<'a> {
+    /// the button press starting at 1.
+    pub press: usize,
+    /// the number of the pulse in the button press, the pulse from the button is step 0.
+    pub step: usize,
+    pub from: &'a str,
+    pub to: &'a str,
+    pub pulse: Pulse,
+}
+
+impl fmt::Display for Event<'_> {
+    /// like in the puzzle: broadcaster -low-> a
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -{}-> {}", self.from, self.pulse, self.to)
+    }
+}
+
+/// a module name, which isn't part of the network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownModule(pub String);
+
+impl fmt::Display for UnknownModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "there is no module named {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownModule {}
+
+/// Runs a network pulse by pulse and reports every pulse with the names of the modules.
+/// It can stop in the middle of a button press at a breakpoint and continue from there.
+pub struct Simulator {
+    network: Network,
+    /// the pulses of the current button press, which haven't been delivered yet
+    queue: VecDeque<(usize, usize, bool)>,
+    presses: usize,
+    step: usize,
+    /// (to, pulse)
+    breakpoints: Vec<(usize, Pulse)>,
+}
+
+impl Simulator {
+    pub fn new(network: Network) -> Self {
+        Simulator { network, queue: VecDeque::new(), presses: 0, step: 0, breakpoints: vec![] }
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// the number of button presses so far, including the current one.
+    pub fn presses(&self) -> usize {
+        self.presses
+    }
+
+    /// stop the simulation right after `to` got the pulse.
+    pub fn break_on(&mut self, to: &str, pulse: Pulse) -> Result<(), UnknownModule> {
+        let to = self.network.index(to).ok_or_else(|| UnknownModule(to.to_string()))?;
+        self.breakpoints.push((to, pulse));
+        Ok(())
+    }
+
+    /// deliver the next pulse, if the last button press is done, press the button again.
+    fn step(&mut self) -> (usize, usize, bool) {
+        if self.queue.is_empty() {
+            self.presses += 1;
+            self.step = 0;
+            self.queue.push_back((self.network.broadcaster, self.network.broadcaster, false));
+        }
+        else {
+            self.step += 1;
+        }
+        let (from, to, pulse) = self.queue.pop_front().unwrap();
+        self.network.deliver(from, to, pulse, &mut self.queue);
+        (from, to, pulse)
+    }
+
+    fn event(&self, (from, to, pulse): (usize, usize, bool)) -> Event<'_> {
+        let from = if self.step == 0 { "button" } else { &self.network.names[from] };
+        Event { press: self.presses, step: self.step, from, to: &self.network.names[to], pulse: pulse.into() }
+    }
+
+    /// run until a breakpoint is hit, but stop after `max_presses` button presses in total.
+    /// Returns the pulse that hit the breakpoint. After that the simulation can be continued from there.
+    pub fn run_until_break(&mut self, max_presses: usize, mut on_event: impl FnMut(&Event)) -> Option<Event<'_>> {
+        loop {
+            if self.queue.is_empty() && self.presses >= max_presses {
+                return None;
+            }
+            let pulse = self.step();
+            on_event(&self.event(pulse));
+            let (_, to, high) = pulse;
+            if self.breakpoints.contains(&(to, high.into())) {
+                return Some(self.event(pulse));
+            }
+        }
+    }
+
+    /// finish the current button press, or press the button if all pulses were delivered already.
+    /// Like [Simulator::run_until_break], it stops early at a breakpoint.
+    pub fn run_press(&mut self, on_event: impl FnMut(&Event)) -> Option<Event<'_>> {
+        let max_presses = self.presses + self.queue.is_empty() as usize;
+        self.run_until_break(max_presses, on_event)
+    }
+
+    /// the log of all pulses of the next button press (until a breakpoint), one per line like in the puzzle.
+    pub fn trace_press(&mut self) -> String {
+        let mut log = String::new();
+        self.run_press(|event| log += &format!("{event}\n"));
+        log
+    }
+}
+
+impl fmt::Display for Network {
+    /// the network in the format of the input, the modules which are only outputs don't get a line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, module) in self.modules.iter().enumerate() {
+            if self.is_passive(i) {
+                continue;
+            }
+            let outputs: Vec<&str> = module.outputs.iter().map(|&output| self.names[output].as_str()).collect();
+            writeln!(f, "{} -> {}", self.full_name(i), outputs.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn get_module_name(value: &str) -> Result<&str, ParseError> {
+    let (name, _) = value.split_once("->").ok_or(ParseError::unexpected(0, value, &value[value.len()..], "->"))?;
+    Ok(name.strip_prefix(['%', '&']).unwrap_or(name).trim())
+}
+
+pub fn parse(input: &str) -> Result<Network, ParseError> {
     // The data is given in a weird format.
     // The conjunction needs to know how many inputs it has and has to actually be able to identify them.
     // Therefore the usual format for these types of problems is better, where each node only knows it's precursor.
@@ -260,55 +565,161 @@ pub fn part1() {
     // To build that, read the data twice.
     // 1. get all the names of the nodes and make a mapping to indices
     // 2. read in the actual nodes
-
-    // 811901844 is too low
-    // 790988331 is too low
-    // 886347020 correct
-    // This problem was not well defined at all!
-
-    use std::io;
-    use std::collections::HashMap;
-
     let mut indices = HashMap::<String, usize>::new();
-    let mut modules = vec!();
-    let null_sink;
-    {
-        let mut lines = vec![];
-        loop {
-            let mut input = String::new();
-            let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-            let input = input.trim();
-            if input.len() == 0 || read_bytes == 0 {
-                break;
-            }
-            indices.insert(get_module_name(input).to_string(), lines.len());
-            lines.push(input.to_string());
+    let mut names = vec![];
+    let mut lines = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            break;
         }
-        null_sink = indices.len();
-        for line in lines {
-            modules.push(Module::translate_from_str(&line, &indices, null_sink));
+        let name = get_module_name(line).map_err(|err| err.within(i + 1, line, line))?.to_string();
+        indices.insert(name.clone(), names.len());
+        names.push(name);
+        lines.push((i + 1, line));
+    }
+    // modules, which only show up as outputs (like rx) are passive
+    for (_, line) in &lines {
+        let (_, outputs) = line.split_once("->").unwrap_or_default();
+        for output in outputs.split(',') {
+            let output = output.trim();
+            if !indices.contains_key(output) {
+                indices.insert(output.to_string(), names.len());
+                names.push(output.to_string());
+            }
         }
-        modules.push(Module::passive()); // output module
     }
-    let broadcaster = *indices.get("broadcaster").expect("There was no broadcaster");
-    let mut network = Network { modules, broadcaster };
+    let mut modules = lines.iter().map(|&(line_number, line)| {
+        Module::translate_from_str(line, &indices, names.len()).map_err(|err| err.within(line_number, line, line))
+    }).collect::<Result<Vec<_>, _>>()?;
+    modules.resize_with(names.len(), Module::passive);
+    let broadcaster = *indices.get("broadcaster").ok_or(ParseError::new(0, "There was no broadcaster"))?;
+    let mut network = Network { modules, broadcaster, names };
     network.connect_modules();
+    Ok(network)
+}
+
+pub fn part1(network: &Network) -> Answer {
+    // 811901844 is too low
+    // 790988331 is too low
+    // 886347020 correct
+    // This problem was not well defined at all!
 
+    let mut network = network.clone();
     let mut low_high = [0, 0];
     // now do the steps
     for _ in 1..=1000 {
-        network.broadcast(false, |to, pulse| {
+        network.broadcast(false, |_, _, pulse| {
             low_high[pulse as usize] += 1;
-            if to == null_sink {
-                //println!("{pulse} to {to}");
-            }
         });
-        println!("{}", network.state_str());
     }
     let [low, high] = low_high;
-    println!("{low} low pulses have been sent.");
-    println!("{high} high pulses have been sent.");
-    println!("multiplied it's {}", low as u128 * high as u128);
+    (low as u64 * high as u64).into()
+}
+
+#[test]
+pub fn example_part1() {
+    let input = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+    assert_eq!(part1(&parse(input).unwrap()), 32000000.into());
+    let input = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+    assert_eq!(part1(&parse(input).unwrap()), 11687500.into());
+}
+
+#[test]
+pub fn round_trip() {
+    let input = crate::read_input(20);
+    let network = parse(&input).unwrap();
+    assert_eq!(network.to_string(), input.lines().map(|line| format!("{}\n", line.trim())).collect::<String>());
+    assert!(parse(&network.to_string()).unwrap() == network);
+    // the counters from part 2 are the only cycles
+    let cycles = network.cycles();
+    assert_eq!(cycles.len(), 4);
+    let rx = network.index("rx").unwrap();
+    for counter in find_counters(&network, rx).unwrap() {
+        let mut modules = counter.bits.clone();
+        modules.push(counter.hub);
+        modules.sort();
+        assert!(cycles.contains(&modules));
+    }
+    let network = parse("broadcaster -> a\n%a -> inv, con\n&inv -> b\n%b -> con\n&con -> output").unwrap();
+    assert_eq!(network.to_string(), "broadcaster -> a\n%a -> inv, con\n&inv -> b\n%b -> con\n&con -> output\n");
+    assert_eq!(network.to_dot(), r#"digraph network {
+    "broadcaster" [shape=doublecircle];
+    "a" [shape=box];
+    "inv" [shape=diamond];
+    "b" [shape=box];
+    "con" [shape=diamond];
+    "output" [shape=plaintext];
+    "broadcaster" -> "a";
+    "a" -> "inv";
+    "a" -> "con";
+    "inv" -> "b";
+    "b" -> "con";
+    "con" -> "output";
+}
+"#);
+    assert_eq!(network.netlist(), "module       type         fan-in  fan-out
+broadcaster  broadcaster       0        1
+%a           flip-flop         1        2
+&inv         conjunction       1        1
+%b           flip-flop         1        1
+&con         conjunction       2        1
+output       output            1        0
+
+0 cycles
+");
+    let network = parse("broadcaster -> a, b, c\n%a -> b\n%b -> c\n%c -> inv\n&inv -> a\n%d -> d").unwrap();
+    assert_eq!(network.cycles(), [vec![1, 2, 3, 4], vec![5]]);
+    assert!(network.netlist().ends_with("\n2 cycles\na, b, c, inv\nd\n"));
+}
+
+#[test]
+pub fn example_trace() {
+    let mut simulator = Simulator::new(parse("broadcaster -> a, b, c\n%a -> b\n%b -> c\n%c -> inv\n&inv -> a").unwrap());
+    let first = "button -low-> broadcaster
+broadcaster -low-> a
+broadcaster -low-> b
+broadcaster -low-> c
+a -high-> b
+b -high-> c
+c -high-> inv
+inv -low-> a
+a -low-> b
+b -low-> c
+c -low-> inv
+inv -high-> a
+";
+    assert_eq!(simulator.trace_press(), first);
+    assert_eq!(simulator.trace_press(), first);
+    let mut simulator = Simulator::new(parse("broadcaster -> a\n%a -> inv, con\n&inv -> b\n%b -> con\n&con -> output").unwrap());
+    let presses = [
+        "button -low-> broadcaster\nbroadcaster -low-> a\na -high-> inv\na -high-> con\ninv -low-> b\ncon -high-> output\nb -high-> con\ncon -low-> output\n",
+        "button -low-> broadcaster\nbroadcaster -low-> a\na -low-> inv\na -low-> con\ninv -high-> b\ncon -high-> output\n",
+        "button -low-> broadcaster\nbroadcaster -low-> a\na -high-> inv\na -high-> con\ninv -low-> b\ncon -low-> output\nb -low-> con\ncon -high-> output\n",
+        "button -low-> broadcaster\nbroadcaster -low-> a\na -low-> inv\na -low-> con\ninv -high-> b\ncon -high-> output\n",
+    ];
+    for press in presses {
+        assert_eq!(simulator.trace_press(), press);
+    }
+    assert_eq!(simulator.presses(), 4);
+    // the low pulse to output in the third press, from the puzzle text
+    let mut simulator = Simulator::new(simulator.network().clone());
+    assert_eq!(simulator.break_on("ouput", Pulse::Low), Err(UnknownModule("ouput".to_string())));
+    simulator.break_on("output", Pulse::Low).unwrap();
+    let hit = simulator.run_until_break(10, |_| ()).unwrap();
+    assert_eq!((hit.press, hit.step, hit.to_string()), (1, 7, "con -low-> output".to_string()));
+    let hit = simulator.run_until_break(10, |_| ()).unwrap();
+    assert_eq!((hit.press, hit.step, hit.to_string()), (3, 5, "con -low-> output".to_string()));
+    // the rest of the third press
+    assert_eq!(simulator.trace_press(), "b -low-> con\ncon -high-> output\n");
 }
 
 /*
@@ -431,70 +842,446 @@ lcm(3907, 3919, 4051, 3761)
 = 233283622908263
 */
 
-#[test]
-pub fn part2() {
-    // Tested it, brute forcing seems bad...
-    // So I will have to use some chinese remainder again or something like that...
-    // why is it always combinatorics and number theory?
-    // Additionally, this system is turing complete,
-    // so I'm solving the halting problem for a finite turing machine.
+/*
+The same thing without the human brain: every group above is a binary counter.
+The flip-flops are a chain of bits, each one flips the next one when it turns off.
+The conjunction (&rn) listens to the bits, which are 1 in the period, so it sends a low pulse once the counter gets there.
+That low pulse flips all the 0 bits and bit 0, which makes the counter overflow back to 0.
+So the period can be read from which flip-flops go to the conjunction:
+bx, fx, nx, kn, mv, fk, rv are bits 0, 1, 6, 8, 9, 10, 11 -> 1 + 2 + 64 + 256 + 512 + 1024 + 2048 = 3907
+*/
 
-    use std::io;
-    use std::collections::HashMap;
+/// a binary counter made from a chain of flip-flops, which is reset by a conjunction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Counter {
+    /// the flip-flops from the lowest bit to the highest.
+    pub bits: Vec<usize>,
+    /// the conjunction which watches the 1 bits of the period and resets the counter.
+    pub hub: usize,
+    /// the inverter between the hub and the conjunction in front of rx.
+    pub output: usize,
+    /// the number of button presses after which the hub sends a low pulse and the counter starts over.
+    pub period: u128,
+}
 
-    let mut indices = HashMap::<String, usize>::new();
-    let mut modules = vec!();
-    let rx;
-    {
-        let mut lines = vec![];
-        loop {
-            let mut input = String::new();
-            let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-            let input = input.trim();
-            if input.len() == 0 || read_bytes == 0 {
-                break;
+/// the counters between the broadcaster and the conjunction in front of `rx`, one for every input of that conjunction.
+/// None if the network isn't made of independent counters like the puzzle input.
+pub fn find_counters(network: &Network, rx: usize) -> Option<Vec<Counter>> {
+    let &[feeder] = &network.modules[rx].inputs[..] else {
+        return None;
+    };
+    if !matches!(network.modules[feeder].module_type, ModuleType::Conjunction(_)) {
+        return None;
+    }
+    let counters = network.modules[feeder].inputs.iter()
+        .map(|&output| find_counter(network, output, feeder))
+        .collect::<Option<Vec<_>>>()?;
+    // every counter has to be separate from the others
+    let mut used = vec![false; network.modules.len()];
+    for counter in &counters {
+        for &module in counter.bits.iter().chain([&counter.hub, &counter.output]) {
+            if std::mem::replace(&mut used[module], true) {
+                return None;
             }
-            indices.insert(get_module_name(input).to_string(), lines.len());
-            lines.push(input.to_string());
         }
-        rx = indices.len();
-        indices.insert("rx".to_string(), indices.len());
-        let null_sink = indices.len();
-        for line in lines {
-            modules.push(Module::translate_from_str(&line, &indices, null_sink));
+    }
+    Some(counters)
+}
+
+/// the counter which sends its pulses through the inverter `output` to `feeder`.
+fn find_counter(network: &Network, output: usize, feeder: usize) -> Option<Counter> {
+    let is_conjunction = |i: usize| matches!(network.modules[i].module_type, ModuleType::Conjunction(_));
+    let is_flip_flop = |i: usize| matches!(network.modules[i].module_type, ModuleType::FlipFlop(_));
+    let inverter = &network.modules[output];
+    let &[hub] = &inverter.inputs[..] else {
+        return None;
+    };
+    if !is_conjunction(output) || inverter.outputs != [feeder] || !is_conjunction(hub) {
+        return None;
+    }
+    // the lowest bit is the one the broadcaster flips
+    let broadcaster = &network.modules[network.broadcaster];
+    let mut heads = broadcaster.outputs.iter().filter(|&&bit| {
+        network.modules[bit].outputs.contains(&hub) || network.modules[hub].outputs.contains(&bit)
+    });
+    let (Some(&head), None) = (heads.next(), heads.next()) else {
+        return None;
+    };
+    // follow the chain, each bit goes to the next one and maybe the hub
+    let mut bits = vec![];
+    let mut bit = Some(head);
+    while let Some(current) = bit {
+        if !is_flip_flop(current) || bits.contains(&current) {
+            return None;
+        }
+        bits.push(current);
+        let mut next = network.modules[current].outputs.iter().filter(|&&next| next != hub);
+        bit = next.next().copied();
+        if next.next().is_some() {
+            return None;
         }
-        modules.push(Module::passive()); // rx module
-        modules.push(Module::passive()); // output module
     }
-    let broadcaster = *indices.get("broadcaster").expect("There was no broadcaster");
-    let mut network = Network { modules, broadcaster };
-    network.connect_modules();
+    let one = |bit: usize| network.modules[bit].outputs.contains(&hub);
+    let period = bits.iter().enumerate().filter(|&(_, &bit)| one(bit)).map(|(i, _)| 1u128.checked_shl(i as u32)).sum::<Option<u128>>()?;
+    // The hub has to get exactly the 1 bits and flip the 0 bits and bit 0 back,
+    // and bit 0 has to be 1, otherwise the counter doesn't start over at 0.
+    let mut ones: Vec<usize> = bits.iter().copied().filter(|&bit| one(bit)).collect();
+    let mut resets: Vec<usize> = bits.iter().copied().filter(|&bit| !one(bit) || bit == head).chain([output]).collect();
+    let mut hub_inputs = network.modules[hub].inputs.clone();
+    let mut hub_outputs = network.modules[hub].outputs.clone();
+    for list in [&mut ones, &mut resets, &mut hub_inputs, &mut hub_outputs] {
+        list.sort_unstable();
+    }
+    if !one(head) || ones != hub_inputs || resets != hub_outputs {
+        return None;
+    }
+    // nothing else may flip the bits
+    for (i, &bit) in bits.iter().enumerate() {
+        let previous = if i == 0 { network.broadcaster } else { bits[i - 1] };
+        let mut expected = vec![previous];
+        if network.modules[hub].outputs.contains(&bit) {
+            expected.push(hub);
+        }
+        let mut inputs = network.modules[bit].inputs.clone();
+        inputs.sort_unstable();
+        expected.sort_unstable();
+        if inputs != expected {
+            return None;
+        }
+    }
+    Some(Counter { bits, hub, output, period })
+}
+
+/// the first button press, which sends a low pulse to rx, if it is fed by independent counters.
+/// Each counter sends a high pulse to the conjunction in front of rx once every period,
+/// so they all do at the lcm of the periods.
+pub fn counter_lcm(network: &Network, rx: usize) -> Option<Result<u128, Overflow>> {
+    let counters = find_counters(network, rx)?;
+    Some(counters.iter().try_fold(1, |l, counter| lcm(l, counter.period)))
+}
 
+const MAX_CYCLE_SEARCH: usize = 1 << 16;
+
+pub fn part2(network: &Network) -> Answer {
+    // Tested it, brute forcing seems bad...
+    // So I will have to use some chinese remainder again or something like that...
+    // why is it always combinatorics and number theory?
+    // Additionally, this system is turing complete,
+    // so I'm solving the halting problem for a finite turing machine.
+
+    let rx = network.index("rx").expect("there is no module rx");
+    // the counters are read from the network directly
+    if let Some(presses) = counter_lcm(network, rx) {
+        let presses = presses.unwrap_or_else(|err| panic!("too many button presses: {err}"));
+        return i128::try_from(presses).expect("too many button presses").into();
+    }
+    // The network isn't built like the puzzle input, so there is nothing better than brute force.
+    let start = network;
+    let mut network = network.clone();
+    let mut last_press = usize::MAX;
     let mut btn_pressed = 0;
-    let mut detect_cycle = None;
-    let mut low_in_cycle = vec![];
-    loop {
+    while btn_pressed < last_press {
         btn_pressed += 1;
         let mut had_low_rx = false;
-        network.broadcast(false, |to, pulse| {
+        network.broadcast(false, |_, to, pulse| {
             if to == rx {
                 had_low_rx |= !pulse;
             }
         });
         if had_low_rx {
-            if let Some(cycle_state) = &detect_cycle {
-                if &network.state_str() == cycle_state {
-                    println!("{btn_pressed} cycle length with low at {:?}", low_in_cycle);
-                    break;
-                }
-                low_in_cycle.push(btn_pressed);
+            return btn_pressed.into();
+        }
+        if btn_pressed == MAX_CYCLE_SEARCH {
+            // this takes a while, so check if the state repeats before rx ever gets a low pulse.
+            // Brent only keeps two networks around, remembering every state would take too much memory.
+            let (prefix_len, period) = cycle::brent(start.clone(), Network::pressed);
+            last_press = prefix_len + period;
+        }
+    }
+    panic!("rx never gets a low pulse")
+}
+
+#[cfg(test)]
+const COUNTER: &str = "broadcaster -> a
+%a -> b, e
+%b -> c, e
+%c -> d, e
+%d -> e
+&e -> rx";
+
+#[test]
+pub fn counter() {
+    // my simple test case, a 4-bit counter
+    let network = parse(COUNTER).unwrap();
+    assert_eq!(part1(&network), (3874 * 3626).into());
+    assert_eq!(part2(&network), 15.into());
+    // it counts to 16 and starts over
+    assert_eq!(cycle::find_cycle(network.clone(), Network::pressed), (0, 16));
+    // stop when rx receives low
+    let mut simulator = Simulator::new(network.clone());
+    simulator.break_on("rx", Pulse::Low).unwrap();
+    let mut pulses = 0;
+    let hit = simulator.run_until_break(100, |_| pulses += 1).unwrap();
+    assert_eq!((hit.press, hit.from, hit.to, hit.pulse), (15, "e", "rx", Pulse::Low));
+    assert_eq!(simulator.run_until_break(20, |_| pulses += 1), None);
+    assert_eq!(simulator.presses(), 20);
+    let mut network = network;
+    let mut expected = 0;
+    for _ in 0..20 {
+        network.broadcast(false, |_, _, _| expected += 1);
+    }
+    assert_eq!(pulses, expected);
+    assert!(simulator.network() == &network);
+}
+
+/// a network like the puzzle input, with a counter for each of the periods.
+#[cfg(test)]
+fn counters_network(periods: &[u32]) -> String {
+    let heads: Vec<String> = (0..periods.len()).map(|j| format!("c{j}b0")).collect();
+    let mut lines = vec![format!("broadcaster -> {}", heads.join(", "))];
+    for (j, &period) in periods.iter().enumerate() {
+        lines.extend(counter_lines(j, period, "g"));
+    }
+    lines.push("&g -> rx".to_string());
+    lines.join("\n")
+}
+
+#[test]
+pub fn counters() {
+    let network = parse(&crate::read_input(20)).unwrap();
+    let rx = network.index("rx").unwrap();
+    let counters = find_counters(&network, rx).unwrap();
+    let mut periods: Vec<u128> = counters.iter().map(|counter| counter.period).collect();
+    periods.sort();
+    assert_eq!(periods, [3761, 3907, 3919, 4051]);
+    assert!(counters.iter().all(|counter| counter.bits.len() == 12));
+    // compare with running the network until rx gets a low pulse
+    for periods in [&[5, 7, 9][..], &[3], &[13, 11], &[1, 15, 21]] {
+        let network = parse(&counters_network(periods)).unwrap();
+        let rx = network.index("rx").unwrap();
+        let mut simulator = Simulator::new(network.clone());
+        simulator.break_on("rx", Pulse::Low).unwrap();
+        let expected = simulator.run_until_break(10000, |_| ()).unwrap().press;
+        assert_eq!(counter_lcm(&network, rx), Some(Ok(expected as u128)), "{periods:?}");
+        assert_eq!(part2(&network), expected.into());
+    }
+    // an even period doesn't reset to 0, so it isn't a counter
+    let network = parse(&counters_network(&[5, 6])).unwrap();
+    assert_eq!(find_counters(&network, network.index("rx").unwrap()), None);
+    // the 4-bit counter has no hub, the bits go to the conjunction in front of rx directly
+    let network = parse(COUNTER).unwrap();
+    assert_eq!(find_counters(&network, network.index("rx").unwrap()), None);
+}
+
+#[test]
+pub fn wide_conjunction() {
+    // the conjunction only sends a low pulse, once all flip-flops are on after the first press
+    for n in [1, 127, 128, 129, 300] {
+        let flip_flops: Vec<String> = (0..n).map(|i| format!("f{i}")).collect();
+        let mut input = format!("broadcaster -> {}\n", flip_flops.join(", "));
+        for flip_flop in &flip_flops {
+            input += &format!("%{flip_flop} -> c\n");
+        }
+        input += "&c -> rx";
+        let mut simulator = Simulator::new(parse(&input).unwrap());
+        let mut lows = vec![];
+        for _ in 0..4 {
+            let mut low = 0;
+            simulator.run_press(|event| low += (event.to == "rx" && event.pulse == Pulse::Low) as usize);
+            lows.push(low);
+        }
+        assert_eq!(lows, [1, 0, 1, 0], "{n} inputs");
+        assert!(simulator.network().state_str().ends_with(&format!("{} ", "0".repeat(n))));
+    }
+}
+
+#[test]
+#[should_panic = "rx never gets a low pulse"]
+pub fn never_low() {
+    // the conjunction only ever sees low pulses, so it always sends high ones
+    part2(&parse("broadcaster -> a\n&a -> rx").unwrap());
+}
+
+/// the lines of a counter like in the puzzle input, which counts to `period` and starts over.
+/// The flip-flops are c{j}b0, c{j}b1, ..., the hub is h{j} and it sends its pulses to `outputs` through the inverter o{j}.
+/// The broadcaster has to go to c{j}b0.
+fn counter_lines(j: usize, period: u32, outputs: &str) -> Vec<String> {
+    let bits = 32 - period.leading_zeros();
+    let mut lines = vec![];
+    let mut resets = vec![];
+    for i in 0..bits {
+        let mut bit_outputs = vec![];
+        if i + 1 < bits {
+            bit_outputs.push(format!("c{j}b{}", i + 1));
+        }
+        if period >> i & 1 == 1 {
+            bit_outputs.push(format!("h{j}"));
+        }
+        if period >> i & 1 == 0 || i == 0 {
+            resets.push(format!("c{j}b{i}"));
+        }
+        lines.push(format!("%c{j}b{i} -> {}", bit_outputs.join(", ")));
+    }
+    resets.push(format!("o{j}"));
+    lines.push(format!("&h{j} -> {}", resets.join(", ")));
+    lines.push(format!("&o{j} -> {outputs}"));
+    lines
+}
+
+/// a random module configuration in the puzzle format, which is always the same for the same seed.
+/// Pulses could go around forever in random cycles, so the only cycles are in up to 3 counters like in the puzzle input.
+/// Everything else is `size` flip-flops and conjunctions, which only go to modules further down, rx or output.
+/// About a third of the flip-flops also go to the last conjunction, so for big sizes it gets lots of inputs.
+pub fn random_network(seed: u64, size: usize) -> String {
+    let mut random = crate::Random::new(seed);
+    let mut random = |n: usize| random.below(n);
+    // ma, mb, ..., mba, ... so they can't be confused with the other names
+    let name = |mut i: usize| {
+        let mut name = String::new();
+        loop {
+            name.insert(0, (b'a' + (i % 26) as u8) as char);
+            i /= 26;
+            if i == 0 {
+                break;
+            }
+        }
+        format!("m{name}")
+    };
+    let conjunctions: Vec<bool> = (0..size).map(|_| random(5) < 2).collect();
+    let hub = conjunctions.iter().rposition(|&c| c);
+    // 1 to 3 different outputs after the module `from`, and maybe the hub
+    let pick_outputs = |random: &mut dyn FnMut(usize) -> usize, from: Option<usize>, hub: Option<usize>| {
+        let first = from.map_or(0, |from| from + 1);
+        let mut outputs = vec![];
+        for _ in 0..random(3) + 1 {
+            let output = match first + random(size - first + 2) {
+                i if i < size => name(i),
+                i if i == size => "rx".to_string(),
+                _ => "output".to_string(),
+            };
+            if !outputs.contains(&output) {
+                outputs.push(output);
+            }
+        }
+        if let Some(hub) = hub.filter(|&hub| first <= hub && random(3) == 0).map(name) {
+            if !outputs.contains(&hub) {
+                outputs.push(hub);
+            }
+        }
+        outputs
+    };
+    let mut lines = vec![];
+    let mut heads = pick_outputs(&mut random, None, None);
+    for j in 0..random(4) {
+        // odd periods from 1 to 255
+        let period = random(128) * 2 + 1;
+        heads.push(format!("c{j}b0"));
+        lines.extend(counter_lines(j, period as u32, &pick_outputs(&mut random, None, None).join(", ")));
+    }
+    lines.insert(0, format!("broadcaster -> {}", heads.join(", ")));
+    for (i, &conjunction) in conjunctions.iter().enumerate() {
+        let outputs = pick_outputs(&mut random, Some(i), (!conjunction).then_some(hub).flatten());
+        lines.push(format!("{}{} -> {}", if conjunction { '&' } else { '%' }, name(i), outputs.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// a slow simulator written straight from the puzzle rules, which works on the names.
+#[cfg(test)]
+struct Reference {
+    /// (type, name, outputs) in the order of the input, the type is the first character of the line
+    modules: Vec<(char, String, Vec<String>)>,
+    on: HashMap<String, bool>,
+    /// the last pulse from each input, in the order of the input
+    memory: HashMap<String, Vec<(String, bool)>>,
+}
+
+#[cfg(test)]
+impl Reference {
+    fn new(input: &str) -> Self {
+        let modules: Vec<(char, String, Vec<String>)> = input.lines().map(|line| {
+            let (name, outputs) = line.split_once(" -> ").unwrap();
+            let kind = name.chars().next().unwrap();
+            let outputs = outputs.split(", ").map(str::to_string).collect();
+            (kind, name.trim_start_matches(['%', '&']).to_string(), outputs)
+        }).collect();
+        let mut memory = HashMap::<String, Vec<(String, bool)>>::new();
+        for (_, name, outputs) in &modules {
+            for output in outputs {
+                memory.entry(output.clone()).or_default().push((name.clone(), false));
+            }
+        }
+        Reference { modules, on: HashMap::new(), memory }
+    }
+
+    /// push the button and count the [low, high] pulses.
+    fn press(&mut self) -> [usize; 2] {
+        let mut count = [0, 0];
+        let mut queue = VecDeque::from([("button".to_string(), "broadcaster".to_string(), false)]);
+        while let Some((from, to, high)) = queue.pop_front() {
+            count[high as usize] += 1;
+            // modules without a line, like rx, don't do anything
+            let Some((kind, _, outputs)) = self.modules.iter().find(|(_, name, _)| *name == to) else {
+                continue;
+            };
+            let send = match kind {
+                '%' if high => continue,
+                '%' => {
+                    let on = self.on.entry(to.clone()).or_default();
+                    *on = !*on;
+                    *on
+                },
+                '&' => {
+                    let memory = self.memory.get_mut(&to).unwrap();
+                    memory.iter_mut().find(|(input, _)| *input == from).unwrap().1 = high;
+                    !memory.iter().all(|&(_, high)| high)
+                },
+                _ => high,
+            };
+            for output in outputs {
+                queue.push_back((to.clone(), output.clone(), send));
             }
-            else {
-                detect_cycle = Some(network.state_str());
-                println!("{btn_pressed} button presses are required.");
-                btn_pressed = 0;
-                low_in_cycle.push(0);
+        }
+        count
+    }
+
+    /// the same as [Network::state_str].
+    fn state_str(&self) -> String {
+        let mut state = String::new();
+        for (kind, name, _) in &self.modules {
+            match kind {
+                '%' => state.push(if self.on.get(name) == Some(&true) { 'H' } else { 'L' }),
+                '&' => {
+                    for &(_, high) in self.memory.get(name).into_iter().flatten() {
+                        state.push(if high { '1' } else { '0' });
+                    }
+                    state.push(' ');
+                },
+                _ => (),
             }
         }
+        state
+    }
+}
+
+#[test]
+pub fn random_networks() {
+    assert_eq!(random_network(3, 20), random_network(3, 20));
+    assert_ne!(random_network(3, 20), random_network(4, 20));
+    assert!((0..10).any(|seed| random_network(seed, 5).contains("&h2 -> ")));
+    for (seed, size) in (0..40).map(|seed| (seed, 2 + seed as usize * 3)).chain([(100, 1000)]) {
+        let input = random_network(seed, size);
+        let mut network = parse(&input).unwrap();
+        let mut reference = Reference::new(&input);
+        // the first conjunction of the big one has more inputs than fit into a u128
+        if size == 1000 {
+            assert!(network.modules.iter().any(|module| matches!(module.module_type, ModuleType::Conjunction(_)) && module.inputs.len() > 128));
+        }
+        for press in 1..=100 {
+            let mut count = [0, 0];
+            network.broadcast(false, |_, _, high| count[high as usize] += 1);
+            assert_eq!(count, reference.press(), "seed {seed}, press {press}");
+            assert_eq!(network.state_str(), reference.state_str(), "seed {seed}, press {press}");
+        }
     }
-}
\ No newline at end of file
+}
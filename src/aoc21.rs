@@ -86,47 +86,32 @@ However, the Elf actually needs to get 64 steps today, and the map he's handed y
 Starting from the garden plot marked S on your map, how many garden plots could the Elf reach in exactly 64 steps?
 */
 
-use std::collections::HashSet;
+use crate::grid::{Grid, Point};
+use crate::{Answer, ParseError, Solution};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub enum Direction {
-    North = 0,
-    East = 1,
-    West = 2,
-    South = 3,
-}
+pub struct Day;
 
-impl Direction {
-    pub fn delta(&self) -> Point {
-        match self {
-            Self::North => Point(0, -1),
-            Self::East => Point(1, 0),
-            Self::South => Point(0, 1),
-            Self::West => Point(-1, 0),
-        }
-    }
-}
+impl Solution for Day {
+    const DAY: usize = 21;
+    const TITLE: &'static str = "Step Counter";
+    type Input = Garden;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub struct Point(pub i64, pub i64);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
 
-impl std::ops::Add for Point {
-    type Output = Point;
-    fn add(self, rhs: Self) -> Self::Output {
-        Point(self.0 + rhs.0, self.1 + rhs.1)
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
     }
-}
 
-impl Point {
-    pub fn in_bounds(&self, width: usize, height: usize) -> bool {
-        self.0 >= 0 && self.1 >= 0 && (self.0 as usize) < width && (self.1 as usize) < height
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
-pub fn reachable(grid: &Vec<Vec<bool>>, start: Point, steps: usize) -> (usize, usize) {
-    let width = grid[0].len();
-    let height = grid.len();
+use std::collections::HashSet;
 
+pub fn reachable(grid: &Grid<bool>, start: Point, steps: usize) -> (usize, usize) {
     let mut old_border = HashSet::new();
     let mut border = HashSet::new();
     border.insert(start);
@@ -136,13 +121,11 @@ pub fn reachable(grid: &Vec<Vec<bool>>, start: Point, steps: usize) -> (usize, u
     for i in 0..steps {
         let mut new_border = HashSet::with_capacity(border.len() + 4);
         for point in &border {
-            for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
-                let next = *point + dir.delta();
-                if next.in_bounds(width, height) && !grid[next.1 as usize][next.0 as usize] {
-                    if !old_border.contains(&next) && !border.contains(&next) {
+            for next in point.neighbors() {
+                if grid.get(next) == Some(&false)
+                    && !old_border.contains(&next) && !border.contains(&next) {
                         new_border.insert(next);
                     }
-                }
             }
         }
         old_border = border;
@@ -154,43 +137,55 @@ pub fn reachable(grid: &Vec<Vec<bool>>, start: Point, steps: usize) -> (usize, u
         else {
             reachable_alternate += border.len();
         }
-        if border.len() == 0 {
+        if border.is_empty() {
             break;
         }
     }
     (reachable, reachable_alternate)
 }
 
-#[test]
-pub fn part1() {
+/// the map of rocks (true) and garden plots (false) and the starting position.
+pub struct Garden {
+    pub grid: Grid<bool>,
+    pub start: Point,
+}
+
+pub fn parse(input: &str) -> Result<Garden, ParseError> {
+    let chars = Grid::parse(input, "a garden plot ., a rock # or the start S", |c| matches!(c, '.' | '#' | 'S').then_some(c))?;
+    let mut starts = chars.iter().filter(|(_, &c)| c == 'S').map(|(p, _)| p);
+    let start = starts.next().ok_or(ParseError::new(0, "Must have exactly one start"))?;
+    if let Some(second) = starts.next() {
+        return Err(ParseError::new(second.1 as usize + 1, "Can't have more than 1 start"));
+    }
+    Ok(Garden { grid: chars.map(|&c| c == '#'), start })
+}
+
+pub fn part1(garden: &Garden) -> Answer {
     // idea: get the closest distance to all the blocks in the grid by flood filling.
     // Then note, that all of the blocks that are closer than/equal 64 blocks can be reached.
     // However only the blocks with even distance can be reached in exactly 64 steps.
+    let reachable = reachable(&garden.grid, garden.start, 64).0;
 
-    use std::io;
-
-    let mut grid = vec![];
-    let mut start = None;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<_> = input.chars().enumerate().map(|(i, c)|
-        match c { '.' => false, '#' => true, 'S' => {
-            assert!(start.is_none(), "Can't have more than 1 start");
-            start = Some(Point(i as i64, grid.len() as i64));
-            false
-        }, _ => panic!("invalid char {c}"), }).collect();
-        grid.push(line);
-    }
-    let start = start.expect("Must have exactly one start");
+    reachable.into()
+}
 
-    let reachable = reachable(&grid, start, 64).0;
+#[cfg(test)]
+const EXAMPLE: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
 
-    println!("{reachable} gardens are reachable");
+#[test]
+pub fn example_part1() {
+    let garden = parse(EXAMPLE).unwrap();
+    assert_eq!(reachable(&garden.grid, garden.start, 6).0, 16);
 }
 
 /*
@@ -253,10 +248,7 @@ Here are the number of reachable garden plots in this new infinite version of th
 However, the step count the Elf needs is much larger! Starting from the garden plot marked S on your infinite map, how many garden plots could the Elf reach in exactly 26501365 steps?
 */
 
-pub fn reachable_periodic(grid: &Vec<Vec<bool>>, start: Point, steps: usize) -> usize {
-    let width = grid[0].len();
-    let height = grid.len();
-
+pub fn reachable_periodic(grid: &Grid<bool>, start: Point, steps: usize) -> usize {
     let mut old_border = HashSet::new();
     let mut border = HashSet::new();
     border.insert(start);
@@ -265,13 +257,11 @@ pub fn reachable_periodic(grid: &Vec<Vec<bool>>, start: Point, steps: usize) ->
     for i in 0..steps {
         let mut new_border = HashSet::with_capacity(border.len() + 4);
         for point in &border {
-            for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
-                let next = *point + dir.delta();
-                if !grid[next.1.rem_euclid(height as i64) as usize][next.0.rem_euclid(width as i64) as usize] {
-                    if !old_border.contains(&next) && !border.contains(&next) {
+            for next in point.neighbors() {
+                if !grid.get_wrapping(next)
+                    && !old_border.contains(&next) && !border.contains(&next) {
                         new_border.insert(next);
                     }
-                }
             }
         }
         old_border = border;
@@ -285,8 +275,7 @@ pub fn reachable_periodic(grid: &Vec<Vec<bool>>, start: Point, steps: usize) ->
     reachable
 }
 
-#[test]
-pub fn part2() {
+pub fn part2(garden: &Garden) -> Answer {
     // Do the same, but don't keep the full thing in memory.
     // Just keep the boundary and the last boundary in memory
     // and add up the even area as it goes.
@@ -303,51 +292,50 @@ pub fn part2() {
 
     // correct answer 598044246091826
     // first try!
-    
-    use std::io;
-
-    let mut grid = vec![];
-    let mut start = None;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let line: Vec<_> = input.chars().enumerate().map(|(i, c)|
-        match c { '.' => false, '#' => true, 'S' => {
-            assert!(start.is_none(), "Can't have more than 1 start");
-            start = Some(Point(i as i64, grid.len() as i64));
-            false
-        }, _ => panic!("invalid char {c}"), }).collect();
-        grid.push(line);
-    }
-    let start = start.expect("Must have exactly one start");
-    let width = grid[0].len();
-    let height = grid.len();
-    
-    let steps = 26501365;
+
+    let reachable = reachable_extrapolated(&garden.grid, garden.start, 26501365);
+    reachable.into()
+}
+
+/// the number of garden plots reachable in exactly `steps` steps on the infinite map,
+/// extrapolated from simulating just a few periods of the map.
+pub fn reachable_extrapolated(grid: &Grid<bool>, start: Point, steps: usize) -> u128 {
+    let width = grid.width();
+    let height = grid.height();
+
     let mut step_mod = width + height;
     
     //let (mut area, area_alt) = reachable(&grid, start, width * height);
-    println!("{width}x{height}");
     
     if step_mod % 2 == 1 {
         step_mod *= 2;
     }
     let simulate_steps = 2; // if this is too low and the start doesn't manage to reach the corners in time, and the computation fails.
-    let big_steps = ((steps / step_mod + simulate_steps).max(simulate_steps) - 2 * simulate_steps) as u128;
-    println!("{step_mod} {big_steps}");
+    if steps / step_mod < simulate_steps + 2 {
+        // not enough steps to extrapolate, just simulate all of them
+        return reachable_periodic(grid, start, steps) as u128;
+    }
+    let big_steps = (steps / step_mod - simulate_steps) as u128;
     
-    let area1 = reachable_periodic(&grid, start, steps % step_mod + step_mod * simulate_steps) as u128;
-    let area2 = reachable_periodic(&grid, start, steps % step_mod + step_mod * (simulate_steps + 1)) as u128;
-    let area3 = reachable_periodic(&grid, start, steps % step_mod + step_mod * (simulate_steps + 2)) as u128;
+    let area1 = reachable_periodic(grid, start, steps % step_mod + step_mod * simulate_steps) as u128;
+    let area2 = reachable_periodic(grid, start, steps % step_mod + step_mod * (simulate_steps + 1)) as u128;
+    let area3 = reachable_periodic(grid, start, steps % step_mod + step_mod * (simulate_steps + 2)) as u128;
     
     // extrapolate quadratically using lagrange polynomials
     let l3 = area3 * big_steps * (big_steps - 1) / 2;
     let l2 = area2 * big_steps * (big_steps - 2);
     let l1 = area1 * (big_steps - 1) * (big_steps - 2) / 2;
-    let reachable = l1 + l3 - l2;
-    println!("{reachable} gardens are reachable");
-}
\ No newline at end of file
+    l1 + l3 - l2
+}
+
+#[test]
+pub fn example_part2() {
+    let garden = parse(EXAMPLE).unwrap();
+    let table = [(6, 16), (10, 50), (50, 1594), (100, 6536), (500, 167004), (1000, 668697), (5000, 16733044)];
+    for (steps, expected) in table {
+        if steps <= 500 {
+            assert_eq!(reachable_periodic(&garden.grid, garden.start, steps), expected as usize, "failed for {steps} steps");
+        }
+        assert_eq!(reachable_extrapolated(&garden.grid, garden.start, steps), expected, "failed to extrapolate {steps} steps");
+    }
+}
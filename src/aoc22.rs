@@ -122,6 +122,28 @@ So, in this example, 5 bricks can be safely disintegrated.
 Figure how the blocks will settle based on the snapshot. Once they've settled, consider disintegrating a single brick; how many bricks could be safely chosen as the one to get disintegrated?
 */
 
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 22;
+    const TITLE: &'static str = "Sand Slabs";
+    type Input = Snapshot;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 /*
 another example for testing
 
@@ -158,16 +180,13 @@ use std::ops::{Index, IndexMut};
 pub struct Point(pub i64, pub i64, pub i64);
 
 impl TryFrom<&str> for Point {
-    type Error = ();
-    fn try_from(value: &str) -> Result<Self, ()> {
-        let mut v_iter = value.split(',').filter_map(|n| n.parse().ok());
-        let x = v_iter.next().ok_or(())?;
-        let y = v_iter.next().ok_or(())?;
-        let z = v_iter.next().ok_or(())?;
-        if !v_iter.next().is_none() {
-            return Err(());
-        }
-        Ok(Point(x, y, z))
+    type Error = ParseError;
+    fn try_from(value: &str) -> Result<Self, ParseError> {
+        let coords: Vec<&str> = value.split(',').collect();
+        let [x, y, z] = coords[..] else {
+            return Err(ParseError::unexpected(0, value, value, "3 coordinates like 1,0,1"));
+        };
+        Ok(Point(parse_number(0, value, x)?, parse_number(0, value, y)?, parse_number(0, value, z)?))
     }
 }
 
@@ -218,7 +237,7 @@ pub struct Graph {
     pub nodes: Vec<Node>,
 }
 
-pub fn stack(blocks: &Vec<(Point, Point)>, min: &Point, max: &Point) -> Graph {
+pub fn stack(blocks: &[(Point, Point)], min: &Point, max: &Point) -> Graph {
     let mut graph = Graph { nodes: vec![] };
     let mut height = Heightmap::new((0, None), min.0, min.1, max.0, max.1);
     for (i, block) in blocks.iter().enumerate() {
@@ -274,7 +293,6 @@ pub fn stack(blocks: &Vec<(Point, Point)>, min: &Point, max: &Point) -> Graph {
         // if the node.rests_on nothing, then it's implicitly resting on the ground.
         graph.nodes.push(node);
     }
-    println!("max stacked height is {}", height.heights.iter().map(|(height, _)| height).max().unwrap());
     // stacking and graphing complete
     // now find all supported nodes
     for i in 0..graph.nodes.len() {
@@ -286,30 +304,31 @@ pub fn stack(blocks: &Vec<(Point, Point)>, min: &Point, max: &Point) -> Graph {
     graph
 }
 
-#[test]
-pub fn part1() {
+/// the bricks sorted by their lowest z-coordinate and the bounding box of all bricks.
+pub struct Snapshot {
+    pub blocks: Vec<(Point, Point)>,
+    pub min: Point,
+    pub max: Point,
+}
+
+pub fn parse(input: &str) -> Result<Snapshot, ParseError> {
     // idea: this is a graph type problem again.
     // 1. read in the data and sort it by minimal z-coordinate
     // 2. make a graph for which blocks are above which (list of linear constraints for each block)
     // 3. compute the fallen down state using the linear constraints from the graph
     // 4. find all nodes in the graph which are supported by exactly one other node and mark that other node as support.
     // 5. invert that selection to get all nodes, which can be removed first.
-
-    use std::io;
-
     let mut blocks = vec![];
     let mut min: Option<Point> = None;
     let mut max: Option<Point> = None;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let input = input.trim();
-        let (p1, p2) = input.split_once('~').expect("no ~ to split the two points");
-        let p1 = Point::try_from(p1).expect("failed to parse point");
-        let p2 = Point::try_from(p2).expect("failed to parse point");
+        let line = line.trim();
+        let (p1, p2) = line.split_once('~').ok_or(ParseError::unexpected(i + 1, line, &line[line.len()..], "~ between the two points"))?;
+        let p1 = Point::try_from(p1).map_err(|err| err.within(i + 1, line, p1))?;
+        let p2 = Point::try_from(p2).map_err(|err| err.within(i + 1, line, p2))?;
         // normalize data by making the first point, the one with smaller coordinates
         if p1.0 < p2.0 || p1.1 < p2.1 || p1.2 < p2.2 {
             blocks.push((p1, p2));
@@ -320,20 +339,45 @@ pub fn part1() {
         min = Some(p1.min(&min.unwrap_or(p1)));
         max = Some(p2.max(&max.unwrap_or(p2)));
     }
-    let min = min.expect("expected at least one block");
+    let min = min.ok_or(ParseError::new(0, "expected at least one block"))?;
     let max = max.unwrap();
     blocks.sort_by_key(|block| block.0.2);
-    println!("footprint: {min:?} <-> {max:?}");
-    // blocks are read in and sorted - now create the graph
+    Ok(Snapshot { blocks, min, max })
+}
+
+pub fn part1(snapshot: &Snapshot) -> Answer {    // blocks are read in and sorted - now create the graph
     // there is many ways of doing spatial tree hierachies.
     // I'm going for the simplest possible here. Just sweep and prune.
     // The data has a very small xy footprint, so I will exploit that!
     // -> Do it as a painting!
     // Don't exploit that the data is only positive.
-    let graph = stack(&blocks, &min, &max);
+    let graph = stack(&snapshot.blocks, &snapshot.min, &snapshot.max);
     let supports = graph.nodes.iter().filter(|node| node.is_support).count();
     let free = graph.nodes.len() - supports;
-    println!("{free} blocks can be disintegrated");
+    free.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 5.into());
+}
+
+#[test]
+pub fn invalid_input() {
+    let err = parse("1,0,1~1,2,1\n0,0,2~2,x,2").err().unwrap();
+    assert_eq!((err.line, err.column, err.expected.as_deref()), (2, 9, Some("a number")));
+    let err = parse("1,0,1~1,2").err().unwrap();
+    assert_eq!((err.line, err.column), (1, 7));
+    assert!(parse("1,0,1 1,2,1").is_err());
 }
 
 /*
@@ -353,38 +397,8 @@ Disintegrating any other brick would cause no other bricks to fall. So, in this
 For each brick, determine how many other bricks would fall if that brick were disintegrated. What is the sum of the number of other bricks that would fall?
 */
 
-#[test]
-pub fn part2() {
-    use std::io;
-
-    let mut blocks = vec![];
-    let mut min: Option<Point> = None;
-    let mut max: Option<Point> = None;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let (p1, p2) = input.split_once('~').expect("no ~ to split the two points");
-        let p1 = Point::try_from(p1).expect("failed to parse point");
-        let p2 = Point::try_from(p2).expect("failed to parse point");
-        // normalize data by making the first point, the one with smaller coordinates
-        if p1.0 < p2.0 || p1.1 < p2.1 || p1.2 < p2.2 {
-            blocks.push((p1, p2));
-        }
-        else {
-            blocks.push((p2, p1));
-        }
-        min = Some(p1.min(&min.unwrap_or(p1)));
-        max = Some(p2.max(&max.unwrap_or(p2)));
-    }
-    let min = min.expect("expected at least one block");
-    let max = max.unwrap();
-    blocks.sort_by_key(|block| block.0.2);
-    println!("footprint: {min:?} <-> {max:?}");
-    let mut graph = stack(&blocks, &min, &max);
+pub fn part2(snapshot: &Snapshot) -> Answer {
+    let mut graph = stack(&snapshot.blocks, &snapshot.min, &snapshot.max);
     // now compute the topple count for each node, that is a support.
     // There is probably some fancy algorithm for this, but my input is small,
     // so I'm going to do the naive thing of counting the nodes for each support.
@@ -402,12 +416,17 @@ pub fn part2() {
             graph.nodes[i].topple = topple_marker;
             for j in i+1..graph.nodes.len() {
                 // if all supports are falling, let it fall
-                if graph.nodes[j].rests_on.len() > 0 && graph.nodes[j].rests_on.iter().all(|&k| graph.nodes[k].topple == topple_marker) {
+                if !graph.nodes[j].rests_on.is_empty() && graph.nodes[j].rests_on.iter().all(|&k| graph.nodes[k].topple == topple_marker) {
                     graph.nodes[j].topple = topple_marker;
                     topple_counter += 1;
                 }
             }
         }
     }
-    println!("{topple_counter} is the sum of the number of blocks that would fall");
-}
\ No newline at end of file
+    topple_counter.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 7.into());
+}
@@ -68,24 +68,45 @@ This hike contains 94 steps. (The other possible hikes you could have taken were
 Find the longest hike you can take through the hiking trails listed on your map. How many steps long is the longest hike?
 */
 
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use crate::grid::{Direction, Grid, Point};
+use crate::{Answer, ParseError, Solution};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub struct Point(pub isize, pub isize);
+pub struct Day;
 
-impl std::ops::Sub for Point {
-    type Output = Point;
-    fn sub(self, rhs: Self) -> Self::Output {
-        Point(self.0 - rhs.0, self.1 - rhs.1)
+impl Solution for Day {
+    const DAY: usize = 23;
+    const TITLE: &'static str = "A Long Walk";
+    const SLOW_PARTS: &'static [usize] = &[2];
+    type Input = HikingMap;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
 #[derive(Clone, Debug)]
 pub struct Node<K> {
     pub next: Vec<(K, u64)>, // next (node, edge weight)
 }
 
+impl<K> Default for Node<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<K> Node<K> {
     pub fn new() -> Self {
         Node { next: vec![] }
@@ -93,105 +114,70 @@ impl<K> Node<K> {
 }
 
 #[derive(Clone, Debug)]
-pub struct DirectedGraph<K> {
-    pub nodes: HashMap<K, Node<K>>, // used a HashMap because I didn't want to deal with reindexing on simplification
+pub struct DirectedGraph<K, S = RandomState> {
+    pub nodes: HashMap<K, Node<K>, S>, // used a HashMap because I didn't want to deal with reindexing on simplification
 }
 
-pub fn rget<'a, K: PartialEq + Eq + Hash + Clone>(map: &'a HashMap<K, (K, u64)>, key: &'a K) -> (&'a K, u64) {
-    let mut res = (key, 0u64);
-    while let Some(edge) = map.get(&res.0) {
-        res = (&edge.0, res.1 + edge.1);
+impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug> Default for DirectedGraph<K> {
+    fn default() -> Self {
+        Self::new()
     }
-    res
 }
 
 impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug> DirectedGraph<K> {
     pub fn new() -> Self {
         DirectedGraph { nodes: HashMap::new() }
     }
+}
 
-    /// simplify the graph by combining edges
-    pub fn simplify(&mut self, keep: &[K]) {
-        // TODO something is still unstable inside this function...
-        // sometimes is just randomly fails at asserts in my tiny example case.
-        loop {
-            // remove nodes, which are bidirectionally connected to one node and connected to a second node.
-            // problem: iterate over all nodes, but also mutate multiple nodes!
-            // I don't know a better Rust solution, than cloning the keys into a vec... :(
-            let keys: Vec<_> = self.nodes.keys().filter(|k| !keep.contains(k)).cloned().collect();
-            let key_map = &mut HashMap::<K, (K, u64)>::new(); // when keys get removed, save their new replacement key and the added length here
-            let mut incoming_edges = HashMap::<K, Vec<K>>::new();
-            for (key, node) in &self.nodes {
-                for (next_key, _) in &node.next {
-                    if let Some(incoming) = incoming_edges.get_mut(next_key) {
-                        incoming.push(key.clone());
-                    }
-                    else {
-                        incoming_edges.insert(next_key.clone(), vec![key.clone()]);
-                    }
-                }
+impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug, S: BuildHasher> DirectedGraph<K, S> {
+    /// simplify the graph by contracting corridors: a node, which isn't kept and is connected to exactly two other nodes,
+    /// is removed and every way through it (without turning around) becomes a single edge with the summed weight.
+    /// The nodes are visited in the order of their keys and the edges are sorted in the end,
+    /// so the result is the same no matter in which order the hash map has the nodes.
+    pub fn simplify(&mut self, keep: &[K]) where K: Ord {
+        // the incoming edges are built once and then kept up to date while removing nodes
+        let mut incoming = HashMap::<K, Vec<(K, u64)>>::new();
+        for (key, node) in &self.nodes {
+            for (next_key, w) in &node.next {
+                incoming.entry(next_key.clone()).or_default().push((key.clone(), *w));
             }
-            let mut removed_key = false;
-            for key in &keys {
-                // clone again to release borrow on self.nodes... :(
-                let node_next: Vec<_> = self.nodes.get_mut(key).unwrap().next.iter_mut().map(|edge| {
-                    let (key, add) = rget(key_map, &edge.0);
-                    let res = (key.clone(), edge.1 + add);
-                    *edge = res.clone(); // save the found shortcuts
-                    res
-                    
-                }).collect();
-                // if this node has more than two edges (of which one needs to be bi), it can not be simplified
-                let mut edges: HashSet<_> = incoming_edges[key].iter().cloned().collect();
-                edges.extend(node_next.iter().map(|edge| edge.0.clone()));
-                if edges.len() > 2 {
-                    continue;
-                }
-                let bi = node_next.iter().enumerate().find(|(_, edge)| self.nodes[&edge.0].next.iter().find(|edge2| rget(key_map, &edge2.0).0 == key).is_some());
-                if let Some((i, bi_edge)) = bi {
-                    let (bi_key, mut here_to_bi_weight) = bi_edge.clone();
-                    // situation:
-                    // found a bidirectional connection with this node
-                    // -> reduce that bidirection connection by removing this node (merge nodes)
-                    // -> add the weight of the bidirectional connection to all connections to the other node
-                    // let the edge from #bi point to all #other
-                    let (bi_key, added_weight) = rget(key_map, &bi_key);
-                    here_to_bi_weight += added_weight;
-                    {
-                        let bi_next = &mut self.nodes.get_mut(&bi_key).unwrap().next;
-                        let bi_to_here = bi_next.iter().position(|edge| rget(key_map, &edge.0).0 == key).unwrap();
-                        let (bi_key2, mut bi_to_here_weight) = bi_next.swap_remove(bi_to_here);
-                        bi_to_here_weight += rget(key_map, &bi_key2).1;
-                        assert_eq!(bi_to_here_weight, here_to_bi_weight);
-                        for other_i in 0..node_next.len() {
-                            if other_i == i {
-                                continue;
-                            }
-                            let (other_key, here_to_other_weight) = &node_next[other_i];
-                            bi_next.push((other_key.clone(), bi_to_here_weight + here_to_other_weight));
-                        }
-                    }
-                    // let the edges other_to_here point from #other to #bi
-                    key_map.insert(key.clone(), (bi_key.clone(), here_to_bi_weight));
-                    // remove the node
-                    self.nodes.remove(key);
-                    removed_key = true;
-                }
+        }
+        let mut todo: BTreeSet<K> = self.nodes.keys().filter(|key| !keep.contains(key)).cloned().collect();
+        while let Some(key) = todo.pop_first() {
+            let Some(node) = self.nodes.get(&key) else {
+                continue;
+            };
+            let incoming_edges = incoming.get(&key).map_or(&[][..], Vec::as_slice);
+            let mut neighbors: Vec<K> = incoming_edges.iter().chain(&node.next).map(|(k, _)| k.clone()).collect();
+            neighbors.sort();
+            neighbors.dedup();
+            if neighbors.len() != 2 || neighbors.contains(&key) {
+                continue;
             }
-            if !removed_key {
-                break; // no nodes removed
+            // remove the node with all its edges
+            let node = self.nodes.remove(&key).unwrap();
+            let incoming_edges = incoming.remove(&key).unwrap_or_default();
+            for (from, _) in &incoming_edges {
+                self.nodes.get_mut(from).unwrap().next.retain(|(k, _)| k != &key);
             }
-            // make the key_map real
-            for (key, node) in &mut self.nodes {
-                assert!(!key_map.contains_key(key));
-                for (nkey, w) in &mut node.next {
-                    while let Some((new_key, added_weight)) = key_map.get(nkey) {
-                        assert_ne!(new_key, nkey);
-                        *nkey = new_key.clone();
-                        *w += added_weight;
+            for (to, _) in &node.next {
+                incoming.get_mut(to).unwrap().retain(|(k, _)| k != &key);
+            }
+            // and connect the neighbors through it
+            for (from, w1) in &incoming_edges {
+                for (to, w2) in &node.next {
+                    if from != to {
+                        self.nodes.get_mut(from).unwrap().next.push((to.clone(), w1 + w2));
+                        incoming.entry(to.clone()).or_default().push((from.clone(), w1 + w2));
                     }
                 }
             }
+            // the neighbors have a new neighbor now, so they might have become corridors
+            todo.extend(neighbors.into_iter().filter(|neighbor| !keep.contains(neighbor)));
+        }
+        for node in self.nodes.values_mut() {
+            node.next.sort();
         }
     }
 
@@ -213,7 +199,6 @@ impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug> DirectedGraph<K> {
             }
         }
         let mut start_keys: Vec<_> = self.nodes.keys().filter(|key| !incoming_edges.contains_key(key)).cloned().collect();
-        println!("{} starting node(s) in the graph", start_keys.len());
         let mut sorted = vec![];
         while let Some(key) = start_keys.pop() {
             sorted.push(key.clone());
@@ -222,7 +207,7 @@ impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug> DirectedGraph<K> {
                 if let Some(index) = incoming.iter().position(|from_key| from_key == &key) {
                     incoming.swap_remove(index);
                     incoming_edges_count -= 1;
-                    if incoming.len() == 0 {
+                    if incoming.is_empty() {
                         start_keys.push(next_key.clone());
                     }
                 }
@@ -230,7 +215,6 @@ impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug> DirectedGraph<K> {
             }
         }
         if incoming_edges_count != 0 {
-            println!("{incoming_edges_count} remaining edge(s) in the graph");
             None
         }
         else {
@@ -295,9 +279,9 @@ impl<K: PartialEq + Eq + Hash + Clone + std::fmt::Debug> DirectedGraph<K> {
     }
 }
 
-pub fn add_line_to_graph(input: &str, graph: &mut DirectedGraph<Point>, row: &mut isize, use_slopes: bool) {
-    for (i, mut c) in input.chars().enumerate() {
-        let i = i as isize;
+pub fn add_line_to_graph(input: &[char], graph: &mut DirectedGraph<Point>, row: &mut i64, use_slopes: bool) {
+    for (i, mut c) in input.iter().copied().enumerate() {
+        let i = i as i64;
         if c != '#' {
             if !use_slopes {
                 c = '.';
@@ -369,24 +353,67 @@ pub fn add_line_to_graph(input: &str, graph: &mut DirectedGraph<Point>, row: &mu
     *row += 1;
 }
 
-/*
-#.#######
+#[cfg(test)]
+const SMALL_EXAMPLE: &str = "#.#######
 #.......#
 #####v#v#
 ###.....#
 ###v#####
 ###.>...#
 ###v###.#
-###.#####
+###.#####";
 
-longest path 17
+#[test]
+pub fn small_example() {
+    // this used to make simplify panic sometimes
+    let map = parse(SMALL_EXAMPLE).unwrap();
+    assert_eq!(part1(&map), 17.into());
+}
 
-this makes simplify panic sometimes!
-No problem on the actual input however.
-*/
+/// the map with the entrance in the top row and the exit in the bottom row.
+pub struct HikingMap {
+    pub grid: Grid<char>,
+    pub start: Point,
+    pub end: Point,
+}
 
-#[test]
-pub fn part1() {
+pub fn parse(input: &str) -> Result<HikingMap, ParseError> {
+    let grid = Grid::parse(input, "a path ., forest # or a slope > < ^ v", |c| matches!(c, '#' | '.' | '>' | '<' | '^' | 'v').then_some(c))?;
+    // find start in the top row and end in the bottom row
+    let last = grid.height() - 1;
+    let start = grid.row(0).iter().position(|&c| c == '.').ok_or(ParseError::new(1, "First row must have an entrance to the maze"))?;
+    let end = grid.row(last).iter().position(|&c| c == '.').ok_or(ParseError::new(last + 1, "The maze must have an exit on the last line."))?;
+    // the slopes need to be on a straight section, otherwise the graph can't be built
+    for (p, &c) in grid.iter() {
+        let Some(dir) = Direction::from_arrow(c) else {
+            continue;
+        };
+        let open = |q: Point| grid.get(q).is_some_and(|&c| c != '#');
+        if open(p - Point(1, 0)) != dir.is_horizontal() || open(p - Point(0, 1)) == dir.is_horizontal() {
+            return Err(ParseError::new(p.1 as usize + 1, "slopes need to be on a straight section").at_column(p.0 as usize + 1));
+        }
+    }
+    Ok(HikingMap { start: Point(start as i64, 0), end: Point(end as i64, last as i64), grid })
+}
+
+/// the graph with a node for every tile of the trails.
+pub fn dense_graph(map: &HikingMap, use_slopes: bool) -> DirectedGraph<Point> {
+    let mut graph: DirectedGraph<Point> = DirectedGraph::new();
+    let mut row = 0;
+    for line in map.grid.rows() {
+        add_line_to_graph(line, &mut graph, &mut row, use_slopes);
+    }
+    graph
+}
+
+/// build the simplified graph of the hiking trails.
+pub fn build_graph(map: &HikingMap, use_slopes: bool) -> DirectedGraph<Point> {
+    let mut graph = dense_graph(map, use_slopes);
+    graph.simplify(&[map.start, map.end]);
+    graph
+}
+
+pub fn part1(map: &HikingMap) -> Answer {
     // idea: build a weighted directed graph and then find the longest path in it.
     // to build the graph, build a dense graph first row by row, then simplify it.
     // still working on the ideas to find the longest path...
@@ -394,42 +421,39 @@ pub fn part1() {
     // also at this point I partly regret my decision to not use libraries,
     // as this is all simple implemented stuff in graph libraries.
 
-    let mut graph: DirectedGraph<Point> = DirectedGraph::new();
-    let mut start = None; // find start in the top row
-    let mut end = None; // find start in the top row
-    let mut row = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = std::io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        if start.is_none() {
-            start = Some(input.chars().enumerate().find_map(|(i, c)| if c == '.' { Some(i) } else { None }).expect("First row must have an entrance to the maze"));
-        }
-        end = input.chars().enumerate().find_map(|(i, c)| if c == '.' { Some(i) } else { None });
-        add_line_to_graph(input, &mut graph, &mut row, true);
-    }
-    let start = Point(start.expect("Input must have at least one line.") as isize, 0);
-    let end = Point(end.expect("The maze must have an exit on the last line.") as isize, row - 1);
-    println!("The original graph has {} nodes", graph.nodes.len());
-    
-    graph.simplify(&[start, end]);
-    if false {
-        // debug code
-        for (key, node) in &graph.nodes {
-            print!("({}, {}) -> ", key.0, key.1);
-            for next in &node.next {
-                print!("({}, {})[{}], ", next.0.0, next.0.1, next.1);
-            }
-            println!();
-        }
-    }
-    println!("The simplified graph has {} nodes", graph.nodes.len());
+    let graph = build_graph(map, true);
+    let length = graph.find_longest_simple_path(&map.start, &map.end);
+    length.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
 
-    let length = graph.find_longest_simple_path(&start, &end);
-    println!("longest path length is {length}");
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 94.into());
 }
 
 /*
@@ -468,36 +492,85 @@ In the example above, this increases the longest hike to 154 steps:
 Find the longest hike you can take through the surprisingly dry hiking trails listed on your map. How many steps long is the longest hike?
 */
 
-#[test]
-pub fn part2() {
+pub fn part2(map: &HikingMap) -> Answer {
     // you must be kidding!
     // now it's not a DAG anymore!
 
     // 6298 is the answer
 
-    let mut graph: DirectedGraph<Point> = DirectedGraph::new();
-    let mut start = None; // find start in the top row
-    let mut end = None; // find start in the top row
-    let mut row = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = std::io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
+    let graph = build_graph(map, false);
+    let length = graph.find_longest_simple_path(&map.start, &map.end);
+    length.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 154.into());
+}
+
+/// a hash map order, which is different for every seed, but always the same for the same seed.
+#[cfg(test)]
+#[derive(Clone, Copy)]
+struct Seeded(u64);
+
+#[cfg(test)]
+impl BuildHasher for Seeded {
+    type Hasher = SeededHasher;
+    fn build_hasher(&self) -> SeededHasher {
+        SeededHasher(self.0.wrapping_mul(6364136223846793005) ^ 0xcbf29ce484222325)
+    }
+}
+
+/// FNV-1a starting from the seed
+#[cfg(test)]
+struct SeededHasher(u64);
+
+#[cfg(test)]
+impl std::hash::Hasher for SeededHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
         }
-        let input = input.trim();
-        if start.is_none() {
-            start = Some(input.chars().enumerate().find_map(|(i, c)| if c == '.' { Some(i) } else { None }).expect("First row must have an entrance to the maze"));
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[test]
+pub fn simplify_is_stable() {
+    for (input, slopes_length, length) in [(SMALL_EXAMPLE, 17, 17), (EXAMPLE, 94, 154)] {
+        let map = parse(input).unwrap();
+        for (use_slopes, expected_length) in [(true, slopes_length), (false, length)] {
+            let dense = dense_graph(&map, use_slopes);
+            let expected = build_graph(&map, use_slopes);
+            let mut expected_edges: Vec<_> = expected.nodes.iter().map(|(key, node)| (*key, node.next.clone())).collect();
+            expected_edges.sort();
+            let mut keys: Vec<Point> = dense.nodes.keys().copied().collect();
+            keys.sort();
+            let mut random = crate::Random::new(1);
+            for seed in 0..100 {
+                // insert the nodes and edges in a different order every time
+                let mut nodes = HashMap::with_hasher(Seeded(seed));
+                for i in 0..keys.len() {
+                    let j = i + random.below(keys.len() - i);
+                    keys.swap(i, j);
+                }
+                for key in &keys {
+                    let mut node = dense.nodes[key].clone();
+                    node.next.reverse();
+                    let shift = seed as usize % node.next.len().max(1);
+                    node.next.rotate_left(shift);
+                    nodes.insert(*key, node);
+                }
+                let mut graph = DirectedGraph { nodes };
+                graph.simplify(&[map.start, map.end]);
+                let mut edges: Vec<_> = graph.nodes.iter().map(|(key, node)| (*key, node.next.clone())).collect();
+                edges.sort();
+                assert_eq!(edges, expected_edges, "seed {seed}");
+                assert_eq!(graph.find_longest_simple_path(&map.start, &map.end), expected_length, "seed {seed}");
+            }
         }
-        end = input.chars().enumerate().find_map(|(i, c)| if c == '.' { Some(i) } else { None });
-        add_line_to_graph(input, &mut graph, &mut row, false);
     }
-    let start = Point(start.expect("Input must have at least one line.") as isize, 0);
-    let end = Point(end.expect("The maze must have an exit on the last line.") as isize, row - 1);
-    println!("The original graph has {} nodes", graph.nodes.len());
-    graph.simplify(&[start, end]);
-    println!("The simplified graph has {} nodes", graph.nodes.len());
-
-    let length = graph.find_longest_simple_path(&start, &end);
-    println!("longest path length is {length}");
-}
\ No newline at end of file
+}
@@ -70,6 +70,28 @@ However, you'll need to search a much larger test area if you want to see if any
 Considering only the X and Y axes, check all pairs of hailstones' future paths for intersections. How many of these intersections occur within the test area?
 */
 
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 24;
+    const TITLE: &'static str = "Never Tell Me The Odds";
+    type Input = Vec<Ray>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 type N = i128;
 
 
@@ -120,6 +142,7 @@ impl Vec3 {
     }
 }
 
+#[derive(Clone)]
 pub struct Ray {
     pos: Vec3,
     dir: Vec3,
@@ -132,17 +155,22 @@ impl Ray {
 }
 
 impl TryFrom<&str> for Ray {
-    type Error = ();
+    type Error = ParseError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let (pos, dir) = value.split_once('@').ok_or(())?;
-        let mut pos = pos.split(',').filter_map(|num| num.trim().parse().ok());
-        let pos = Vec3(pos.next().ok_or(())?, pos.next().ok_or(())?, pos.next().ok_or(())?);
-        let mut dir = dir.split(',').filter_map(|num| num.trim().parse().ok());
-        let dir = Vec3(dir.next().ok_or(())?, dir.next().ok_or(())?, dir.next().ok_or(())?);
-        Ok(Ray::new(pos, dir))
+        let (pos, dir) = value.split_once('@').ok_or(ParseError::unexpected(0, value, &value[value.len()..], "@ between the position and the velocity"))?;
+        Ok(Ray::new(parse_vec3(value, pos)?, parse_vec3(value, dir)?))
     }
 }
 
+/// parse the numbers "x, y, z" in a part of the line.
+fn parse_vec3(line: &str, part: &str) -> Result<Vec3, ParseError> {
+    let numbers: Vec<&str> = part.split(',').map(str::trim).collect();
+    let [x, y, z] = numbers[..] else {
+        return Err(ParseError::unexpected(0, line, part, "3 numbers like \"19, 13, 30\""));
+    };
+    Ok(Vec3(parse_number(0, line, x)?, parse_number(0, line, y)?, parse_number(0, line, z)?))
+}
+
 /// find the integer intersection of two rays.
 /// returns the intersection point x with the condition
 /// x <= intersection < x+1
@@ -181,24 +209,28 @@ pub fn intersect_2d(a: &Ray, b: &Ray) -> Option<(Vec3, (bool, bool))> {
          p.1 * det == xydet.0 * a.dir.1)))
 }
 
-#[test]
-pub fn part1() {
+pub fn parse(input: &str) -> Result<Vec<Ray>, ParseError> {
     let mut rays: Vec<Ray> = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = std::io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let input = input.trim();
-        rays.push(input.try_into().expect("failed to parse line"));
+        let line = line.trim();
+        rays.push(Ray::try_from(line).map_err(|err| err.within(i + 1, line, line))?);
+    }
+    // part 2 needs 3 hailstones to determine the throw
+    if rays.len() < 3 {
+        return Err(ParseError::new(rays.len(), "expected at least 3 hailstones"));
     }
+    Ok(rays)
+}
 
-    let min = 200000000000000i64 as N;
-    let max = 400000000000000i64 as N;
-    //let min = 7i64;
-    //let max = 27i64;
+pub fn part1(rays: &[Ray]) -> Answer {
+    count_intersections(rays, 200000000000000, 400000000000000).into()
+}
 
+/// count the pairs of hailstones, whose future paths cross inside the test area min..=max in x and y.
+pub fn count_intersections(rays: &[Ray], min: N, max: N) -> usize {
     // test all combinations
     let mut count = 0;
     for (i, a) in rays.iter().enumerate() {
@@ -212,7 +244,19 @@ pub fn part1() {
             }
         }
     }
-    println!("found {count} intersections");
+    count
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(count_intersections(&parse(EXAMPLE).unwrap(), 7, 27), 2);
 }
 
 /*
@@ -268,7 +312,7 @@ pub fn gcd(mut a: N, mut b: N) -> N {
 pub fn linsolve(mut mat: [[N; 9]; 9], mut b: [N; 9]) -> Option<[N; 9]> {
     // simplify all rows (divide by largest common divisor)
     for i in 0..9 {
-        let div = gcd(mat[i].iter().copied().reduce(|a, b| gcd(a, b)).unwrap(), b[i]);
+        let div = gcd(mat[i].iter().copied().reduce(gcd).unwrap(), b[i]);
         mat[i].iter_mut().for_each(|x| *x /= div);
         b[i] /= div;
     }
@@ -296,15 +340,16 @@ pub fn linsolve(mut mat: [[N; 9]; 9], mut b: [N; 9]) -> Option<[N; 9]> {
             let div = gcd(d, r);
             let mul_d = r / div;
             let mul_r = d / div;
-            for k in 0..9 {
-                mat[j][k] = mat[j][k] * mul_r - mat[i][k] * mul_d;
+            let row_i = mat[i];
+            for (x, x_i) in mat[j].iter_mut().zip(row_i) {
+                *x = *x * mul_r - x_i * mul_d;
             }
             b[j] = b[j] * mul_r - b[i] * mul_d;
         }
         
         // simplify all rows (divide by largest common divisor)
         for i in 0..9 {
-            let div = gcd(mat[i].iter().copied().reduce(|a, b| gcd(a, b)).unwrap(), b[i]);
+            let div = gcd(mat[i].iter().copied().reduce(gcd).unwrap(), b[i]);
             mat[i].iter_mut().for_each(|x| *x /= div);
             b[i] /= div;
         }
@@ -411,8 +456,7 @@ pub fn hailstone_smasher_throw(a: &Ray, b: &Ray, c: &Ray) -> Ray {
     ray
 }
 
-#[test]
-pub fn part2() {
+pub fn part2(rays: &[Ray]) -> Answer {
     // if I understand correctly, the hailstones are guaranteed
     // to collide at integer coordinates and times!
     // That means they are all constructed in a certain way to make this possible.
@@ -427,21 +471,16 @@ pub fn part2() {
     // So in principle 3 hailstones are enough to determine the ray.
     // for the rest I will just have to check if it holds true.
 
-    let mut rays: Vec<Ray> = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = std::io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        rays.push(input.try_into().expect("failed to parse line"));
-    }
+    let mut rays = rays.to_vec();
 
     // find 3 good ones with small positions
     // -> got lucky and it worked, however it's very much at it's numerical limit here!
-    rays.sort_unstable_by_key(|ray| (ray.pos.0.abs() + ray.pos.1.abs() + ray.pos.2.abs()));
+    rays.sort_unstable_by_key(|ray| ray.pos.0.abs() + ray.pos.1.abs() + ray.pos.2.abs());
     let r = hailstone_smasher_throw(&rays[0], &rays[1], &rays[2]);
-    println!("The ray that hits all hailstones is\n{}, {}, {} @ {}, {}, {}", r.pos.0, r.pos.1, r.pos.2, r.dir.0, r.dir.1, r.dir.2);
-    println!("The xyz sum is {}", r.pos.0 + r.pos.1 + r.pos.2);
-}
\ No newline at end of file
+    (r.pos.0 + r.pos.1 + r.pos.2).into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 47.into());
+}
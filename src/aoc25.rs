@@ -50,6 +50,29 @@ Multiplying the sizes of these groups together produces 54.
 Find the three wires you need to disconnect in order to divide the components into two separate groups. What do you get if you multiply the sizes of these two groups together?
 */
 
+use crate::{Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 25;
+    const TITLE: &'static str = "Snowverload";
+    const SLOW_PARTS: &'static [usize] = &[1];
+    type Input = Graph;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug)]
@@ -69,6 +92,12 @@ pub struct Graph {
     pub nodes: Vec<Node>,
 }
 
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Graph {
     pub fn new() -> Self {
         Graph { nodes: vec![] }
@@ -140,7 +169,7 @@ impl Graph {
         // O(V E log E) complexity
         while a_vec.len() < self.nodes.len() {
             last = a_vec.last().copied();
-            let mut a_next: Vec<_> = a_vec.iter().map(|j| &self.nodes[*j].next).flatten().filter(|(i, _)| !a_set.contains(i)).copied().collect();
+            let mut a_next: Vec<_> = a_vec.iter().flat_map(|j| &self.nodes[*j].next).filter(|(i, _)| !a_set.contains(i)).copied().collect();
             a_next.sort_unstable_by_key(|(i, _)| *i);
             a_next.dedup_by(|a, b| {
                 if a.0 == b.0 {
@@ -174,7 +203,6 @@ impl Graph {
         let mut best_cut_weight = self.nodes.len() * self.nodes.len();
         for _ in 1..self.nodes.len() {
             let (weight, new_cut) = self.minimum_phase_cut();
-            println!("weight {weight} with cut-size {}", new_cut.len());
             if weight < best_cut_weight {
                 best_cut = new_cut;
                 best_cut_weight = weight;
@@ -208,43 +236,64 @@ e: f
 f: d
 */
 
-#[test]
-pub fn part1() {
-    // typical graph problem again
-    // This can be solved with the minimum cut of the graph, as that will be <= 3
-
+pub fn parse(input: &str) -> Result<Graph, ParseError> {
     let mut graph = Graph::new();
     let mut node_names = HashMap::new();
-    loop {
-        let mut input = String::new();
-        let read_bytes = std::io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let input = input.trim();
-        let (from, to) = input.split_once(":").expect("missing :, invalid line");
+        let line = line.trim();
+        let (from, to) = line.split_once(":").ok_or(ParseError::new(i + 1, "missing :, invalid line"))?;
         let from = from.trim();
         let from = get_index(&mut node_names, from);
         for to in to.split(' ') {
             let to = to.trim();
-            if to.len() > 0 {
+            if !to.is_empty() {
                 let to = get_index(&mut node_names, to);
                 graph.add_edge(from, to, 1);
             }
         }
     }
-    let mut rev_node_names = HashMap::new();
-    for (k, v) in node_names {
-        rev_node_names.insert(v, k);
-    }
+    Ok(graph)
+}
+
+pub fn part1(graph: &Graph) -> Answer {
+    // typical graph problem again
+    // This can be solved with the minimum cut of the graph, as that will be <= 3
+
     // do a partition of the graph
     let graph_len = graph.nodes.len();
-    let cut = graph.min_cut(3);
-    let cut_named: Vec<_> = cut.iter().map(|x| &rev_node_names[x]).collect();
-    //println!("{n}, {cut_named:?}");
-    println!("{cut_named:?}");
-    println!("The cut resulted in a region sized {} and {}", cut.len(), graph_len - cut.len());
-    println!("multiplied sizes: {}", cut.len() * (graph_len - cut.len()));
+    let cut = graph.clone().min_cut(3);
+    (cut.len() * (graph_len - cut.len())).into()
+}
+
+#[test]
+pub fn example_part1() {
+    let input = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+    assert_eq!(part1(&parse(input).unwrap()), 54.into());
+    // my simple test case (2 triangles)
+    // they are connected by a single wire, so stop at a cut of 1 instead of 3,
+    // otherwise the cut around a single node with its 2 wires is good enough.
+    let input = "a: b
+b: c
+c: a d
+d: e
+e: f
+f: d";
+    assert_eq!(parse(input).unwrap().min_cut(1).len(), 3);
 }
 
 /*
@@ -269,4 +318,9 @@ push the button to restart the system.
 You supply all fifty stars and restart global snow production!
 
 As you reach the edge of Snow Island, you can already tell from way up here that everyone will have a white Christmas this year after all.
-*/
\ No newline at end of file
+*/
+
+pub fn part2(_graph: &Graph) -> Answer {
+    // there is no second puzzle on the last day, just push the button
+    Answer::Text("Merry Christmas!".into())
+}
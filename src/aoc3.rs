@@ -34,180 +34,116 @@ In this schematic, two numbers are not part numbers because they are not adjacen
 Of course, the actual engine schematic is much larger. What is the sum of all of the part numbers in the engine schematic?
 */
 
+use crate::grid::{Grid, Point};
+use crate::{Answer, ParseError, Solution};
 
-pub fn is_part(c: char) -> bool {
-    !c.is_digit(10) && !c.is_whitespace() && c != '.'
-}
+pub struct Day;
 
-use std::str::Chars;
+impl Solution for Day {
+    const DAY: usize = 3;
+    const TITLE: &'static str = "Gear Ratios";
+    type Input = Grid<char>;
 
-#[derive(Clone, Copy, Debug)]
-pub enum NumberState<T> {
-    None,
-    Started(T),
-    Partial(T),
-    Finished(T),
-}
-
-impl<T> NumberState<T> {
-    pub fn is_started(&self) -> bool {
-        match self {
-            NumberState::Started(_) => true,
-            _ => false,
-        }
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-    pub fn is_finished(&self) -> bool {
-        match self {
-            NumberState::Finished(_) => true,
-            _ => false,
-        }
-    }
-    pub fn is_some(&self) -> bool {
-        match self {
-            NumberState::None => false,
-            _ => true,
-        }
-    }
-    pub fn unwrap(self) -> Option<T> {
-        match self {
-            NumberState::Started(num) => Some(num),
-            NumberState::Partial(num) => Some(num),
-            NumberState::Finished(num) => Some(num),
-            NumberState::None => None,
-        }
-    }
-    pub fn unwrap_finished(self) -> Option<T> {
-        match self {
-            NumberState::Finished(num) => Some(num),
-            _ => None,
-        }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
     }
-    pub fn unfinished(self) -> NumberState<T> {
-        match self {
-            NumberState::Started(num) => NumberState::Started(num),
-            NumberState::Partial(num) => NumberState::Partial(num),
-            _ => NumberState::None,
-        }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
     }
 }
 
-/// an iterator over chars, which detects positive number as it goes.
-pub struct NumberIterator<'a> {
-    base: Chars<'a>,
-    current_num: Option<u32>,
+
+pub fn is_part(c: char) -> bool {
+    !c.is_ascii_digit() && !c.is_whitespace() && c != '.'
+}
+
+/// a number in the schematic, which goes `len` digits to the right from `start`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Number {
+    pub start: Point,
+    pub len: usize,
+    pub value: u32,
 }
 
-impl Iterator for NumberIterator<'_> {
-    type Item = (char, NumberState<u32>);
-    fn next(&mut self) -> Option<Self::Item> {
-        let c = self.base.next()?;
-        let state = if let Some(num) = self.current_num {
-            if !c.is_digit(10) {
-                // finish number
-                self.current_num = None;
-                NumberState::Finished(num)
+impl Number {
+    /// the positions of the digits
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.len as i64).map(|i| Point(self.start.0 + i, self.start.1))
+    }
+}
+
+/// all the numbers in the schematic, row by row.
+pub fn numbers(schematic: &Grid<char>) -> Vec<Number> {
+    let mut numbers = vec![];
+    for (y, row) in schematic.rows().enumerate() {
+        let mut current: Option<Number> = None;
+        for (x, c) in row.iter().enumerate() {
+            if let Some(digit) = c.to_digit(10) {
+                let number = current.get_or_insert(Number { start: Point(x as i64, y as i64), len: 0, value: 0 });
+                number.len += 1;
+                number.value = number.value * 10 + digit;
             }
-            else {
-                // extend number
-                let num = num * 10 + c.to_digit(10).unwrap();
-                self.current_num = Some(num);
-                NumberState::Partial(num)
+            else if let Some(number) = current.take() {
+                numbers.push(number);
             }
         }
-        else if c.is_digit(10) {
-            let num = c.to_digit(10).unwrap();
-            self.current_num = Some(num);
-            NumberState::Started(num)
-        }
-        else {
-            NumberState::None
-        };
-        Some((c, state))
+        // numbers can end at the end of the row
+        numbers.extend(current);
     }
+    numbers
 }
 
-pub fn read_numbers(line: &str) -> NumberIterator {
-    NumberIterator { base: line.chars(), current_num: None }
+/// read the schematic, which needs to be rectangular.
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input, "a digit, a symbol or .", |c| (!c.is_whitespace()).then_some(c))
 }
 
-#[test]
-pub fn part1() {
-    // idea: still go line by line, but this time keep the last line around
-    // then do:
-    // 1. check if unused numbers from the last line match parts of the current line
-    // 2. check if numbers from the current line match parts from the last line and remove these numbers
-    // the only issue with this idea is, that I need to parse the numbers twice, but I consider that OK.
-    
-    use std::io;
+pub fn part1(schematic: &Grid<char>) -> Answer {
+    // idea: find all the numbers and check the 8 neighbors of every digit for parts.
+    let sum: u64 = numbers(schematic).iter()
+        .filter(|number| number.points()
+            .flat_map(|p| p.neighbors_diagonal())
+            .any(|p| schematic.get(p).is_some_and(|&c| is_part(c))))
+        .map(|number| number.value as u64)
+        .sum();
+    sum.into()
+}
 
-    let mut sum: u64 = 0;
-    let mut last_line = None;
-    loop {
-        let mut line = String::new();
-        let read_bytes = io::stdin().read_line(&mut line).expect("Failed to read line");
-        if line.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        line = line.trim().into();
-        line.push('.'); // add a . to never end on a number (simplifies stuff)
-        if last_line.is_none() {
-            last_line = ".".repeat(line.len()).into();
-        }
-        // check that all lines have equal length (otherwise I will need to fill them up with .)
-        assert!(line.len() == last_line.as_ref().unwrap().len());
-        let mut line_buffer = String::with_capacity(line.len());
-        let mut bot_number_confirmed = false;
-        let mut top_number_confirmed = false;
-        let mut last_bot_part = false;
-        let mut last_top_part = false;
-        for ((bot_c, bot_num), (top_c, top_num))
-                in read_numbers(&line).zip(read_numbers(&last_line.unwrap())) {
-            if let NumberState::Finished(num) = bot_num {
-                // finish number
-                if bot_number_confirmed || is_part(bot_c) || is_part(top_c) {
-                    sum += num as u64;
-                    // remove the number from the line buffer and replace it with .
-                    let num_length = line_buffer.chars().rev().enumerate().find_map(|(i, c)|
-                        if c.is_digit(10) { None }
-                        else { Some(i) }).unwrap_or(line_buffer.len());
-                    assert!(num_length > 0);
-                    line_buffer.drain(line_buffer.len()-num_length..);
-                    for _ in 0..num_length {
-                        line_buffer.push('.');
-                    }
-                }
-                bot_number_confirmed = false;
-            }
-            if let NumberState::Finished(num) = top_num {
-                // finish number
-                if top_number_confirmed || is_part(bot_c) {
-                    sum += num as u64;
-                }
-                top_number_confirmed = false;
-            }
+#[cfg(test)]
+const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
 
-            if bot_num.is_started() {
-                bot_number_confirmed = last_bot_part || last_top_part;
-            }
-            else if is_part(bot_c) {
-                bot_number_confirmed |= bot_num.is_some();
-                top_number_confirmed |= top_num.is_some() || top_c.is_digit(10);
-            }
-            if top_num.is_started() {
-                top_number_confirmed = last_bot_part | is_part(bot_c);
-            }
-            else if is_part(top_c) {
-                bot_number_confirmed |= bot_num.is_some();
-                top_number_confirmed |= top_num.is_some();
-            }
-            last_bot_part = is_part(bot_c);
-            last_top_part = is_part(top_c);
-            line_buffer.push(bot_c);
-        }
-        //println!("{line_buffer} {sum}");
-        last_line = Some(line_buffer);
-    }
-    println!("The engine part sum is {sum}");
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 4361.into());
+    // my extended example with numbers at the edges and next to each other
+    let input = "467..114..
+...*......
+..35..633.
+...*..#...
+617.1..1..
+......+.58
+.592.1....
+....+.754.
+...$.*....
+.664.598..
+.5.5..7.7.
+..$.....*.
+.664.598..";
+    assert_eq!(part1(&parse(input).unwrap()), 5642.into());
 }
 
 /*
@@ -241,136 +177,29 @@ In this schematic, there are two gears. The first is in the top left; it has par
 What is the sum of all of the gear ratios in your engine schematic?
 */
 
-/// WIP gear during parsing
-pub struct Gear {
-    left: [Option<u32>; 3],
-    right: [NumberState<u32>; 3],
-}
-
-impl Gear {
-    pub fn is_valid(&self) -> bool {
-        // valid if there is exactly 2 numbers
-        let mut count = 0;
-        for i in 0..3 {
-            count += self.left[i].is_some() as u32;
-            count += self.right[i].is_some() as u32;
-        }
-        count == 2
-    }
-    pub fn ratio(&self) -> u64 {
-        // find the two numbers and add them up
-        let mut count = 0;
-        let mut ratio = 1;
-        for i in 0..3 {
-            if let Some(num) = self.left[i] {
-                ratio *= num as u64;
-                count += 1;
-            }
-            if let NumberState::Finished(num) = self.right[i] {
-                ratio *= num as u64;
-                count += 1;
-            }
+pub fn part2(schematic: &Grid<char>) -> Answer {
+    // idea: label every digit with the number it belongs to,
+    // then a gear is a * with exactly 2 different labels around it.
+    let numbers = numbers(schematic);
+    let mut labels = schematic.map(|_| None);
+    for (i, number) in numbers.iter().enumerate() {
+        for p in number.points() {
+            labels[p] = Some(i);
         }
-        assert!(count == 2, "gear wasn't a finished valid gear");
-        ratio
     }
-    pub fn update(&mut self, right: [NumberState<u32>; 3]) -> bool {
-        let mut count_finished = 0;
-        for i in 0..3 {
-            match self.right[i] {
-                NumberState::Partial(_) | NumberState::Started(_) => self.right[i] = right[i],
-                _ => (),
-            }
-            match self.right[i] {
-                NumberState::Finished(_) | NumberState::None => count_finished += 1,
-                _ => (),
-            }
+    let mut sum: u64 = 0;
+    for (p, _) in schematic.iter().filter(|(_, &c)| c == '*') {
+        let mut adjacent: Vec<usize> = p.neighbors_diagonal().iter().filter_map(|&p| labels.get(p).copied().flatten()).collect();
+        adjacent.sort();
+        adjacent.dedup();
+        if let [a, b] = adjacent[..] {
+            sum += numbers[a].value as u64 * numbers[b].value as u64;
         }
-        count_finished == 3
     }
+    sum.into()
 }
 
 #[test]
-pub fn part2() {
-    // idea: do it in a scan like in last solution but use 3 lines here.
-    // start with 2 empty lines
-    // 1. read the 3 lines simultaneously and detect numbers like before
-    // 2. when a gear is encountered in the center, check how many numbers are around
-    //    if it's 2, then do the ratio thing
-    // 3. when a gear was encountered in the center at last char and not used, check again
-    // 4. end with an additional empty line
-    use std::io;
-
-    let mut sum: u64 = 0;
-    let mut last_line: Option<String> = None;
-    let mut last2_line = None;
-    loop {
-        let mut line = String::new();
-        let read_bytes = io::stdin().read_line(&mut line).expect("Failed to read line");
-        let end = line.len() <= 1 || read_bytes == 0;
-        if end {
-            line = ".".repeat(last_line.as_ref().unwrap().len()).into();
-        }
-        else {
-            line = line.trim().into();
-            line.push('.'); // add a . to never end on a number (simplifies stuff)
-        }
-        if last_line.is_none() {
-            assert!(last2_line.is_none());
-            last_line = ".".repeat(line.len()).into();
-            last2_line = ".".repeat(line.len()).into();
-        }
-        // check that all lines have equal length (otherwise I will need to fill them up with .)
-        assert!(line.len() == last_line.as_ref().unwrap().len());
-        assert!(line.len() == last2_line.as_ref().unwrap().len());
-        let mut last_center = '.';
-        let mut last_gear_data = [Some(0); 3];
-        let mut gears: Vec<Gear> = vec![];
-        for (i1, (i2, i3)) in read_numbers(last2_line.as_ref().unwrap()).zip(read_numbers(last_line.as_ref().unwrap()).zip(read_numbers(&line))) {
-            let (_, num1) = i1;
-            let (c2, num2) = i2;
-            let (_, num3) = i3;
-            if last_center == '*' {
-                // here all the necessary data is already collected
-                // except for the numbers at the right (which none of them are finished at this point)
-                last_gear_data = [
-                    last_gear_data[0].or(num1.unwrap_finished()),
-                    last_gear_data[1].or(num2.unwrap_finished()),
-                    last_gear_data[2].or(num3.unwrap_finished()),
-                ];
-                let gear = Gear {
-                    left: last_gear_data,
-                    right: [num1.unfinished(), num2.unfinished(), num3.unfinished()],
-                };
-                if gear.is_valid() {
-                    gears.push(gear);
-                }
-            }
-            if c2 == '*' {
-                // collect all the data to complete it in the next character read
-                last_gear_data = [
-                    num1.unwrap_finished(),
-                    num2.unwrap_finished(),
-                    num3.unwrap_finished(),
-                ];
-            }
-            // update the existing gears
-            gears.retain_mut(|gear| {
-                if gear.update([num1, num2, num3]) {
-                    sum += gear.ratio();
-                    false
-                }
-                else {
-                    true
-                }
-            });
-            last_center = c2;
-        }
-        if end {
-            break;
-        }
-        last2_line = last_line;
-        last_line = Some(line);
-    }
-    println!("The gear ratio sum is {sum}");
-}
\ No newline at end of file
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 467835.into());
+}
@@ -37,33 +37,73 @@ So, in this example, the Elf's pile of scratchcards is worth 13 points.
 Take a seat in the large pile of colorful cards. How many points are they worth in total?
 */
 
-#[test]
-pub fn part1() {
-    use std::io;
-    use std::collections::HashSet;
+use crate::{parse_number, Answer, ParseError, Solution};
+use std::collections::HashSet;
 
-    let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 4;
+    const TITLE: &'static str = "Scratchcards";
+    type Input = Vec<[HashSet<u32>; 2]>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+/// parse the winning numbers and the numbers you have for each card.
+pub fn parse(input: &str) -> Result<Vec<[HashSet<u32>; 2]>, ParseError> {
+    let mut cards = vec![];
+    for (line_index, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let input = &input.trim_start_matches(|c| c != ':')[1..];
+        let (_, numbers) = line.split_once(':').ok_or(ParseError::new(line_index + 1, "missing : after the card number"))?;
         let mut data = [HashSet::new(), HashSet::new()];
-        for (i, list) in input.trim().split("|").enumerate() {
+        for (i, list) in numbers.trim().split("|").enumerate().take(2) {
             for elem in list.split(" ") {
-                if elem.len() > 0 {
-                    let num = elem.parse::<u32>().expect("Failed to parse number");
-                    assert!(!data[i].contains(&num));
-                    data[i].insert(num);
+                if !elem.is_empty() {
+                    let num = parse_number(line_index + 1, line, elem)?;
+                    if !data[i].insert(num) {
+                        return Err(ParseError::new(line_index + 1, format!("the number {num} is listed twice")));
+                    }
                 }
             }
         }
+        cards.push(data);
+    }
+    Ok(cards)
+}
+
+pub fn part1(cards: &[[HashSet<u32>; 2]]) -> Answer {
+    let mut sum = 0;
+    for data in cards {
         let matches = data[0].intersection(&data[1]).count();
         sum += (1u64 << matches) >> 1;
     }
-    println!("The elf has won {sum} points");
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 13.into());
 }
 
 /*
@@ -99,31 +139,12 @@ Once all of the originals and copies have been processed, you end up with 1 inst
 Process all of the original and copied scratchcards until no more scratchcards are won. Including the original set of scratchcards, how many total scratchcards do you end up with?
 */
 
-#[test]
-pub fn part2() {
-    use std::io;
-    use std::collections::HashSet;
+pub fn part2(cards: &[[HashSet<u32>; 2]]) -> Answer {
     use std::collections::VecDeque;
 
     let mut card_count = 0;
     let mut copy_counter: VecDeque<u64> = VecDeque::new();
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = &input.trim_start_matches(|c| c != ':')[1..];
-        let mut data = [HashSet::new(), HashSet::new()];
-        for (i, list) in input.trim().split("|").enumerate() {
-            for elem in list.split(" ") {
-                if elem.len() > 0 {
-                    let num = elem.parse::<u32>().expect("Failed to parse number");
-                    assert!(!data[i].contains(&num));
-                    data[i].insert(num);
-                }
-            }
-        }
+    for data in cards {
         let multiply = copy_counter.pop_front().unwrap_or(1);
         let matches = data[0].intersection(&data[1]).count();
         // make sure copy_counter is long enough to hold matches
@@ -136,5 +157,10 @@ pub fn part2() {
         }
         card_count += multiply;
     }
-    println!("The elf has won {card_count} scratchcards");
-}
\ No newline at end of file
+    card_count.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 30.into());
+}
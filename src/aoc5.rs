@@ -101,8 +101,73 @@ So, the lowest location number in this example is 35.
 What is the lowest location number that corresponds to any of the initial seed numbers?
 */
 
-#[test]
-pub fn part1() {
+use crate::interval::IntervalSet;
+use std::ops::RangeInclusive;
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 5;
+    const TITLE: &'static str = "If You Give A Seed A Fertilizer";
+    type Input = Almanac;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+/// the seeds and the conversion tables in order.
+/// Each table row is (destination start, source start, length).
+pub struct Almanac {
+    pub seeds: Vec<i64>,
+    pub tables: Vec<Vec<[i64; 3]>>,
+}
+
+pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+    let mut seeds = None;
+    let mut tables: Vec<Vec<[i64; 3]>> = vec![];
+    for (i, line) in input.lines().enumerate() {
+        if seeds.is_none() {
+            // wait for seeds first, no seeds is an error
+            if let Some(numbers) = line.strip_prefix("seeds:") {
+                let tokens: Vec<&str> = numbers.split_whitespace().collect();
+                // part 2 reads them as pairs of start and length
+                if !tokens.len().is_multiple_of(2) {
+                    let last = tokens[tokens.len() - 1];
+                    return Err(ParseError::new(i + 1, "the seeds don't come in pairs of start and length").at_column(crate::column_of(line, last)));
+                }
+                seeds = Some(tokens.iter()
+                    .map(|num| parse_number::<i64>(i + 1, line, num))
+                    .collect::<Result<Vec<_>, _>>()?);
+            }
+        }
+        // expect the conversion tables to come in correct order
+        else if line.trim().ends_with(":") {
+            // new table
+            tables.push(vec![]);
+        }
+        else if line.starts_with(|c: char| c.is_ascii_digit()) {
+            // number row of the table with meaning
+            // dest index, source index, length
+            let numbers: Vec<i64> = line.split_whitespace().map(|s| parse_number(i + 1, line, s)).collect::<Result<_, _>>()?;
+            let row = numbers.try_into().map_err(|numbers| ParseError::new(i + 1, format!("expected 3 numbers, got {numbers:?}")))?;
+            tables.last_mut().ok_or(ParseError::new(i + 1, "table row before the first table"))?.push(row);
+        }
+    }
+    let seeds = seeds.filter(|seeds| !seeds.is_empty()).ok_or(ParseError::new(0, "no seeds available"))?;
+    Ok(Almanac { seeds, tables })
+}
+
+pub fn part1(almanac: &Almanac) -> Answer {
     // idea: make a custom type for the mapping
     // -> assuming the mapping is correct, sort the source list and use binary search for each lookup
     // then read in all the data into the mappings
@@ -110,68 +175,73 @@ pub fn part1() {
     // - read in the seeds
     // - read in the tables in order and always just convert the current numbers from the last table directly
 
-    use std::io;
-    use std::time::Instant;
-    let start = Instant::now();
-
-    let mut last_locations = vec![];
-    let mut locations = vec![];
-    let mut last_location_len = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if read_bytes == 0 {
-            break; // only break on EOF (Ctrl+D)
-        }
-        if last_location_len == 0 {
-            // wait for seeds first, no seeds is an error
-            if input.starts_with("seeds:") {
-                last_locations.extend(input
-                    .trim_start_matches("seeds:")
-                    .trim()
-                    .split(' ')
-                    .filter_map(|num| num.parse::<i64>().ok()));
-                last_location_len = last_locations.len();
-            }
-        }
-        else {
-            // expect the conversion tables to come in correct order
-            if input.trim().ends_with(":") {
-                // new table
-                locations.extend(last_locations);
-                assert_eq!(locations.len(), last_location_len);
-                last_locations = locations;
-                last_locations.sort(); // sort here! last_locations is always sorted!
-                //println!("{last_locations:?}");
-                locations = vec![];
-            }
-            else if input.chars().next().unwrap().is_digit(10) {
-                // number row of the table with meaning
-                // source index, dest index, length
-                let numbers: Vec<i64> = input.trim().split(' ').filter_map(|s| s.parse().ok()).collect();
-                assert_eq!(numbers.len(), 3, "numbers = {numbers:?}");
-                let src = numbers[1];
-                let add = numbers[0] - src;
-                let len = numbers[2];
-                // find the range in last_locations, which gets mapped
-                let start = match last_locations.binary_search(&src) {
-                    Ok(index) => index,
-                    Err(index) => index,
-                };
-                let end = match last_locations[start..].binary_search(&(src + len)) {
-                    Ok(index) => index,
-                    Err(index) => index,
-                } + start;
-                locations.extend(last_locations.drain(start..end).map(|x| x + add));
-            }
+    let mut locations = almanac.seeds.clone();
+    let location_len = locations.len();
+    for table in &almanac.tables {
+        // new table
+        let mut last_locations = locations;
+        last_locations.sort(); // sort here! last_locations is always sorted!
+        //println!("{last_locations:?}");
+        locations = vec![];
+        for &[dest, src, len] in table {
+            let add = dest - src;
+            // find the range in last_locations, which gets mapped
+            let start = match last_locations.binary_search(&src) {
+                Ok(index) => index,
+                Err(index) => index,
+            };
+            let end = match last_locations[start..].binary_search(&(src + len)) {
+                Ok(index) => index,
+                Err(index) => index,
+            } + start;
+            locations.extend(last_locations.drain(start..end).map(|x| x + add));
         }
+        locations.extend(last_locations);
+        assert_eq!(locations.len(), location_len);
     }
-    locations.extend(last_locations);
-    assert_eq!(locations.len(), last_location_len);
     //println!("{locations:?}");
 
-    println!("The lowest location number is {}", locations.iter().min().expect("no locations available"));
-    println!("finished in {:?}", Instant::now() - start);
+    (*locations.iter().min().unwrap()).into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 35.into());
 }
 
 /*
@@ -192,146 +262,180 @@ In the above example, the lowest location number can be obtained from seed numbe
 Consider all of the initial seed numbers listed in the ranges on the first line of the almanac. What is the lowest location number that corresponds to any of the initial seed numbers?
 */
 
-/// Interval with invariant start < end, start inclusive, end exclusive.
-#[derive(Clone, Copy, PartialEq, Eq)]
-pub struct Interval {
-    start: i64,
-    end: i64,
+/// A map from numbers to numbers like the ones in the almanac: every number is moved by an offset,
+/// which is constant on pieces of the number line. Numbers outside of all table rows keep their value.
+/// Maps can be chained into one map, so the whole almanac becomes a single seed to location map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlmanacMap {
+    // (start, offset) sorted by start, each piece goes until the start of the next one.
+    // The first piece starts at i64::MIN, so every number is in exactly one piece,
+    // and neighboring pieces always have different offsets.
+    pieces: Vec<(i64, i64)>,
 }
 
-impl Interval {
-    pub fn new(start: i64, end: i64) -> Interval {
-        assert!(start < end);
-        Interval { start, end }
+impl AlmanacMap {
+    /// the map which doesn't change anything.
+    pub fn identity() -> Self {
+        AlmanacMap { pieces: vec![(i64::MIN, 0)] }
     }
-    pub fn new_checked(start: i64, end: i64) -> Option<Interval> {
-        if start < end {
-            Some(Interval { start, end })
+
+    /// the map of one table with rows (destination start, source start, length).
+    /// If rows overlap, the first one wins, just like in part 1.
+    pub fn from_table(table: &[[i64; 3]]) -> Self {
+        let mut map = Self::identity();
+        for &[dest, src, len] in table.iter().rev() {
+            map.set(src, src + len, dest - src);
         }
-        else {
-            None
+        map
+    }
+
+    /// use the offset for all numbers in start..end.
+    fn set(&mut self, start: i64, end: i64, offset: i64) {
+        if start >= end {
+            return;
         }
+        let offset_after = self.pieces[self.piece_index(end)].1;
+        let mut pieces: Vec<_> = self.pieces.iter().copied().filter(|&(s, _)| s < start).collect();
+        pieces.push((start, offset));
+        pieces.push((end, offset_after));
+        pieces.extend(self.pieces.iter().copied().filter(|&(s, _)| s > end));
+        *self = Self::merged(pieces);
     }
 
-    pub fn start(&self) -> i64 {
-        self.start
+    /// join the neighboring pieces with the same offset.
+    fn merged(mut pieces: Vec<(i64, i64)>) -> Self {
+        pieces.dedup_by(|next, previous| next.1 == previous.1);
+        AlmanacMap { pieces }
     }
 
-    pub fn end(&self) -> i64 {
-        self.end
+    fn piece_index(&self, x: i64) -> usize {
+        self.pieces.partition_point(|&(start, _)| start <= x) - 1
     }
 
-    /// Subtract an interval from this one.
-    /// This can result in up to 2 intervals, which are returned in order.
-    pub fn sub(&self, other: Interval) ->[Option<Interval>; 2] {
-        [Interval::new_checked(self.start, other.start.min(self.end)),
-         Interval::new_checked(other.end.max(self.start), self.end)]
+    /// the exclusive end of the piece, the last one goes until after i64::MAX.
+    fn piece_end(&self, index: usize) -> i128 {
+        self.pieces.get(index + 1).map_or(i64::MAX as i128 + 1, |&(start, _)| start as i128)
     }
 
-    /// Compute the intersection of this interval with the other interval.
-    /// Since Intervals can't be empty, return None in case the intersection is empty.
-    pub fn intersection(&self, other: Interval) -> Option<Interval> {
-        Interval::new_checked(self.start.max(other.start), self.end.min(other.end))
+    /// the pieces as (numbers, offset).
+    pub fn pieces(&self) -> impl Iterator<Item = (RangeInclusive<i64>, i64)> + '_ {
+        self.pieces.iter().enumerate().map(|(i, &(start, offset))| (start..=(self.piece_end(i) - 1) as i64, offset))
     }
-}
 
-impl std::ops::Add<i64> for Interval {
-    type Output = Interval;
-    fn add(self, rhs: i64) -> Self::Output {
-        Interval { start: self.start + rhs, end: self.end + rhs }
+    /// map a single number.
+    pub fn apply(&self, x: i64) -> i64 {
+        x + self.pieces[self.piece_index(x)].1
+    }
+
+    /// the map which first applies this map and then the next one.
+    pub fn then(&self, next: &AlmanacMap) -> AlmanacMap {
+        let mut pieces = vec![];
+        for (i, &(start, offset)) in self.pieces.iter().enumerate() {
+            let end = self.piece_end(i);
+            // the image of this piece gets split up by the pieces of the next map
+            let mut x = start as i128;
+            while x < end {
+                let j = next.piece_index((x + offset as i128) as i64);
+                pieces.push((x as i64, offset + next.pieces[j].1));
+                x = end.min(next.piece_end(j) - offset as i128);
+            }
+        }
+        Self::merged(pieces)
     }
-}
 
-impl std::fmt::Debug for Interval {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}, {})", self.start, self.end)
+    /// the map which undoes this one. None if two numbers map to the same one,
+    /// which means that some numbers aren't reached at all as well.
+    pub fn inverse(&self) -> Option<AlmanacMap> {
+        // the images of the pieces have to fit together without gaps or overlaps
+        let mut images: Vec<_> = self.pieces.iter().enumerate().map(|(i, &(start, offset))| {
+            (start as i128 + offset as i128, self.piece_end(i) + offset as i128, offset)
+        }).collect();
+        images.sort_unstable();
+        let mut expected_start = i64::MIN as i128;
+        for &(start, end, _) in &images {
+            if start != expected_start {
+                return None;
+            }
+            expected_start = end;
+        }
+        (expected_start == i64::MAX as i128 + 1).then(|| {
+            Self::merged(images.into_iter().map(|(start, _, offset)| (start as i64, -offset)).collect())
+        })
+    }
+
+    /// the set of numbers, which the given numbers map to.
+    pub fn image(&self, numbers: &IntervalSet<i64>) -> IntervalSet<i64> {
+        let mut image = IntervalSet::new();
+        for (range, offset) in self.pieces() {
+            image = image.union(&numbers.intersection(&range.into()).shift(offset));
+        }
+        image
+    }
+
+    /// the set of numbers, which map to one of the given numbers.
+    pub fn preimage(&self, numbers: &IntervalSet<i64>) -> IntervalSet<i64> {
+        let mut preimage = IntervalSet::new();
+        for (range, offset) in self.pieces() {
+            let image = IntervalSet::from((range.start() + offset)..=(range.end() + offset));
+            preimage = preimage.union(&numbers.intersection(&image).shift(-offset));
+        }
+        preimage
+    }
+
+    /// the smallest number, that one of the given numbers maps to.
+    pub fn min_over(&self, numbers: &IntervalSet<i64>) -> Option<i64> {
+        self.pieces().filter_map(|(range, offset)| Some(numbers.intersection(&range.into()).min()? + offset)).min()
     }
 }
 
-pub fn volume(v: &Vec<Interval>) -> u64 {
-    v.iter().map(|i| (i.end() - i.start()) as u64).sum()
+impl Almanac {
+    /// all tables chained together into the map from seeds to locations.
+    pub fn map(&self) -> AlmanacMap {
+        self.tables.iter().fold(AlmanacMap::identity(), |map, table| map.then(&AlmanacMap::from_table(table)))
+    }
 }
 
-#[test]
-pub fn part2() {
+pub fn part2(almanac: &Almanac) -> Answer {
     // actually... the custom type wasn't needed!
     // I now need to consider intervals.
-    // -> creating an interval type is the cleanest solution
-
-    use std::io;
-    use std::time::Instant;
-    let start = Instant::now();
-
-    let mut last_locations = vec![];
-    let mut locations = vec![];
-    let mut last_location_len = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if read_bytes == 0 {
-            break; // only break on EOF (Ctrl+D)
-        }
-        if last_location_len == 0 {
-            // wait for seeds first, no seeds is an error
-            if input.starts_with("seeds:") {
-                let numbers: Vec<_> = input
-                        .trim_start_matches("seeds:")
-                        .trim()
-                        .split(' ')
-                        .filter_map(|num| num.parse::<i64>().ok())
-                        .collect();
-                last_locations.extend(numbers
-                    .chunks_exact(2)
-                    .map(|c| Interval::new(c[0], c[0] + c[1])));
-                last_location_len = volume(&last_locations);
-            }
-        }
-        else {
-            // expect the conversion tables to come in correct order
-            if input.trim().ends_with(":") {
-                // new table
-                locations.extend(last_locations);
-                assert_eq!(volume(&locations), last_location_len);
-                last_locations = locations;
-                last_locations.sort_by_key(|i| i.start); // sort here! last_locations is always sorted!
-                // for optimal performance one would also want to combine touching intervals here, but I omitted that.
-                //println!("{last_locations:?}");
-                locations = vec![];
-            }
-            else if input.chars().next().unwrap().is_digit(10) {
-                // number row of the table with meaning
-                // source index, dest index, length
-                let numbers: Vec<i64> = input.trim().split(' ').filter_map(|s| s.parse().ok()).collect();
-                assert_eq!(numbers.len(), 3, "numbers = {numbers:?}");
-                let src = numbers[1];
-                let add = numbers[0] - src;
-                let len = numbers[2];
-                let src_interval = Interval::new(src, src + len);
-                // find the range in last_locations, which gets mapped
-                let start = match last_locations.binary_search_by_key(&(src + 1), |i| i.end()) {
-                    Ok(index) => index,
-                    Err(index) => index,
-                };
-                let end = match last_locations[start..].binary_search_by_key(&(src + len), |i| i.start()) {
-                    Ok(index) => index,
-                    Err(index) => index,
-                } + start;
-                let mut remains = vec![];
-                locations.extend(last_locations.drain(start..end).map(|x| {
-                    remains.push(x.sub(src_interval));
-                    x.intersection(src_interval).map(|i| i + add)
-                }).filter_map(|x| x));
-                // put the splits back in
-                last_locations.splice(start..start, remains.into_iter().flatten().filter_map(|i| i));
-                //assert!(last_locations.is_sorted_by_key(|i| i.start()));
-                //assert!(last_locations.is_sorted_by_key(|i| i.end()));
-            }
-        }
+    // -> a set of intervals is the cleanest solution.
+    // And the tables can be chained into one map first, which then only needs to be checked once.
+
+    let seeds: IntervalSet<i64> = almanac.seeds.chunks_exact(2).map(|c| c[0]..c[0] + c[1]).collect();
+    almanac.map().min_over(&seeds).expect("no seed ranges available").into()
+}
+
+#[test]
+pub fn almanac_map() {
+    let almanac = parse(EXAMPLE).unwrap();
+    let map = almanac.map();
+    // seed 79 -> soil 81 -> fertilizer 81 -> water 81 -> light 74 -> temperature 78 -> humidity 78 -> location 82
+    for (seed, location) in [(79, 82), (14, 43), (55, 86), (13, 35), (82, 46)] {
+        assert_eq!(map.apply(seed), location);
     }
-    locations.extend(last_locations);
-    assert_eq!(volume(&locations), last_location_len);
-    //println!("{locations:?}");
+    // chaining gives the same as going through the tables one by one
+    for seed in -10..200 {
+        let location = almanac.tables.iter().fold(seed, |x, table| AlmanacMap::from_table(table).apply(x));
+        assert_eq!(map.apply(seed), location);
+    }
+    // the almanac maps are bijective, so they can be inverted
+    let inverse = map.inverse().unwrap();
+    assert_eq!(inverse.apply(46), 82);
+    assert_eq!(map.then(&inverse), AlmanacMap::identity());
+    assert_eq!(inverse.then(&map), AlmanacMap::identity());
+    // which of the seeds from part 2 end up in the locations 40..50
+    let seeds = IntervalSet::from_iter([79..93, 55..68]);
+    let found = map.preimage(&IntervalSet::from(40..50)).intersection(&seeds);
+    assert!(found.ranges().flatten().all(|seed| (40..50).contains(&map.apply(seed))));
+    assert!(found.contains(&82));
+    assert_eq!(map.image(&found).min(), map.min_over(&seeds));
+    // rows which map two ranges onto the same numbers can't be inverted
+    assert_eq!(AlmanacMap::from_table(&[[0, 10, 5]]).inverse(), None);
+}
 
-    println!("The lowest location number is {}", locations.iter().map(|i| i.start()).min().expect("no locations available"));
-    println!("finished in {:?}", Instant::now() - start);
-}
\ No newline at end of file
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 46.into());
+    let err = parse(&EXAMPLE.replacen(" 13", "", 1)).err().unwrap();
+    assert_eq!((err.line, err.column, err.message.as_str()), (1, 14, "the seeds don't come in pairs of start and length"));
+}
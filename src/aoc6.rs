@@ -46,6 +46,28 @@ To see how much margin of error you have, determine the number of ways you can b
 Determine the number of ways you could beat the record in each race. What do you get if you multiply these numbers together?
 */
 
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 6;
+    const TITLE: &'static str = "Wait For It";
+    type Input = Races;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 pub fn win_range(t: i64, d: i64) -> (i64, i64) {
     // the equation for the distance with the press time x and time t is
     // d = (t - x) * 1mm/ms * x
@@ -75,35 +97,58 @@ pub fn win_range(t: i64, d: i64) -> (i64, i64) {
     (x_min, x_max)
 }
 
-#[test]
-pub fn part1() {
-    use std::io;
+/// the race times and record distances in the order of the races.
+pub struct Races {
+    pub times: Vec<i64>,
+    pub distances: Vec<i64>,
+}
 
-    let mut prod = 1;
-    let mut times: Vec<_> = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+pub fn parse(input: &str) -> Result<Races, ParseError> {
+    let mut times = None;
+    let mut distances = None;
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        if input.starts_with("Time:") {
-            let input = input[5..].trim();
-            times.extend(input.split(' ').filter_map(|x| x.parse::<i64>().ok()));
+        let numbers = |input: &str| input.split_whitespace().map(|x| parse_number::<i64>(i + 1, line, x)).collect::<Result<Vec<_>, _>>();
+        if let Some(input) = line.strip_prefix("Time:") {
+            times = Some(numbers(input)?);
         }
-        else if input.starts_with("Distance:") {
-            let input = input[9..].trim();
-            assert!(times.len() != 0, "Times need to be specified first");
-            for (&t, d) in times.iter().zip(input.split(' ').filter_map(|x| x.parse::<i64>().ok())) {
-                // now compute the two borders using floating point number
-                // then check the exact number around the floating point approximation
-                let (x_min, x_max) = win_range(t, d);
-                //println!("[{x_min} {x_max}]");
-                prod *= x_max - x_min + 1;
-            }
+        else if let Some(input) = line.strip_prefix("Distance:") {
+            distances = Some(numbers(input)?);
         }
     }
-    println!("The product of the ranges is {prod}");
+    let times: Vec<_> = times.ok_or(ParseError::new(0, "no times were specified"))?;
+    let distances: Vec<_> = distances.ok_or(ParseError::new(0, "no distances were specified"))?;
+    if times.len() != distances.len() {
+        return Err(ParseError::new(0, "there needs to be a distance for every time"));
+    }
+    Ok(Races { times, distances })
+}
+
+pub fn part1(races: &Races) -> Answer {
+    let mut prod = 1;
+    for (&t, &d) in races.times.iter().zip(&races.distances) {
+        // now compute the two borders using floating point number
+        // then check the exact number around the floating point approximation
+        let (x_min, x_max) = win_range(t, d);
+        //println!("[{x_min} {x_max}]");
+        prod *= x_max - x_min + 1;
+    }
+    prod.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+#[test]
+pub fn example_part1() {
+    // the ways to win the three races are 4, 8 and 9
+    assert_eq!(win_range(7, 9), (2, 5));
+    assert_eq!(win_range(15, 40), (4, 11));
+    assert_eq!(win_range(30, 200), (11, 19));
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 288.into());
 }
 
 /*
@@ -126,27 +171,19 @@ Now, you have to figure out how many ways there are to win this single race. In
 How many ways can you beat the record in this one much longer race?
 */
 
-#[test]
-pub fn part2() {
-    use std::io;
-
-    let mut dist = 0;
-    let mut time = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        if input.starts_with("Time:") {
-            let input = input[5..].trim();
-            time = input.replace(" ", "").parse().expect("parsing time failed");
-        }
-        else if input.starts_with("Distance:") {
-            let input = input[9..].trim();
-            dist = input.replace(" ", "").parse().expect("parsing time failed");
-        }
-    }
+/// join the numbers, as if the spaces between them weren't there.
+pub fn join_numbers(numbers: &[i64]) -> i64 {
+    numbers.iter().map(|x| x.to_string()).collect::<String>().parse().expect("the joined number is too large")
+}
+
+pub fn part2(races: &Races) -> Answer {
+    let time = join_numbers(&races.times);
+    let dist = join_numbers(&races.distances);
     let (x_min, x_max) = win_range(time, dist);
-    println!("The range is {}", x_max - x_min + 1);
-}
\ No newline at end of file
+    (x_max - x_min + 1).into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 71503.into());
+}
@@ -56,6 +56,28 @@ Now, you can determine the total winnings of this set of hands by adding up the
 Find the rank of every hand in your set. What are the total winnings?
 */
 
+use crate::{parse_number, Answer, ParseError, Solution};
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 7;
+    const TITLE: &'static str = "Camel Cards";
+    type Input = Vec<(Hand, u64)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 use std::cmp::*;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -77,7 +99,7 @@ impl Card {
 
 impl PartialOrd for Card {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -105,11 +127,11 @@ pub struct Hand {
 
 impl Hand {
     pub fn new(s: &str) -> Hand {
-        Hand { cards: s.chars().map(|c| Card(c)).collect::<Vec<Card>>().try_into().expect(&format!("{s} isn't a valid hand")) }
+        Hand { cards: s.chars().map(Card).collect::<Vec<Card>>().try_into().unwrap_or_else(|_| panic!("{s} isn't a valid hand")) }
     }
 
     pub fn kind(&self) -> u32 {
-        let mut c = self.cards.clone();
+        let mut c = self.cards;
         c.sort();
         if c[0] == c[4] {
             6 // 5 equal
@@ -136,7 +158,7 @@ impl Hand {
 
 impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -153,23 +175,40 @@ impl Ord for Hand {
     }
 }
 
-#[test]
-pub fn part1() {
-    use std::io;
-
+/// parse the hands with their bids.
+pub fn parse(input: &str) -> Result<Vec<(Hand, u64)>, ParseError> {
     let mut hands = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let (hand, bid) = input.trim().split_once(' ').expect("can't split the line in two");
-        hands.push((Hand::new(hand), bid.parse::<u64>().ok().unwrap()));
+        let (hand, bid) = line.trim().split_once(' ').ok_or(ParseError::new(i + 1, "can't split the line in two"))?;
+        if hand.chars().count() != 5 || !hand.chars().all(|c| matches!(c, '2'..='9' | 'T' | 'J' | 'Q' | 'K' | 'A')) {
+            return Err(ParseError::unexpected(i + 1, line, hand, "a hand of 5 cards out of 23456789TJQKA"));
+        }
+        let bid = parse_number(i + 1, line, bid)?;
+        hands.push((Hand::new(hand), bid));
     }
+    Ok(hands)
+}
+
+pub fn part1(hands: &[(Hand, u64)]) -> Answer {
+    let mut hands = hands.to_vec();
     hands.sort_by(|a, b| a.0.cmp(&b.0));
     let sum: u64 = hands.into_iter().enumerate().map(|(i, (_, bid))| bid * (i as u64 + 1)).sum();
-    println!("The bid sum is {sum}");
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 6440.into());
 }
 
 /*
@@ -221,7 +260,7 @@ impl Card {
 
 impl Hand {
     pub fn kind_j(&self) -> u32 {
-        let mut c = self.cards.clone();
+        let mut c = self.cards;
         c.sort_by(|a, b| a.cmp_j(b));
         let kind = if c[3].j() {
             Kind::Five
@@ -327,7 +366,7 @@ pub fn test_kinds() {
                         if i2 == i0 || i2 == i1 { continue; }
                         for i3 in 0..5 {
                             if i3 == i0 || i3 == i1 || i3 == i2 { continue; }
-                            let i4 = (0 + 1 + 2 + 3 + 4) - (i0 + i1 + i2 + i3);
+                            let i4 = (1 + 2 + 3 + 4) - (i0 + i1 + i2 + i3);
                             let perm_hand = Hand { cards: [hand.cards[i0], hand.cards[i1], hand.cards[i2], hand.cards[i3], hand.cards[i4]] };
                             assert_eq!(kind, perm_hand.kind_j(), "failed on hand {:?}", perm_hand.cards);
                         }
@@ -338,10 +377,7 @@ pub fn test_kinds() {
     }
 }
 
-#[test]
-pub fn part2() {
-    use std::io;
-
+pub fn part2(hands: &[(Hand, u64)]) -> Answer {
     // story here:
     // I got this incorrect...
     // with the tie breaking rule J < all
@@ -353,17 +389,13 @@ pub fn part2() {
     // now that it's correct, with tie breaking rule J < all
     // I got 246894760 (finally correct)
 
-    let mut hands = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let (hand, bid) = input.trim().split_once(' ').expect("can't split the line in two");
-        hands.push((Hand::new(hand), bid.parse::<u64>().ok().unwrap()));
-    }
+    let mut hands = hands.to_vec();
     hands.sort_by(|a, b| a.0.cmp_j(&b.0));
     let sum: u64 = hands.into_iter().enumerate().map(|(i, (_, bid))| bid * (i as u64 + 1)).sum();
-    println!("The bid sum is {sum}");
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 5905.into());
+}
@@ -34,54 +34,99 @@ ZZZ = (ZZZ, ZZZ)
 Starting at AAA, follow the left/right instructions. How many steps are required to reach ZZZ?
 */
 
+use crate::crt::{self, Congruence};
+use crate::cycle;
+use crate::{Answer, ParseError, Solution};
+use std::collections::HashMap;
+
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 8;
+    const TITLE: &'static str = "Haunted Wasteland";
+    type Input = Network;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Left = 0,
     Right = 1,
 }
 
-#[test]
-pub fn part1() {
-    use std::io;
-    use std::collections::HashMap;
+/// the left/right instructions and the nodes with their left and right neighbors.
+pub struct Network {
+    pub path: Vec<Direction>,
+    pub nodes: HashMap<String, [String; 2]>,
+}
 
+/// the network. AAA and ZZZ aren't required, as the ghosts of part 2 start at every node ending in A.
+pub fn parse(input: &str) -> Result<Network, ParseError> {
     let mut nodes = HashMap::new();
     let mut path = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if nodes.len() > 0 && input.len() <= 1 || read_bytes == 0 {
+    for (i, line) in input.lines().enumerate() {
+        if !nodes.is_empty() && line.is_empty() {
             break;
         }
-        let input = input.trim();
-        if input.len() == 0 {
+        let line = line.trim();
+        if line.is_empty() {
             continue;
         }
-        if path.len() == 0 {
-            path.extend(input.chars().map(|c| {
-                match c {
+        if path.is_empty() {
+            for (j, c) in line.char_indices() {
+                path.push(match c {
                     'L' => Direction::Left,
                     'R' => Direction::Right,
-                    _ => panic!("invalid letter {c} encountered in path"),
-                }
-            }));
+                    _ => return Err(ParseError::unexpected(i + 1, line, &line[j..j + c.len_utf8()], "L or R")),
+                });
+            }
         }
         else {
-            let (src, dst) = input.split_once('=').expect("can't split the line at =");
-            let dst = dst.trim();
-            let (dst_l, dst_r) = dst[1..dst.len()-1].split_once(',').expect("can't split the line at ,");
+            let (src, dst) = line.split_once('=').ok_or(ParseError::new(i + 1, "can't split the line at ="))?;
+            let (dst_l, dst_r) = dst.trim()
+                .strip_prefix('(')
+                .and_then(|dst| dst.strip_suffix(')'))
+                .and_then(|dst| dst.split_once(','))
+                .ok_or(ParseError::new(i + 1, "expected the destinations as (left, right)"))?;
             let src = src.trim().to_string();
             let dst_l = dst_l.trim().to_string();
             let dst_r = dst_r.trim().to_string();
             nodes.insert(src, [dst_l, dst_r]);
         }
     }
+    if let Some(unknown) = nodes.values().flatten().find(|dst| !nodes.contains_key(*dst)) {
+        return Err(ParseError::new(0, format!("the node {unknown} is used as a destination, but never defined")));
+    }
+    if path.is_empty() {
+        return Err(ParseError::new(0, "there are no left/right instructions"));
+    }
+    Ok(Network { path, nodes })
+}
+
+pub fn part1(network: &Network) -> Answer {
+    let Network { path, nodes } = network;
     // Now start the algorithm!
     // All we need to do is follow the instructions until we find ZZZ.
     // Since we are in a finite graph with no dead ends, we will necessarily run into a cycle.
     // The input seems to guarantee, that this cycle will contain ZZZ if we start from AAA.
     // Hoping the naive solution is fine, just go through the path until ZZZ is reached.
-    let res = path.iter().cycle().enumerate().try_fold("AAA", |node, (i, instruction)| {
+    if !nodes.contains_key("AAA") {
+        return Answer::Text("there is no node named AAA".into());
+    }
+    // after going through every (node, position in the path) once, the walk only repeats itself
+    let max_steps = path.len() * nodes.len();
+    let res = path.iter().cycle().take(max_steps).enumerate().try_fold("AAA", |node, (i, instruction)| {
         let next = &nodes[node][*instruction as usize][..];
         if next == "ZZZ" {
             Err(i + 1)
@@ -89,8 +134,44 @@ pub fn part1() {
         else {
             Ok(next)
         }
-    }).err().unwrap();
-    println!("{res} steps are needed to reach ZZZ from AAA");
+    });
+    match res {
+        Err(steps) => steps.into(),
+        Ok(_) => Answer::Text("ZZZ can't be reached from AAA".into()),
+    }
+}
+
+#[test]
+pub fn example_part1() {
+    let input = "RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)";
+    assert_eq!(part1(&parse(input).unwrap()), 2.into());
+    let input = "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+    assert_eq!(part1(&parse(input).unwrap()), 6.into());
+}
+
+#[test]
+pub fn parse_errors() {
+    let message = |input: &str| parse(input).err().unwrap().message;
+    assert_eq!(message(""), "there are no left/right instructions");
+    assert_eq!(message("AAA = (AAA, AAA)"), "unexpected \"A\"");
+    assert_eq!(message("L\n\nAAA = (AAA, BBB)"), "the node BBB is used as a destination, but never defined");
+    // part 1 can't be solved without AAA or ZZZ, but part 2 doesn't need them
+    let day = crate::day(8).unwrap();
+    assert_eq!(day.solve(1, "L\n\nBBA = (BBZ, BBA)\nBBZ = (BBZ, BBZ)"), Ok(Answer::Text("there is no node named AAA".into())));
+    assert_eq!(day.solve(1, "L\n\nAAA = (BBB, ZZZ)\nBBB = (AAA, BBB)\nZZZ = (ZZZ, ZZZ)"), Ok(Answer::Text("ZZZ can't be reached from AAA".into())));
+    assert_eq!(day.solve(2, "L\n\nBBA = (BBZ, BBA)\nBBZ = (BBZ, BBZ)"), Ok(1.into()));
 }
 
 /*
@@ -130,70 +211,10 @@ So, in this example, you end up entirely on nodes that end in Z after 6 steps.
 Simultaneously start on every node that ends with A. How many steps does it take before you're only on nodes that end with Z?
 */
 
-// greatest common divisor (euclidean algorithm)
-pub fn gcd(mut a: u64, mut b: u64) -> u64 {
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-    a
-}
-
-// extended euclidean algorithm
-pub fn xgcd(mut a: u64, mut b: u64) -> (u64, i64, i64) {
-    // return (g, x, y) such that a*x + b*y = g = gcd(a, b)
-    let mut x0 = 0;
-    let mut x1 = 1;
-    let mut y0 = 1;
-    let mut y1 = 0;
-    while a != 0 {
-        let q;
-        (q, a, b) = ((b / a) as i64, b % a, a);
-        (y0, y1) = (y1, y0 - q * y1); // for b >= 2^63 this q * y1 can be outside of i64 range, but still in u64
-        (x0, x1) = (x1, x0 - q * x1);
-    }
-    (b, x0, y0)
-}
-
-#[test]
-pub fn part2() {
-    use std::io;
-    use std::collections::HashMap;
-
-    let mut starts = vec![];
-    let mut nodes = HashMap::new();
-    let mut path = vec![];
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if nodes.len() > 0 && input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        if input.len() == 0 {
-            continue;
-        }
-        if path.len() == 0 {
-            path.extend(input.chars().map(|c| {
-                match c {
-                    'L' => Direction::Left,
-                    'R' => Direction::Right,
-                    _ => panic!("invalid letter {c} encountered in path"),
-                }
-            }));
-        }
-        else {
-            let (src, dst) = input.split_once('=').expect("can't split the line at =");
-            let dst = dst.trim();
-            let (dst_l, dst_r) = dst[1..dst.len()-1].split_once(',').expect("can't split the line at ,");
-            let src = src.trim().to_string();
-            let dst_l = dst_l.trim().to_string();
-            let dst_r = dst_r.trim().to_string();
-            if src.ends_with('A') {
-                starts.push(src.clone());
-            }
-            nodes.insert(src, [dst_l, dst_r]);
-        }
-    }
+pub fn part2(network: &Network) -> Answer {
+    let Network { path, nodes } = network;
+    let mut starts: Vec<_> = nodes.keys().filter(|node| node.ends_with('A')).cloned().collect();
+    starts.sort();
     // Now start the algorithm!
     // I love this one!!! <3 <3 <3
     // It's math! To solve it, figure out the cycle sizes
@@ -208,41 +229,25 @@ pub fn part2() {
     let mut path_data = vec![];
     let mut acyclic_length = 0;
     for start in &starts {
-        let mut ends = vec![];
+        // The state of a ghost is the node and the position in the path.
         // The longest possible cycle would be <= path.len() * nodes.len()
-        let mut wraps = HashMap::new();
-        let (cycle_start_node, cycle_start, cycle_length) = path.iter().enumerate().cycle().enumerate().try_fold(&start[..], |node, (j, (i, instruction))| {
-            let next = &nodes[node][*instruction as usize][..];
-            if next.ends_with('Z') {
-                ends.push((j + 1, next.to_string()));
-            }
-            if i == 0 {
-                // detect cycle
-                if let Some(old_j) = wraps.insert(node.to_string(), j) {
-                    // node was already in the cycle!
-                    // cycle detected!!!
-                    Err((node.to_string(), old_j, j - old_j))
-                }
-                else {
-                    Ok(next)
-                }
-            }
-            else {
-                Ok(next)
-            }
-        }).err().unwrap();
-        println!("The cycle of {start} is {cycle_length} steps long and {} ends occurred in the run.", ends.len());
-        let cycle_ends: Vec<_> = ends.iter().filter(|(i, _)| i >= &cycle_start).cloned().collect();
-        println!("The cycle has {} end points. {cycle_ends:?}", cycle_ends.len());
-
-        path_data.push((cycle_ends, cycle_start_node, cycle_start as u64, cycle_length as u64));
+        let step = |&(i, node): &(usize, &str)| ((i + 1) % path.len(), &nodes[node][path[i] as usize][..]);
+        let (cycle_start, cycle_length) = cycle::find_cycle((0, &start[..]), step);
+        // the steps along the cycle, at which the ghost is at an end
+        let cycle_ends: Vec<_> = std::iter::successors(Some((0, &start[..])), |state| Some(step(state)))
+            .take(cycle_start + cycle_length)
+            .enumerate()
+            .filter(|(j, (_, node))| *j >= cycle_start && node.ends_with('Z'))
+            .map(|(j, _)| j)
+            .collect();
+        path_data.push((cycle_ends, cycle_length));
         acyclic_length = acyclic_length.max(cycle_start);
     }
 
     // for the acyclic start, use the naive version of the algorithm.
     // set acyclic_length to a very big number to brute force the problem
     //acyclic_length = 10000000;
-    if let Err((xxz, res)) = path.iter().cycle().take(acyclic_length).enumerate().try_fold(starts, |c_nodes, (i, instruction)| {
+    if let Err((_, res)) = path.iter().cycle().take(acyclic_length).enumerate().try_fold(starts, |c_nodes, (i, instruction)| {
         let next: Vec<_> = c_nodes.iter().map(|node| {
             nodes[node][*instruction as usize].clone()
         }).collect();
@@ -253,38 +258,52 @@ pub fn part2() {
             Ok(next)
         }
     }) {
-        println!("{res} steps are needed to reach {xxz:?}");
-        return;
+        // all paths reached an end before they were all inside their cycles
+        return res.into();
     }
-    println!("The end is not reached in the first {acyclic_length} steps.");
 
     // Now extend the rest using the chinese remainder theorem.
-    // Seems like my data only has one endpoint along each cycle, so it's super simple.
-    // My data seems even more simple, since all cycles start at the start of the data and finish with the end node xxZ.
-    // -> lcm = 7_309_459_565_207 = steps needed
-    // However I'm implementing this for general offsets.
-    let mut n1 = 1;
-    let mut a1 = 0u64;
-    for (cycle_ends, _, _, n2) in path_data {
-        assert!(cycle_ends.len() == 1, "only implemented for one endpoint per cycle");
-        // for multiple endpoints per cycle, one could go through all
-        // combinations and find the one that happens first. However that
-        // can become really inefficient with many cycle ends.
-        let end = cycle_ends[0].0;
-        let a2 = (end as i64 - acyclic_length as i64).rem_euclid(n2 as i64) as u64;
-        let gcd = gcd(n1, n2 as u64);
-        let n = (n1 as i128 * n2 as i128) / gcd as i128;
-        // chinese remainder theorem only works for gcd = 1,
-        // however if the a1 and a2 are the same (mod gcd) then it can be reduced to that.
-        assert!(a1.rem_euclid(gcd) == a2.rem_euclid(gcd), "can't find a solution");
-        // in this case do the chinese remainder on a1 (mod n1/gcd), a2 (mod n2/gcd), a1 = a2 =: a3 (mod gcd)
-        // which are pairwise coprime. Of course that simplifies to
-        // a1 (mod n1), a2 (mod n2/gcd)
-        let (_, m1, _) = xgcd(n1, n2 / gcd);
-        // now compute the combined number (formula from the proof on the wikipage)
-        a1 = (a1 as i128 + (a2 as i128 - a1 as i128) * n1 as i128 * m1 as i128).rem_euclid(n) as u64;
-        n1 = n as u64;
-    }
-    a1 += acyclic_length as u64;
-    println!("{a1} steps are needed.");
-}
\ No newline at end of file
+    // My data only has one endpoint along each cycle, and all cycles start at the start of the data
+    // and finish with the end node xxZ. -> lcm = 7_309_459_565_207 = steps needed
+    // However this works for general offsets and any number of endpoints per cycle:
+    // every ghost is at an end if the steps after the acyclic part are one of its residues (mod its cycle length).
+    let congruences: Vec<_> = path_data.iter().map(|(cycle_ends, n)| {
+        let residues = cycle_ends.iter().map(|&end| (end as i128 - acyclic_length as i128).rem_euclid(*n as i128) as u128);
+        Congruence::new(residues, *n as u128)
+    }).collect();
+    let steps = crt::solve(&congruences)
+        .unwrap_or_else(|err| panic!("too many steps: {err}"))
+        .expect("the ghosts are never all at an end at the same time");
+    let steps = steps.checked_add(acyclic_length as u128).and_then(|steps| i128::try_from(steps).ok()).expect("too many steps");
+    steps.into()
+}
+
+#[test]
+pub fn example_part2() {
+    let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+    assert_eq!(part2(&parse(input).unwrap()), 6.into());
+}
+
+#[test]
+pub fn several_ends_per_cycle() {
+    // 01A passes 13Z once before its cycle and then 03Z every 4 steps, 02A reaches 06Z every 6 steps,
+    // so they are at an end together after 13 steps
+    let input = std::fs::read_to_string("res/aoc8_3.txt").unwrap();
+    assert_eq!(part2(&parse(&input).unwrap()), 13.into());
+}
+
+#[test]
+pub fn ends_off_the_cycle_start() {
+    // MPA reaches its ..Z node 2545 steps before the end of its cycle, so the answer isn't just the lcm
+    let input = std::fs::read_to_string("res/aoc8_2.txt").unwrap();
+    assert_eq!(part2(&parse(&input).unwrap()), 4_363_856_456_840u64.into());
+}
@@ -74,25 +74,54 @@ If you find the next value for each history in this example and add them togethe
 Analyze your OASIS report and extrapolate the next value for each history. What is the sum of these extrapolated values?
 */
 
-#[test]
-pub fn part1() {
-    use std::io;
+use crate::{parse_number, Answer, ParseError, Solution};
 
-    let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
+pub struct Day;
+
+impl Solution for Day {
+    const DAY: usize = 9;
+    const TITLE: &'static str = "Mirage Maintenance";
+    type Input = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input)
+    }
+}
+
+/// parse the history of every value, none of them can be empty.
+pub fn parse(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    let mut histories = vec![];
+    for (i, line) in input.lines().enumerate() {
+        if line.is_empty() {
             break;
         }
-        let input = input.trim();
-        let numbers: Vec<_> = input.split(' ').filter_map(|c| c.parse::<i64>().ok()).collect();
+        let line = line.trim();
+        let numbers: Vec<i64> = line.split_whitespace().map(|c| parse_number(i + 1, line, c)).collect::<Result<_, _>>()?;
+        if numbers.is_empty() {
+            return Err(ParseError::new(i + 1, "the history is empty"));
+        }
+        histories.push(numbers);
+    }
+    Ok(histories)
+}
+
+pub fn part1(histories: &[Vec<i64>]) -> Answer {
+    let mut sum = 0;
+    for numbers in histories {
         // this task is simple in rust
         let mut line = numbers.clone();
-        let mut last_num = vec![numbers.last().unwrap().clone()];
+        let mut last_num = vec![*numbers.last().unwrap()];
         while line.len() >= 2 {
             line = line.windows(2).map(|w| w[1] - w[0]).collect();
-            last_num.push(line.last().unwrap().clone());
+            last_num.push(*line.last().unwrap());
             if line.iter().all(|&n| n == 0) {
                 break;
             }
@@ -101,7 +130,17 @@ pub fn part1() {
         let next = last_num.into_iter().sum::<i64>();
         sum += next;
     }
-    println!("The sum of the following digits is {sum}");
+    sum.into()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+#[test]
+pub fn example_part1() {
+    assert_eq!(part1(&parse(EXAMPLE).unwrap()), 114.into());
 }
 
 /*
@@ -125,25 +164,15 @@ Doing this for the remaining example data above results in previous values of -3
 
 Analyze your OASIS report again, this time extrapolating the previous value for each history. What is the sum of these extrapolated values?
 */
-#[test]
-pub fn part2() {
-    use std::io;
-
+pub fn part2(histories: &[Vec<i64>]) -> Answer {
     let mut sum = 0;
-    loop {
-        let mut input = String::new();
-        let read_bytes = io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.len() <= 1 || read_bytes == 0 {
-            break;
-        }
-        let input = input.trim();
-        let numbers: Vec<_> = input.split(' ').filter_map(|c| c.parse::<i64>().ok()).collect();
+    for numbers in histories {
         // this task is simple in rust
         let mut line = numbers.clone();
-        let mut first_num = vec![numbers.first().unwrap().clone()];
+        let mut first_num = vec![*numbers.first().unwrap()];
         while line.len() >= 2 {
             line = line.windows(2).map(|w| w[1] - w[0]).collect();
-            first_num.push(line.first().unwrap().clone());
+            first_num.push(*line.first().unwrap());
             if line.iter().all(|&n| n == 0) {
                 break;
             }
@@ -152,5 +181,10 @@ pub fn part2() {
         let next = first_num.into_iter().rev().fold(0, |acc, n| n - acc);
         sum += next;
     }
-    println!("The sum of the following digits is {sum}");
-}
\ No newline at end of file
+    sum.into()
+}
+
+#[test]
+pub fn example_part2() {
+    assert_eq!(part2(&parse(EXAMPLE).unwrap()), 2.into());
+}
@@ -0,0 +1,139 @@
+// Common timing harness for the bench subcommand.
+// Every day is parsed and solved a number of times and the min/median/max times are reported,
+// either as a table for reading or as CSV/JSON to compare the timings between commits.
+
+use crate::{AnySolution, ParseError};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// the min, median and max time of repeated runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timing {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    /// summarize the times of the runs. There must be at least one run.
+    pub fn from_runs(mut runs: Vec<Duration>) -> Self {
+        assert!(!runs.is_empty(), "can't summarize zero runs");
+        runs.sort_unstable();
+        let n = runs.len();
+        let median = if n % 2 == 1 {
+            runs[n / 2]
+        }
+        else {
+            (runs[n / 2 - 1] + runs[n / 2]) / 2
+        };
+        Timing { min: runs[0], median, max: runs[n - 1] }
+    }
+}
+
+/// run `f` the given number of times and time every run.
+pub fn time<T>(runs: usize, mut f: impl FnMut() -> T) -> Timing {
+    let runs = (0..runs.max(1)).map(|_| {
+        let start = Instant::now();
+        black_box(f());
+        start.elapsed()
+    }).collect();
+    Timing::from_runs(runs)
+}
+
+/// the timings of one day. Parts which weren't run are None.
+#[derive(Clone, Debug)]
+pub struct DayBench {
+    pub day: usize,
+    pub title: &'static str,
+    pub runs: usize,
+    pub parse: Timing,
+    pub parts: [Option<Timing>; 2],
+}
+
+/// time parsing and both parts of a day. The slow parts are skipped, unless `slow` is set.
+pub fn bench_day(solution: &dyn AnySolution, input: &str, runs: usize, slow: bool) -> Result<DayBench, ParseError> {
+    // parse once outside of the timing, so that invalid input is reported as an error
+    let parsed = solution.parse(input)?;
+    let parse = time(runs, || solution.parse(input));
+    let parts = [1, 2].map(|part| {
+        (slow || !solution.is_slow(part)).then(|| time(runs, || solution.run(part, parsed.as_ref())))
+    });
+    Ok(DayBench { day: solution.day(), title: solution.title(), runs: runs.max(1), parse, parts })
+}
+
+impl DayBench {
+    /// the timed steps with their names, leaving out the parts which weren't run.
+    pub fn steps(&self) -> impl Iterator<Item = (&'static str, Timing)> + '_ {
+        [("parse", Some(self.parse)), ("part1", self.parts[0]), ("part2", self.parts[1])]
+            .into_iter()
+            .filter_map(|(name, timing)| timing.map(|timing| (name, timing)))
+    }
+}
+
+/// human readable table with one row per step.
+pub fn to_table(benches: &[DayBench]) -> String {
+    let mut table = format!("{:>3}  {:<6} {:>14} {:>14} {:>14}\n", "day", "step", "min", "median", "max");
+    for bench in benches {
+        for (step, timing) in bench.steps() {
+            table += &format!("{:>3}  {:<6} {:>14} {:>14} {:>14}\n", bench.day, step,
+                format!("{:?}", timing.min), format!("{:?}", timing.median), format!("{:?}", timing.max));
+        }
+    }
+    table
+}
+
+/// CSV with one row per step, the times are in nanoseconds.
+pub fn to_csv(benches: &[DayBench]) -> String {
+    let mut csv = String::from("day,step,runs,min_ns,median_ns,max_ns\n");
+    for bench in benches {
+        for (step, timing) in bench.steps() {
+            csv += &format!("{},{step},{},{},{},{}\n", bench.day, bench.runs,
+                timing.min.as_nanos(), timing.median.as_nanos(), timing.max.as_nanos());
+        }
+    }
+    csv
+}
+
+/// JSON array with one object per day, the times are in nanoseconds and parts which weren't run are null.
+pub fn to_json(benches: &[DayBench]) -> String {
+    fn timing_json(timing: Option<Timing>) -> String {
+        match timing {
+            Some(t) => format!("{{\"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}", t.min.as_nanos(), t.median.as_nanos(), t.max.as_nanos()),
+            None => "null".to_string(),
+        }
+    }
+    let days: Vec<_> = benches.iter().map(|bench| {
+        // the titles don't need much escaping
+        let title = bench.title.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("  {{\"day\": {}, \"title\": \"{title}\", \"runs\": {}, \"parse\": {}, \"part1\": {}, \"part2\": {}}}",
+            bench.day, bench.runs, timing_json(Some(bench.parse)), timing_json(bench.parts[0]), timing_json(bench.parts[1]))
+    }).collect();
+    format!("[\n{}\n]\n", days.join(",\n"))
+}
+
+#[test]
+pub fn test_timing() {
+    let ms = Duration::from_millis;
+    assert_eq!(Timing::from_runs(vec![ms(3), ms(1), ms(2)]), Timing { min: ms(1), median: ms(2), max: ms(3) });
+    assert_eq!(Timing::from_runs(vec![ms(4), ms(1), ms(2), ms(8)]), Timing { min: ms(1), median: ms(3), max: ms(8) });
+    let mut count = 0;
+    let timing = time(5, || count += 1);
+    assert_eq!(count, 5);
+    assert!(timing.min <= timing.median && timing.median <= timing.max);
+}
+
+#[test]
+pub fn test_formats() {
+    let ns = Duration::from_nanos;
+    let bench = DayBench {
+        day: 3,
+        title: "Gear \"Ratios\"",
+        runs: 2,
+        parse: Timing { min: ns(1), median: ns(2), max: ns(3) },
+        parts: [Some(Timing { min: ns(4), median: ns(5), max: ns(6) }), None],
+    };
+    assert_eq!(to_csv(std::slice::from_ref(&bench)), "day,step,runs,min_ns,median_ns,max_ns\n3,parse,2,1,2,3\n3,part1,2,4,5,6\n");
+    assert_eq!(to_json(std::slice::from_ref(&bench)), "[\n  {\"day\": 3, \"title\": \"Gear \\\"Ratios\\\"\", \"runs\": 2, \
+        \"parse\": {\"min_ns\": 1, \"median_ns\": 2, \"max_ns\": 3}, \"part1\": {\"min_ns\": 4, \"median_ns\": 5, \"max_ns\": 6}, \"part2\": null}\n]\n");
+    assert_eq!(to_table(&[bench]).lines().count(), 3);
+}
@@ -0,0 +1,75 @@
+// A set of small integers as bits in a growable list of words.
+// Setting a bit past the end makes it longer, so it works for any number of elements,
+// like the inputs of a conjunction, of which there can be any number.
+
+/// the set only stores the words up to the last 1 bit, so equal sets are also equal as structs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { words: vec![] }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.words.get(index / 64).is_some_and(|word| word >> (index % 64) & 1 == 1)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        let (word, bit) = (index / 64, index % 64);
+        if value {
+            if word >= self.words.len() {
+                self.words.resize(word + 1, 0);
+            }
+            self.words[word] |= 1 << bit;
+        }
+        else if word < self.words.len() {
+            self.words[word] &= !(1 << bit);
+            while self.words.last() == Some(&0) {
+                self.words.pop();
+            }
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// the set bits in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64).filter(move |bit| word >> bit & 1 == 1).map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+#[test]
+pub fn test_bitset() {
+    let mut set = BitSet::new();
+    assert!(set.is_empty() && !set.get(1000));
+    for i in [3, 64, 127, 128, 500] {
+        set.set(i, true);
+    }
+    assert_eq!(set.count_ones(), 5);
+    assert_eq!(set.ones().collect::<Vec<_>>(), [3, 64, 127, 128, 500]);
+    assert!(set.get(128) && !set.get(129));
+    set.set(500, false);
+    set.set(1000, false);
+    assert_eq!(set.count_ones(), 4);
+    // the same set, no matter how it got there
+    let mut other = BitSet::new();
+    for i in [128, 127, 64, 3] {
+        other.set(i, true);
+    }
+    assert_eq!(set, other);
+    for i in [3, 64, 127, 128] {
+        set.set(i, false);
+    }
+    assert_eq!(set, BitSet::new());
+}
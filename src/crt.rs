@@ -0,0 +1,128 @@
+// Chinese remainder theorem for systems where every equation allows a whole set of residues,
+// like "the ghost is on a ..Z node after x steps if x = 3 or x = 7 (mod 12)".
+// The earliest common solution is found by combining the equations one by one,
+// and once the combinations would get too many, by checking the candidates in increasing order instead.
+
+use crate::modular::{lcm, Mod, Overflow};
+
+/// x = r (mod modulus) for any of the residues r.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Congruence {
+    /// sorted without duplicates, all smaller than the modulus.
+    residues: Vec<u128>,
+    modulus: u128,
+}
+
+impl Congruence {
+    /// the residues are reduced by the modulus, so they can be given as any representative.
+    pub fn new(residues: impl IntoIterator<Item = u128>, modulus: u128) -> Self {
+        assert!(modulus > 0, "the modulus must be positive");
+        let mut residues: Vec<_> = residues.into_iter().map(|r| r % modulus).collect();
+        residues.sort_unstable();
+        residues.dedup();
+        Congruence { residues, modulus }
+    }
+
+    pub fn single(residue: u128, modulus: u128) -> Self {
+        Self::new([residue], modulus)
+    }
+
+    pub fn residues(&self) -> &[u128] {
+        &self.residues
+    }
+
+    pub fn modulus(&self) -> u128 {
+        self.modulus
+    }
+
+    pub fn contains(&self, x: u128) -> bool {
+        self.residues.binary_search(&(x % self.modulus)).is_ok()
+    }
+
+    /// all x which satisfy both congruences. Pairs of residues which contradict each other are dropped.
+    pub fn intersect(&self, other: &Congruence) -> Result<Congruence, Overflow> {
+        let modulus = lcm(self.modulus, other.modulus)?;
+        let mut residues = vec![];
+        for &a in &self.residues {
+            for &b in &other.residues {
+                if let Some(x) = Mod::new(a, self.modulus).crt(Mod::new(b, other.modulus))? {
+                    residues.push(x.value());
+                }
+            }
+        }
+        Ok(Congruence::new(residues, modulus))
+    }
+}
+
+/// above this many combined residues, the remaining equations are only checked, instead of combined.
+const MAX_COMBINATIONS: usize = 1 << 16;
+
+/// the smallest x >= 0 which satisfies all the congruences, None if there is no solution.
+pub fn solve(congruences: &[Congruence]) -> Result<Option<u128>, Overflow> {
+    // the equations with the fewest residues first, that keeps the combinations small for the longest time
+    let mut congruences: Vec<_> = congruences.iter().collect();
+    congruences.sort_by_key(|c| c.residues.len());
+    let mut combined = Congruence::single(0, 1);
+    let mut rest = &congruences[..];
+    while let Some((next, remaining)) = rest.split_first() {
+        if combined.residues.len() * next.residues.len() > MAX_COMBINATIONS {
+            break;
+        }
+        combined = combined.intersect(next)?;
+        if combined.residues.is_empty() {
+            return Ok(None);
+        }
+        rest = remaining;
+    }
+    if rest.is_empty() {
+        return Ok(combined.residues.first().copied());
+    }
+    // Too many combinations, so go through the candidates of the combined equation in increasing order
+    // and check the others directly. Everything repeats after the lcm of all moduli, so stop there.
+    let period = rest.iter().try_fold(combined.modulus, |l, c| lcm(l, c.modulus))?;
+    let mut base = 0;
+    while base < period {
+        for &r in &combined.residues {
+            if rest.iter().all(|c| c.contains(base + r)) {
+                return Ok(Some(base + r));
+            }
+        }
+        base += combined.modulus;
+    }
+    Ok(None)
+}
+
+#[test]
+pub fn test_solve() {
+    assert_eq!(solve(&[Congruence::single(2, 3), Congruence::single(3, 5), Congruence::single(2, 7)]), Ok(Some(23)));
+    assert_eq!(solve(&[Congruence::single(1, 4), Congruence::single(2, 6)]), Ok(None));
+    assert_eq!(solve(&[]), Ok(Some(0)));
+    assert_eq!(solve(&[Congruence::new([], 5)]), Ok(None));
+    assert_eq!(solve(&[Congruence::single(1, u128::MAX), Congruence::single(2, u128::MAX - 1)]), Err(Overflow));
+    // x = 1 or 2 (mod 5) and x = 3 (mod 7)
+    assert_eq!(solve(&[Congruence::new([1, 2], 5), Congruence::single(3, 7)]), Ok(Some(17)));
+    // compare with brute force on a bunch of pseudo random systems
+    let mut random = crate::Random::new(12345);
+    let mut random = |n: u128| random.below(n as usize) as u128;
+    for _ in 0..200 {
+        let count = random(4) + 1;
+        let system: Vec<_> = (0..count).map(|_| {
+            let modulus = random(20) + 1;
+            let residues: Vec<_> = (0..random(4) + 1).map(|_| random(modulus)).collect();
+            Congruence::new(residues, modulus)
+        }).collect();
+        let period = system.iter().fold(1, |l, c| lcm(l, c.modulus).unwrap());
+        let expected = (0..period).find(|&x| system.iter().all(|c| c.contains(x)));
+        assert_eq!(solve(&system), Ok(expected), "{system:?}");
+    }
+}
+
+#[test]
+pub fn test_solve_many_residues() {
+    // 500 * 500 combinations are too many, so the last equation is only checked
+    let evens = Congruence::new((0..500).map(|i| 2 * i), 1000);
+    let threes = Congruence::new((0..500).map(|i| 3 * i + 1), 1501);
+    let x = solve(&[evens.clone(), threes.clone(), Congruence::single(5, 7)]).unwrap().unwrap();
+    let expected = (0..1000 * 1501 * 7).find(|&x| evens.contains(x) && threes.contains(x) && x % 7 == 5).unwrap();
+    assert_eq!(x, expected);
+}
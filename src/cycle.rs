@@ -0,0 +1,107 @@
+// Cycle detection for simulations which end up repeating themselves.
+// A state is stepped by a function again and again, and at some point it comes back to an earlier state.
+// From there on everything repeats, so a billion steps can be skipped by going around the cycle.
+// All functions return (prefix_len, period): the states from step prefix_len on repeat every period steps.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Brent's algorithm. It only keeps two states at a time, but steps through the cycle a few times.
+/// Use this if the states are big or if they can't be hashed.
+pub fn brent<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> S) -> (usize, usize) {
+    // find the period by moving the hare ahead in power of two sized rounds
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+    // now the hare starts one period ahead of the tortoise, they meet at the start of the cycle
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix_len = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix_len += 1;
+    }
+    (prefix_len, period)
+}
+
+/// hash based cycle detection, which remembers all states until one repeats.
+/// It only steps through the cycle once, which makes it the faster choice if the states are small.
+pub fn find_cycle<S: Clone + Hash + Eq>(start: S, step: impl FnMut(&S) -> S) -> (usize, usize) {
+    let (states, cycle_start) = run_until_repeat(start, step, usize::MAX);
+    let prefix_len = cycle_start.expect("the states always repeat eventually");
+    (prefix_len, states.len() - prefix_len)
+}
+
+/// the state after n steps. If the states repeat before that, the rest is skipped using the cycle.
+pub fn state_after<S: Clone + Hash + Eq>(start: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    let (mut states, cycle_start) = run_until_repeat(start, step, n);
+    let index = match cycle_start {
+        Some(prefix_len) => prefix_len + (n - prefix_len) % (states.len() - prefix_len),
+        None => n,
+    };
+    states.swap_remove(index)
+}
+
+/// all states until one repeats (not included) together with the index of the first state of the cycle.
+/// If there is no repetition in the first `limit` steps, the states up to step `limit` (included) are returned.
+fn run_until_repeat<S: Clone + Hash + Eq>(start: S, mut step: impl FnMut(&S) -> S, limit: usize) -> (Vec<S>, Option<usize>) {
+    let mut indices = HashMap::from([(start.clone(), 0)]);
+    let mut states = vec![start];
+    while states.len() <= limit {
+        let next = step(states.last().unwrap());
+        if let Some(&index) = indices.get(&next) {
+            return (states, Some(index));
+        }
+        indices.insert(next.clone(), states.len());
+        states.push(next);
+    }
+    (states, None)
+}
+
+#[test]
+pub fn test_cycle() {
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 2 -> ...
+    let step = |&x: &u32| if x == 6 { 2 } else { x + 1 };
+    assert_eq!(brent(0, step), (2, 5));
+    assert_eq!(find_cycle(0, step), (2, 5));
+    assert_eq!(state_after(0, step, 1), 1);
+    assert_eq!(state_after(0, step, 7), 2);
+    assert_eq!(state_after(0, step, 1_000_000_000), 2 + (1_000_000_000 - 2) % 5);
+    // starting inside the cycle and a cycle of length 1
+    assert_eq!(brent(3, step), (0, 5));
+    assert_eq!(find_cycle(3, step), (0, 5));
+    assert_eq!(brent(7, |_| 7), (0, 1));
+    assert_eq!(find_cycle(7, |_| 7), (0, 1));
+    assert_eq!(state_after(7, |_| 7, 0), 7);
+}
+
+#[test]
+pub fn test_cycle_agree() {
+    // x -> x^2 + 1 (mod m) has all kinds of prefixes and periods
+    for m in 1..200u64 {
+        for start in (0..m).step_by(7) {
+            let step = |x: &u64| (x * x + 1) % m;
+            let (prefix_len, period) = find_cycle(start, step);
+            assert_eq!(brent(start, step), (prefix_len, period), "x^2 + 1 mod {m} starting at {start}");
+            let mut x = start;
+            for _ in 0..prefix_len + 3 * period + 1 {
+                x = step(&x);
+            }
+            assert_eq!(state_after(start, step, prefix_len + 3 * period + 1), x);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+// A 2D grid with the Point and Direction types, that almost every grid puzzle needs.
+// x goes to the right (East) and y goes down (South), like the lines of the input.
+
+use crate::ParseError;
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// position (x, y) on a grid. It can be outside of the grid, so the coordinates are signed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Point(pub i64, pub i64);
+
+impl Point {
+    pub fn in_bounds(&self, width: usize, height: usize) -> bool {
+        self.0 >= 0 && self.1 >= 0 && (self.0 as usize) < width && (self.1 as usize) < height
+    }
+
+    /// |dx| + |dy|
+    pub fn manhattan(&self, other: &Point) -> u64 {
+        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+    }
+
+    /// the 4 points next to this one in the order of [Direction::ALL]
+    pub fn neighbors(&self) -> [Point; 4] {
+        Direction::ALL.map(|dir| *self + dir.delta())
+    }
+
+    /// the 8 points around this one, including the diagonals.
+    pub fn neighbors_diagonal(&self) -> [Point; 8] {
+        [(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)].map(|(dx, dy)| Point(self.0 + dx, self.1 + dy))
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, rhs: Self) -> Self::Output {
+        Point(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+    fn mul(self, rhs: i64) -> Self::Output {
+        Point(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+    fn neg(self) -> Self::Output {
+        Point(-self.0, -self.1)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// The 4 directions on the grid. They are ordered clockwise,
+/// so they can be used as an index with `dir as usize`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum Direction {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    /// the step to take in this direction
+    pub fn delta(&self) -> Point {
+        match self {
+            Self::North => Point(0, -1),
+            Self::East => Point(1, 0),
+            Self::South => Point(0, 1),
+            Self::West => Point(-1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        Self::ALL[(*self as usize + 2) % 4]
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        Self::ALL[(*self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        Self::ALL[(*self as usize + 3) % 4]
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Self::East | Self::West)
+    }
+
+    /// the arrow pointing in this direction, like on the maps in the puzzles: ^ > v <
+    pub fn arrow(&self) -> char {
+        match self {
+            Self::North => '^',
+            Self::East => '>',
+            Self::South => 'v',
+            Self::West => '<',
+        }
+    }
+
+    pub fn from_arrow(c: char) -> Option<Direction> {
+        Self::ALL.into_iter().find(|dir| dir.arrow() == c)
+    }
+}
+
+/// A rectangular grid stored row by row.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// grid filled with copies of one value
+    pub fn new(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    /// build the grid from its rows, which need to have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        let height = rows.len();
+        Some(Grid { width, height, cells: rows.into_iter().flatten().collect() })
+    }
+
+    /// parse a grid with one cell per character. The grid stops at the first empty line.
+    /// `cell` converts the characters and returns None for invalid characters,
+    /// which are reported with the `expected` description of the valid ones.
+    pub fn parse(input: &str, expected: &str, mut cell: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        let mut cells = vec![];
+        let mut width = 0;
+        let mut height = 0;
+        for (y, raw_line) in input.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() {
+                break;
+            }
+            let len = cells.len();
+            for (i, c) in line.char_indices() {
+                let value = cell(c).ok_or_else(|| ParseError::unexpected(y + 1, raw_line, &line[i..i + c.len_utf8()], expected))?;
+                cells.push(value);
+            }
+            if y == 0 {
+                width = cells.len();
+            }
+            else if cells.len() - len != width {
+                return Err(ParseError::new(y + 1, "one line wasn't the same length as the previous."));
+            }
+            height += 1;
+        }
+        if cells.is_empty() {
+            return Err(ParseError::new(0, "the grid is empty"));
+        }
+        Ok(Grid { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, p: Point) -> bool {
+        p.in_bounds(self.width, self.height)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        if self.in_bounds(p) {
+            Some(&self.cells[p.1 as usize * self.width + p.0 as usize])
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        if self.in_bounds(p) {
+            Some(&mut self.cells[p.1 as usize * self.width + p.0 as usize])
+        }
+        else {
+            None
+        }
+    }
+
+    /// index into the grid, where the grid repeats infinitely in all directions.
+    pub fn get_wrapping(&self, p: Point) -> &T {
+        let x = p.0.rem_euclid(self.width as i64) as usize;
+        let y = p.1.rem_euclid(self.height as i64) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        // chunks_exact panics for 0, but then there are no cells anyway
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    /// all points of the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point(x as i64, y as i64)))
+    }
+
+    /// all cells with their position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// the first position (row by row) of a cell matching the predicate.
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Point> {
+        self.iter().find(|(_, cell)| predicate(cell)).map(|(p, _)| p)
+    }
+
+    /// the neighbors of p in the order of [Direction::ALL], which are inside of the grid.
+    pub fn neighbors(&self, p: Point) -> impl Iterator<Item = (Direction, Point)> + '_ {
+        Direction::ALL.into_iter().map(move |dir| (dir, p + dir.delta())).filter(|(_, next)| self.in_bounds(*next))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// mirror the grid along the diagonal, so the rows become the columns.
+    pub fn transpose(&self) -> Grid<T> where T: Clone {
+        let cells = (0..self.width).flat_map(|x| self.column(x).cloned()).collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    /// rotate the grid by 90° clockwise, so North becomes East.
+    pub fn rotate_clockwise(&self) -> Grid<T> where T: Clone {
+        let cells = (0..self.width).flat_map(|x| self.column(x).rev().cloned()).collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    /// rotate the grid by 90° counterclockwise, so North becomes West.
+    pub fn rotate_counterclockwise(&self) -> Grid<T> where T: Clone {
+        let cells = (0..self.width).rev().flat_map(|x| self.column(x).cloned()).collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+    fn index(&self, p: Point) -> &Self::Output {
+        self.get(p).unwrap_or_else(|| panic!("{p:?} is outside of the {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p).unwrap_or_else(|| panic!("{p:?} is outside of the {width}x{height} grid"))
+    }
+}
+
+/// one line per row, without separators between the cells.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+pub fn test_grid() {
+    let grid = Grid::parse("abc\ndef\n\nignored", "a letter", Some).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Point(2, 1)], 'f');
+    assert_eq!(grid.get(Point(3, 0)), None);
+    assert_eq!(grid.get(Point(0, -1)), None);
+    assert_eq!(*grid.get_wrapping(Point(-1, 2)), 'c');
+    assert_eq!(grid.position(|&c| c == 'e'), Some(Point(1, 1)));
+    assert_eq!(grid.neighbors(Point(0, 0)).collect::<Vec<_>>(), [(Direction::East, Point(1, 0)), (Direction::South, Point(0, 1))]);
+    assert_eq!(grid.to_string(), "abc\ndef\n");
+    assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+    assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+    assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad\n");
+    assert_eq!(grid.rotate_clockwise().rotate_clockwise(), Grid::parse("fed\ncba", "a letter", Some).unwrap());
+    assert_eq!(Grid::from_rows(vec![vec!['a', 'b', 'c'], vec!['d', 'e', 'f']]), Some(grid));
+
+    assert_eq!(Grid::parse("ab\nabc", "a letter", Some).unwrap_err().line, 2);
+    let err = Grid::parse("ab\nax", "a or b", |c| (c != 'x').then_some(c)).unwrap_err();
+    assert_eq!((err.line, err.column, err.expected.as_deref()), (2, 2, Some("a or b")));
+    assert!(Grid::parse("", "a letter", Some).is_err());
+    assert_eq!(Grid::from_rows(vec![vec![1], vec![1, 2]]), None);
+}
+
+#[test]
+pub fn test_directions() {
+    for dir in Direction::ALL {
+        assert_eq!(dir.opposite().opposite(), dir);
+        assert_eq!(dir.turn_left().turn_right(), dir);
+        assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+        assert_eq!(dir.delta() + dir.opposite().delta(), Point(0, 0));
+        assert_eq!(Direction::from_arrow(dir.arrow()), Some(dir));
+    }
+    assert_eq!(Direction::North.turn_right(), Direction::East);
+    assert_eq!(Point(1, 2).neighbors(), [Point(1, 1), Point(2, 2), Point(1, 3), Point(0, 2)]);
+    assert_eq!(Point(1, 2).manhattan(&Point(-1, 5)), 5);
+}
@@ -0,0 +1,219 @@
+// Axis aligned boxes of integer points in any number of dimensions, and sets of them.
+// Like the interval sets, but for regions like "all parts with 1 <= x < 1416 and s < 1351",
+// where every dimension is a range. Sets of boxes keep their boxes disjoint,
+// so overlapping boxes can be added without counting anything twice.
+
+use std::ops::Range;
+
+/// the integer points start[d] <= p[d] < end[d] in all dimensions d.
+/// If any range is empty, the box is empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HyperBox<const D: usize> {
+    pub start: [i64; D],
+    pub end: [i64; D],
+}
+
+impl<const D: usize> HyperBox<D> {
+    pub fn new(start: [i64; D], end: [i64; D]) -> Self {
+        HyperBox { start, end }
+    }
+
+    pub fn from_ranges(ranges: [Range<i64>; D]) -> Self {
+        HyperBox { start: ranges.clone().map(|r| r.start), end: ranges.map(|r| r.end) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..D).any(|d| self.start[d] >= self.end[d])
+    }
+
+    /// the number of points in the box.
+    pub fn volume(&self) -> u128 {
+        if self.is_empty() {
+            return 0;
+        }
+        (0..D).map(|d| self.end[d].abs_diff(self.start[d]) as u128).product()
+    }
+
+    pub fn contains(&self, point: &[i64; D]) -> bool {
+        (0..D).all(|d| (self.start[d]..self.end[d]).contains(&point[d]))
+    }
+
+    /// the points in both boxes, None if there are none.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let start = std::array::from_fn(|d| self.start[d].max(other.start[d]));
+        let end = std::array::from_fn(|d| self.end[d].min(other.end[d]));
+        Some(HyperBox { start, end }).filter(|b| !b.is_empty())
+    }
+
+    /// the points of this box, which are not in the other one, as up to 2 * D disjoint boxes.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        if self.is_empty() {
+            return vec![];
+        }
+        if self.intersection(other).is_none() {
+            return vec![*self];
+        }
+        // cut off the slices below and above the other box one dimension at a time,
+        // what's left in the end is the intersection
+        let mut pieces = vec![];
+        let mut rest = *self;
+        for d in 0..D {
+            if rest.start[d] < other.start[d] {
+                let mut below = rest;
+                below.end[d] = other.start[d];
+                pieces.push(below);
+                rest.start[d] = other.start[d];
+            }
+            if rest.end[d] > other.end[d] {
+                let mut above = rest;
+                above.start[d] = other.end[d];
+                pieces.push(above);
+                rest.end[d] = other.end[d];
+            }
+        }
+        pieces
+    }
+}
+
+/// A set of points as disjoint boxes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoxSet<const D: usize> {
+    boxes: Vec<HyperBox<D>>,
+}
+
+impl<const D: usize> Default for BoxSet<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const D: usize> From<HyperBox<D>> for BoxSet<D> {
+    fn from(value: HyperBox<D>) -> Self {
+        let mut set = Self::new();
+        set.insert(value);
+        set
+    }
+}
+
+impl<const D: usize> FromIterator<HyperBox<D>> for BoxSet<D> {
+    fn from_iter<I: IntoIterator<Item = HyperBox<D>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for b in iter {
+            set.insert(b);
+        }
+        set
+    }
+}
+
+impl<const D: usize> BoxSet<D> {
+    pub fn new() -> Self {
+        BoxSet { boxes: vec![] }
+    }
+
+    /// a set of boxes, which are known to be disjoint already, so nothing has to be cut apart.
+    pub fn from_disjoint(boxes: Vec<HyperBox<D>>) -> Self {
+        debug_assert!(boxes.iter().enumerate().all(|(i, a)| boxes[i + 1..].iter().all(|b| a.intersection(b).is_none())));
+        BoxSet { boxes: boxes.into_iter().filter(|b| !b.is_empty()).collect() }
+    }
+
+    /// the disjoint boxes of the set.
+    pub fn boxes(&self) -> &[HyperBox<D>] {
+        &self.boxes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    /// the number of points in the set.
+    pub fn volume(&self) -> u128 {
+        self.boxes.iter().map(HyperBox::volume).sum()
+    }
+
+    pub fn contains(&self, point: &[i64; D]) -> bool {
+        self.boxes.iter().any(|b| b.contains(point))
+    }
+
+    /// add the points of the box. Only the parts, which aren't in the set yet, are added.
+    pub fn insert(&mut self, new: HyperBox<D>) {
+        let mut pieces = vec![new];
+        for b in &self.boxes {
+            pieces = pieces.iter().flat_map(|piece| piece.subtract(b)).collect();
+        }
+        self.boxes.extend(pieces.into_iter().filter(|piece| !piece.is_empty()));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for &b in &other.boxes {
+            union.insert(b);
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        // the boxes of both sets are disjoint, so the pairwise intersections are as well
+        let boxes = self.boxes.iter().flat_map(|a| other.boxes.iter().filter_map(|b| a.intersection(b))).collect();
+        BoxSet { boxes }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut pieces = self.boxes.clone();
+        for b in &other.boxes {
+            pieces = pieces.iter().flat_map(|piece| piece.subtract(b)).collect();
+        }
+        BoxSet { boxes: pieces }
+    }
+}
+
+/// the number of points in at least one of the boxes, which can overlap.
+pub fn union_volume<const D: usize>(boxes: &[HyperBox<D>]) -> u128 {
+    boxes.iter().copied().collect::<BoxSet<D>>().volume()
+}
+
+#[test]
+pub fn test_hyperbox() {
+    let a = HyperBox::from_ranges([0..4, 0..4]);
+    let b = HyperBox::from_ranges([2..6, 1..3]);
+    assert_eq!(a.volume(), 16);
+    assert_eq!(a.intersection(&b), Some(HyperBox::from_ranges([2..4, 1..3])));
+    assert_eq!(a.subtract(&b).iter().map(HyperBox::volume).sum::<u128>(), 12);
+    assert_eq!(union_volume(&[a, b]), 16 + 8 - 4);
+    assert_eq!(HyperBox::from_ranges([0..4, 3..3]).volume(), 0);
+    assert_eq!(a.intersection(&HyperBox::from_ranges([4..5, 0..4])), None);
+    assert!(a.contains(&[3, 0]) && !a.contains(&[4, 0]));
+    // the puzzle needs 4 dimensions
+    let full = HyperBox::from_ranges([1..4001, 1..4001, 1..4001, 1..4001]);
+    assert_eq!(full.volume(), 4000u128.pow(4));
+}
+
+#[test]
+pub fn box_set_properties() {
+    // compare with counting the points one by one on a small 3d grid
+    let mut random = crate::Random::new(99);
+    let mut random = |n: i64| random.below(n as usize) as i64;
+    let mut random_box = || {
+        let ranges: [Range<i64>; 3] = std::array::from_fn(|_| {
+            let (a, b) = (random(8), random(8));
+            a.min(b)..a.max(b) + 1
+        });
+        HyperBox::from_ranges(ranges)
+    };
+    let points: Vec<[i64; 3]> = (0..8).flat_map(|x| (0..8).flat_map(move |y| (0..8).map(move |z| [x, y, z]))).collect();
+    let count = |f: &dyn Fn(&[i64; 3]) -> bool| points.iter().filter(|p| f(p)).count() as u128;
+    for _ in 0..50 {
+        let a: BoxSet<3> = (0..4).map(|_| random_box()).collect();
+        let b: BoxSet<3> = (0..4).map(|_| random_box()).collect();
+        for set in [&a, &b] {
+            // disjoint boxes, so the volume is the number of points
+            assert_eq!(set.volume(), count(&|p| set.contains(p)));
+        }
+        let union = a.union(&b);
+        assert_eq!(union.volume(), count(&|p| a.contains(p) || b.contains(p)));
+        let intersection = a.intersection(&b);
+        assert_eq!(intersection.volume(), count(&|p| a.contains(p) && b.contains(p)));
+        let difference = a.difference(&b);
+        assert_eq!(difference.volume(), count(&|p| a.contains(p) && !b.contains(p)));
+        assert!(points.iter().all(|p| difference.contains(p) == (a.contains(p) && !b.contains(p))));
+    }
+}
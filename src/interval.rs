@@ -0,0 +1,354 @@
+// Sets of integers, stored as sorted lists of disjoint ranges.
+// Puzzles like "map these seed ranges" or "which ratings are accepted" work on huge ranges of numbers,
+// which can't be stored one by one, but they only ever get split into a few pieces.
+// The ranges are kept inclusive on both ends internally, so that every kind of range can be stored,
+// including the ones going up to the maximum of the type like `5..=u32::MAX`.
+
+use std::fmt;
+use std::ops::{Add, Bound, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+/// integer types which can be stored in an [IntervalSet].
+pub trait Discrete: Copy + Ord {
+    const MIN: Self;
+    const MAX: Self;
+    /// the next larger value, None for MAX.
+    fn succ(self) -> Option<Self>;
+    /// the next smaller value, None for MIN.
+    fn pred(self) -> Option<Self>;
+    /// the number of values from self to end, both included. Panics if it doesn't fit into u128.
+    fn count_to(self, end: Self) -> u128;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty => $unsigned:ty),*) => {
+        $(impl Discrete for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            fn succ(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+            fn pred(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+            fn count_to(self, end: Self) -> u128 {
+                // the wrapping difference is exact in the unsigned type of the same size
+                (end.wrapping_sub(self) as $unsigned as u128).checked_add(1).expect("too many values to count")
+            }
+        })*
+    };
+}
+impl_discrete!(u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+/// A set of integers as sorted, disjoint and non touching inclusive ranges.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T: Discrete> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Discrete> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// the inclusive start and end of any range, None if it's empty.
+fn inclusive_bounds<T: Discrete>(range: &impl RangeBounds<T>) -> Option<(T, T)> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.succ()?,
+        Bound::Unbounded => T::MIN,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end,
+        Bound::Excluded(&end) => end.pred()?,
+        Bound::Unbounded => T::MAX,
+    };
+    (start <= end).then_some((start, end))
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    /// the empty set.
+    pub fn new() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+
+    /// the set of all values of the type.
+    pub fn full() -> Self {
+        IntervalSet { ranges: vec![(T::MIN, T::MAX)] }
+    }
+
+    /// the set of all values in the range. Any kind of range works, like `1..4`, `..=7` or `5..`.
+    pub fn from_range(range: impl RangeBounds<T>) -> Self {
+        IntervalSet { ranges: inclusive_bounds(&range).into_iter().collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// the number of values in the set.
+    pub fn len(&self) -> u128 {
+        self.ranges.iter().map(|&(start, end)| start.count_to(end)).sum()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|&(start, _)| start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|&(_, end)| end)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        // the first range, which doesn't end before the value
+        let index = self.ranges.partition_point(|(_, end)| end < value);
+        self.ranges.get(index).is_some_and(|(start, _)| start <= value)
+    }
+
+    /// the ranges of the set in increasing order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|&(start, end)| start..=end)
+    }
+
+    /// all values which are in one of the sets.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges: Vec<(T, T)> = Vec::with_capacity(self.ranges.len() + other.ranges.len());
+        let (mut a, mut b) = (self.ranges.iter().peekable(), other.ranges.iter().peekable());
+        // merge the sorted lists, joining ranges which overlap or touch
+        loop {
+            let next = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) if x.0 <= y.0 => a.next(),
+                (Some(_), Some(_)) => b.next(),
+                (Some(_), None) => a.next(),
+                (None, _) => b.next(),
+            };
+            let Some(&(start, end)) = next else {
+                break;
+            };
+            match ranges.last_mut() {
+                Some(last) if last.1.succ().is_none_or(|after| start <= after) => last.1 = last.1.max(end),
+                _ => ranges.push((start, end)),
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// all values which are in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // the range which ends first can't overlap anything else
+            if a.1 < b.1 {
+                i += 1;
+            }
+            else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// all values which are not in the set.
+    pub fn complement(&self) -> Self {
+        let mut ranges = vec![];
+        let mut start = Some(T::MIN);
+        for &(range_start, range_end) in &self.ranges {
+            if let Some(start) = start.filter(|&start| start < range_start) {
+                ranges.push((start, range_start.pred().unwrap()));
+            }
+            start = range_end.succ();
+        }
+        if let Some(start) = start {
+            ranges.push((start, T::MAX));
+        }
+        IntervalSet { ranges }
+    }
+
+    /// all values of this set, which are not in the other one.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    /// add all values of the range to the set.
+    pub fn insert(&mut self, range: impl RangeBounds<T>) {
+        *self = self.union(&Self::from_range(range));
+    }
+
+    /// remove all values of the range from the set.
+    pub fn remove(&mut self, range: impl RangeBounds<T>) {
+        *self = self.difference(&Self::from_range(range));
+    }
+
+    /// only keep the values in the range.
+    pub fn retain(&mut self, range: impl RangeBounds<T>) {
+        *self = self.intersection(&Self::from_range(range));
+    }
+}
+
+impl<T: Discrete + Add<Output = T>> IntervalSet<T> {
+    /// move all values by the offset. Like `+`, this panics in debug builds if a value overflows.
+    pub fn shift(&self, offset: T) -> Self {
+        IntervalSet { ranges: self.ranges.iter().map(|&(start, end)| (start + offset, end + offset)).collect() }
+    }
+}
+
+macro_rules! from_range {
+    ($($range:ty),*) => {
+        $(impl<T: Discrete> From<$range> for IntervalSet<T> {
+            fn from(range: $range) -> Self {
+                Self::from_range(range)
+            }
+        })*
+    };
+}
+from_range!(Range<T>, RangeInclusive<T>, RangeFrom<T>, RangeTo<T>, RangeToInclusive<T>, RangeFull, (Bound<T>, Bound<T>));
+
+impl<T: Discrete, R: RangeBounds<T>> FromIterator<R> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        // sort the ranges first, so the union only needs one pass
+        let mut ranges: Vec<_> = iter.into_iter().filter_map(|range| inclusive_bounds(&range)).collect();
+        ranges.sort_unstable();
+        IntervalSet::new().union(&IntervalSet { ranges })
+    }
+}
+
+impl<T: Discrete + fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.ranges()).finish()
+    }
+}
+
+#[test]
+pub fn test_interval_set() {
+    let mut set = IntervalSet::from(1..10u32);
+    set.remove(3..=4);
+    set.remove(8..);
+    assert_eq!(set.ranges().collect::<Vec<_>>(), [1..=2, 5..=7]);
+    assert_eq!(set.len(), 5);
+    assert!(set.contains(&5) && !set.contains(&4) && !set.contains(&0) && !set.contains(&8));
+    assert_eq!((set.min(), set.max()), (Some(1), Some(7)));
+    set.insert(3..5);
+    assert_eq!(set, IntervalSet::from(1..=7));
+    assert_eq!(format!("{set:?}"), "{1..=7}");
+    // every kind of bound
+    assert_eq!(IntervalSet::from((Bound::Excluded(3), Bound::Included(5))), IntervalSet::from(4..6u8));
+    assert_eq!(IntervalSet::<u8>::from(250..), IntervalSet::from(250..=255));
+    assert_eq!(IntervalSet::<u8>::from(..), IntervalSet::full());
+    assert!(IntervalSet::<u8>::from(5..5).is_empty());
+    assert!(IntervalSet::<u8>::from((Bound::Excluded(255), Bound::Unbounded)).is_empty());
+    assert_eq!(IntervalSet::<i64>::full().len(), 1 << 64);
+    assert_eq!(IntervalSet::from(-3..3i64).shift(10), IntervalSet::from(7..13));
+    assert_eq!(IntervalSet::from_iter([5..7, 0..2, 1..3, 7..8]), IntervalSet::from(0..3).union(&IntervalSet::from(5..8)));
+}
+
+/// pseudo random sets over the whole range of a small type, to check the set operations against a simple bitset model.
+#[cfg(test)]
+fn random_sets<T: Discrete + TryFrom<i32>>(seed: u64, count: usize) -> Vec<(IntervalSet<T>, Vec<bool>)>
+where T::Error: fmt::Debug {
+    let mut random = crate::Random::new(seed);
+    let mut random = |n: usize| random.below(n);
+    let min = model_min::<T>();
+    let value = |i: usize| T::try_from(i as i32 + min).unwrap();
+    (0..count).map(|_| {
+        let mut set = IntervalSet::new();
+        let mut model = vec![false; 256];
+        for _ in 0..random(6) {
+            let (a, b) = (random(256), random(256));
+            let (start, end) = (a.min(b), a.max(b));
+            let remove = random(3) == 0;
+            // all kinds of ranges, the model gets the same values as start..end
+            let (start, end) = match random(6) {
+                0 => { set_range(&mut set, value(start)..value(end), remove); (start, end) }
+                1 => { set_range(&mut set, value(start)..=value(end), remove); (start, end + 1) }
+                2 => { set_range(&mut set, value(start).., remove); (start, 256) }
+                3 => { set_range(&mut set, ..=value(end), remove); (0, end + 1) }
+                4 => { set_range(&mut set, (Bound::Excluded(value(start)), Bound::Excluded(value(end))), remove); (start + 1, end.max(start + 1)) }
+                _ => { set_range(&mut set, .., remove); (0, 256) }
+            };
+            model[start..end].fill(!remove);
+        }
+        (set, model)
+    }).collect()
+}
+
+/// the smallest value of u8 or i8 as i32
+#[cfg(test)]
+fn model_min<T: Discrete + TryFrom<i32>>() -> i32 {
+    if T::try_from(-1).is_ok() { -128 } else { 0 }
+}
+
+#[cfg(test)]
+fn set_range<T: Discrete>(set: &mut IntervalSet<T>, range: impl RangeBounds<T>, remove: bool) {
+    if remove {
+        set.remove(range);
+    }
+    else {
+        set.insert(range);
+    }
+}
+
+#[cfg(test)]
+fn check_model<T: Discrete + TryFrom<i32> + fmt::Debug>(set: &IntervalSet<T>, model: &[bool])
+where T::Error: fmt::Debug {
+    let min = model_min::<T>();
+    for (i, &expected) in model.iter().enumerate() {
+        assert_eq!(set.contains(&T::try_from(i as i32 + min).unwrap()), expected, "{} in {set:?}", i as i32 + min);
+    }
+    assert_eq!(set.len(), model.iter().filter(|&&x| x).count() as u128);
+    // the ranges are always sorted, disjoint and don't touch
+    assert!(set.ranges.iter().all(|(start, end)| start <= end));
+    assert!(set.ranges.windows(2).all(|w| w[0].1.succ().is_some_and(|after| after < w[1].0)));
+}
+
+#[cfg(test)]
+fn check_properties<T: Discrete + TryFrom<i32> + fmt::Debug>(seed: u64)
+where T::Error: fmt::Debug {
+    let sets = random_sets::<T>(seed, 40);
+    for (set, model) in &sets {
+        check_model(set, model);
+        let not: Vec<_> = model.iter().map(|x| !x).collect();
+        check_model(&set.complement(), &not);
+        assert_eq!(set.complement().complement(), *set);
+        assert_eq!(set.union(&set.complement()), IntervalSet::full());
+        assert!(set.intersection(&set.complement()).is_empty());
+    }
+    for (a, model_a) in &sets {
+        for (b, model_b) in &sets {
+            let zip = |f: fn(bool, bool) -> bool| model_a.iter().zip(model_b).map(|(&x, &y)| f(x, y)).collect::<Vec<_>>();
+            check_model(&a.union(b), &zip(|x, y| x || y));
+            check_model(&a.intersection(b), &zip(|x, y| x && y));
+            check_model(&a.difference(b), &zip(|x, y| x && !y));
+            assert_eq!(a.union(b), b.union(a));
+            assert_eq!(a.intersection(b), b.intersection(a));
+            // de Morgan
+            assert_eq!(a.union(b).complement(), a.complement().intersection(&b.complement()));
+            assert_eq!(a.union(b).len() + a.intersection(b).len(), a.len() + b.len());
+        }
+    }
+}
+
+#[test]
+pub fn interval_set_properties() {
+    // the whole range of the types, so MIN and MAX are covered as well
+    check_properties::<u8>(1);
+    check_properties::<i8>(2);
+}
+
+#[test]
+pub fn interval_set_shift() {
+    for (mut set, mut model) in random_sets::<u8>(4, 50) {
+        set.retain(..200);
+        model.truncate(200);
+        let mut shifted = vec![false; 50];
+        shifted.extend(&model);
+        shifted.resize(256, false);
+        check_model(&set.shift(50), &shifted);
+    }
+}
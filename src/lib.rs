@@ -0,0 +1,335 @@
+pub mod aoc1;
+pub mod aoc2;
+pub mod aoc3;
+pub mod aoc4;
+pub mod aoc5;
+pub mod aoc6;
+pub mod aoc7;
+pub mod aoc8;
+pub mod aoc9;
+pub mod aoc10;
+pub mod aoc11;
+pub mod aoc12;
+pub mod aoc13;
+pub mod aoc14;
+pub mod aoc15;
+pub mod aoc16;
+pub mod aoc17;
+pub mod aoc18;
+pub mod aoc19;
+pub mod aoc20;
+pub mod aoc21;
+pub mod aoc22;
+pub mod aoc23;
+pub mod aoc24;
+pub mod aoc25;
+pub mod answers;
+pub mod bench;
+pub mod bitset;
+pub mod crt;
+pub mod cycle;
+pub mod grid;
+pub mod hyperbox;
+pub mod interval;
+pub mod modular;
+pub mod pathfinding;
+
+use std::any::Any;
+use std::fmt;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(value: $t) -> Self {
+                Answer::Number(i128::try_from(value).expect("the answer doesn't fit into an i128"))
+            }
+        })*
+    };
+}
+answer_from_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+/// Error for input, that doesn't have the expected format.
+/// It points to the place in the input where it went wrong, as far as that is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// the day which failed to parse, 0 if it isn't known.
+    pub day: usize,
+    /// line number starting at 1, 0 if the error isn't about a specific line.
+    pub line: usize,
+    /// column (in characters) starting at 1, 0 if the error isn't about a specific column.
+    pub column: usize,
+    /// the token which would have been valid at the position, like "a number".
+    pub expected: Option<String>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        ParseError { day: 0, line, column: 0, expected: None, message: message.into() }
+    }
+
+    /// error for a token in a line, that isn't what was expected there.
+    /// `token` needs to be a slice of `line`, so its column can be found.
+    pub fn unexpected(line_number: usize, line: &str, token: &str, expected: impl Into<String>) -> Self {
+        let message = if token.is_empty() {
+            "unexpected end".to_string()
+        }
+        else {
+            format!("unexpected \"{token}\"")
+        };
+        ParseError::new(line_number, message).at_column(column_of(line, token)).expecting(expected)
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = column;
+        self
+    }
+
+    pub fn expecting(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+
+    pub fn on_day(mut self, day: usize) -> Self {
+        self.day = day;
+        self
+    }
+
+    /// Move an error from parsing a part of a line to the position of that part.
+    /// Errors from `TryFrom<&str>` only know the column inside of the part they got,
+    /// so the column of the part is added to it.
+    pub fn within(mut self, line_number: usize, line: &str, part: &str) -> Self {
+        self.line = line_number;
+        self.column = column_of(line, part) + self.column.max(1) - 1;
+        self
+    }
+
+    /// the error with the offending line of the input and a ^ pointing at the column, like
+    /// ```text
+    /// day 2, line 3, column 9: unexpected "x"
+    ///   |
+    /// 3 | Game 3: x green
+    ///   |         ^ expected a number
+    /// ```
+    pub fn annotate(&self, input: &str) -> String {
+        let Some(line) = input.lines().nth(self.line.wrapping_sub(1)) else {
+            return self.to_string();
+        };
+        let number = self.line.to_string();
+        let pad = " ".repeat(number.len());
+        let mut text = format!("{}\n{pad} |\n{number} | {line}\n", self.location_and_message(self.column == 0));
+        if self.column != 0 {
+            text += &format!("{pad} | {}^", " ".repeat(self.column - 1));
+            if let Some(expected) = &self.expected {
+                text += &format!(" expected {expected}");
+            }
+            text += "\n";
+        }
+        text
+    }
+
+    fn location_and_message(&self, with_expected: bool) -> String {
+        let mut location = vec![];
+        if self.day != 0 {
+            location.push(format!("day {}", self.day));
+        }
+        if self.line != 0 {
+            location.push(format!("line {}", self.line));
+        }
+        if self.column != 0 {
+            location.push(format!("column {}", self.column));
+        }
+        let mut text = location.join(", ");
+        if !text.is_empty() {
+            text += ": ";
+        }
+        text += &self.message;
+        if let (true, Some(expected)) = (with_expected, &self.expected) {
+            text += &format!(", expected {expected}");
+        }
+        text
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.location_and_message(true))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// column (starting at 1) where `part` starts in `line`. `part` needs to be a slice of `line`.
+pub fn column_of(line: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    assert!(offset <= line.len(), "the part isn't inside of the line");
+    line[..offset].chars().count() + 1
+}
+
+/// parse the number `token`, which is a slice of `line`. On failure the error points at the token.
+pub fn parse_number<T: std::str::FromStr>(line_number: usize, line: &str, token: &str) -> Result<T, ParseError> {
+    token.parse().map_err(|_| ParseError::unexpected(line_number, line, token, "a number"))
+}
+
+/// The puzzle of one day, split into parsing the input and solving the two parts on the parsed input.
+pub trait Solution {
+    const DAY: usize;
+    const TITLE: &'static str;
+    /// Parts, which take too long on the full input to run them by default.
+    const SLOW_PARTS: &'static [usize] = &[];
+    /// The parsed puzzle input, which is shared by both parts.
+    type Input: 'static;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+
+    fn solve_part1(input: &str) -> Result<Answer, ParseError> {
+        Ok(Self::part1(&Self::parse(input)?))
+    }
+
+    fn solve_part2(input: &str) -> Result<Answer, ParseError> {
+        Ok(Self::part2(&Self::parse(input)?))
+    }
+}
+
+/// Object safe version of [Solution], so all days can be put in one list.
+/// The parsed input is passed around as `dyn Any`.
+pub trait AnySolution: Sync {
+    fn day(&self) -> usize;
+    fn title(&self) -> &'static str;
+    fn is_slow(&self, part: usize) -> bool;
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError>;
+    /// run part 1 or 2 on the input from [AnySolution::parse].
+    fn run(&self, part: usize, input: &dyn Any) -> Answer;
+
+    fn solve(&self, part: usize, input: &str) -> Result<Answer, ParseError> {
+        Ok(self.run(part, self.parse(input)?.as_ref()))
+    }
+}
+
+impl<S: Solution + Sync> AnySolution for S {
+    fn day(&self) -> usize {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn is_slow(&self, part: usize) -> bool {
+        S::SLOW_PARTS.contains(&part)
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+        Ok(Box::new(S::parse(input).map_err(|err| err.on_day(S::DAY))?))
+    }
+
+    fn run(&self, part: usize, input: &dyn Any) -> Answer {
+        let input = input.downcast_ref::<S::Input>().unwrap_or_else(|| panic!("the input wasn't parsed by day {}", S::DAY));
+        match part {
+            1 => S::part1(input),
+            2 => S::part2(input),
+            _ => panic!("there is no part {part}"),
+        }
+    }
+}
+
+/// All days in order.
+pub static DAYS: [&dyn AnySolution; 25] = [
+    &aoc1::Day,
+    &aoc2::Day,
+    &aoc3::Day,
+    &aoc4::Day,
+    &aoc5::Day,
+    &aoc6::Day,
+    &aoc7::Day,
+    &aoc8::Day,
+    &aoc9::Day,
+    &aoc10::Day,
+    &aoc11::Day,
+    &aoc12::Day,
+    &aoc13::Day,
+    &aoc14::Day,
+    &aoc15::Day,
+    &aoc16::Day,
+    &aoc17::Day,
+    &aoc18::Day,
+    &aoc19::Day,
+    &aoc20::Day,
+    &aoc21::Day,
+    &aoc22::Day,
+    &aoc23::Day,
+    &aoc24::Day,
+    &aoc25::Day,
+];
+
+/// get a day from the registry, the days start at 1.
+pub fn day(day: usize) -> Option<&'static dyn AnySolution> {
+    DAYS.get(day.wrapping_sub(1)).copied()
+}
+
+pub fn input_path(day: usize) -> String {
+    format!("res/aoc{day}.txt")
+}
+
+/// read the checked in puzzle input for the given day.
+pub fn read_input(day: usize) -> String {
+    let path = input_path(day);
+    std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {path}: {err}"))
+}
+
+/// pseudo random numbers from a linear congruential generator,
+/// so the randomized tests and generated inputs are the same on every run for the same seed.
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random(seed)
+    }
+
+    /// a number in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) as usize % n
+    }
+}
+
+#[test]
+pub fn parse_error() {
+    let input = "Game 1: 3 blue\nGame 2: x green\n";
+    let line = input.lines().nth(1).unwrap();
+    let err = parse_number::<u32>(2, line, &line[8..9]).unwrap_err().on_day(2);
+    assert_eq!(err.column, 9);
+    assert_eq!(err.to_string(), "day 2, line 2, column 9: unexpected \"x\", expected a number");
+    assert_eq!(err.annotate(input), "day 2, line 2, column 9: unexpected \"x\"\n  |\n2 | Game 2: x green\n  |         ^ expected a number\n");
+    // errors from parsing a part of the line get moved to the part
+    let part = &line[8..];
+    let err = parse_number::<u32>(0, part, &part[0..1]).unwrap_err().within(2, line, part);
+    assert_eq!((err.line, err.column), (2, 9));
+    // without line, there is nothing to point at
+    assert_eq!(ParseError::new(0, "the input is empty").annotate(input), "the input is empty");
+    assert_eq!(ParseError::new(1, "no").annotate(input), "line 1: no\n  |\n1 | Game 1: 3 blue\n");
+}
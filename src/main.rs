@@ -1,35 +1,144 @@
-pub mod aoc1;
-pub mod aoc2;
-pub mod aoc3;
-pub mod aoc4;
-pub mod aoc5;
-pub mod aoc6;
-pub mod aoc7;
-pub mod aoc8;
-pub mod aoc9;
-pub mod aoc10;
-pub mod aoc11;
-pub mod aoc12;
-pub mod aoc13;
-pub mod aoc14;
-pub mod aoc15;
-pub mod aoc16;
-pub mod aoc17;
-pub mod aoc18;
-pub mod aoc19;
-pub mod aoc20;
-pub mod aoc21;
-pub mod aoc22;
-pub mod aoc23;
-pub mod aoc24;
-pub mod aoc25;
+use aoc::*;
+use std::io::{self, Read};
 
-fn main() {
+/// run one part of a day. The input is read from the given file, where "-" means stdin.
+pub fn run(day: usize, part: usize, input: &str) -> Result<(), String> {
+    let solution = aoc::day(day).ok_or(format!("there is no day {day}"))?;
+    if part != 1 && part != 2 {
+        return Err(format!("there is no part {part}"));
+    }
+    let text = if input == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map_err(|err| format!("failed to read stdin: {err}"))?;
+        text
+    }
+    else {
+        std::fs::read_to_string(input).map_err(|err| format!("failed to read {input}: {err}"))?
+    };
+    println!("--- Day {day}: {}, part {part} ---", solution.title());
+    let answer = solution.solve(part, &text).map_err(|err| {
+        let source = if input == "-" { "stdin" } else { input };
+        format!("invalid input in {source}\n{}", err.annotate(&text).trim_end())
+    })?;
+    println!("{answer}");
+    Ok(())
+}
+
+/// run every day, except for the parts that take too long on the full input.
+pub fn run_all() -> Result<(), String> {
+    for solution in aoc::DAYS {
+        let day = solution.day();
+        for part in [1, 2] {
+            if solution.is_slow(part) {
+                println!("--- Day {day}: {}, part {part} ---", solution.title());
+                println!("(skipped, this part takes a long time, use \"aoc run {day} {part}\" to run it)");
+                continue;
+            }
+            run(day, part, &input_path(day))?;
+        }
+    }
+    Ok(())
+}
+
+/// options of the bench subcommand
+pub struct BenchOptions {
+    pub runs: usize,
+    pub slow: bool,
+    pub csv: Option<String>,
+    pub json: Option<String>,
+}
+
+impl BenchOptions {
+    pub fn parse(args: &[&str]) -> Result<Self, String> {
+        let mut options = BenchOptions { runs: 10, slow: false, csv: None, json: None };
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            let mut value = || args.next().map(|s| s.to_string()).ok_or(format!("missing value for {arg}"));
+            match arg {
+                "--runs" => options.runs = value()?.parse().ok().filter(|&runs| runs > 0).ok_or("--runs needs a positive number")?,
+                "--slow" => options.slow = true,
+                "--csv" => options.csv = Some(value()?),
+                "--json" => options.json = Some(value()?),
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// time the given days on their inputs in res/ and print the timings,
+/// the CSV or JSON tables are written to files if requested.
+pub fn bench(days: &[&'static dyn AnySolution], options: &BenchOptions) -> Result<(), String> {
+    let mut benches = vec![];
+    for solution in days {
+        let day = solution.day();
+        let path = input_path(day);
+        let text = std::fs::read_to_string(&path).map_err(|err| format!("failed to read {path}: {err}"))?;
+        eprintln!("benchmarking day {day}: {}", solution.title());
+        let bench = bench::bench_day(*solution, &text, options.runs, options.slow).map_err(|err| format!("invalid input in {path}\n{}", err.annotate(&text).trim_end()))?;
+        benches.push(bench);
+    }
+    print!("{}", bench::to_table(&benches));
+    if let Some(path) = &options.csv {
+        std::fs::write(path, bench::to_csv(&benches)).map_err(|err| format!("failed to write {path}: {err}"))?;
+    }
+    if let Some(path) = &options.json {
+        std::fs::write(path, bench::to_json(&benches)).map_err(|err| format!("failed to write {path}: {err}"))?;
+    }
+    Ok(())
+}
+
+fn print_usage() {
     println!("Advent of Code 2023!");
     println!("--------------------");
-    println!("There is a file for each day, with two runnable tests each.");
-    println!("Every file is standalone, meaning all the code is in that one file.");
-    println!("The input is read from stdin, so either copy paste it or pipe it.");
-    println!("to run in release mode use:");
-    println!("cargo test --release aoc1::part1 -- --nocapture < ./res/aoc1.txt");
+    println!("There is a file for each day, with two parts each.");
+    println!("Code shared between days, like grids and pathfinding, lives in its own modules.");
+    println!("usage:");
+    println!("  aoc run <day> <part> [--input FILE]   run one part, the input defaults to res/aoc<day>.txt");
+    println!("  aoc run all                           run every day against its input in res/");
+    println!("  aoc bench <day|all> [OPTIONS]         time parsing and both parts of the days on their inputs in res/");
+    println!("bench options:");
+    println!("  --runs N        number of runs of each step (default 10)");
+    println!("  --slow          also time the parts that take a long time");
+    println!("  --csv FILE      write the timings as CSV");
+    println!("  --json FILE     write the timings as JSON");
+    println!("Use --input - to read the input from stdin. To run in release mode use:");
+    println!("cargo run --release -- run 1 1");
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let result = match args[..] {
+        ["run", "all"] => run_all(),
+        ["run", day, part, ref rest @ ..] => {
+            let input = match rest {
+                [] => None,
+                ["--input", file] => Some(file.to_string()),
+                _ => {
+                    print_usage();
+                    return;
+                }
+            };
+            match (day.parse(), part.parse()) {
+                (Ok(day), Ok(part)) => run(day, part, &input.unwrap_or_else(|| input_path(day))),
+                _ => Err(format!("invalid day or part \"{day} {part}\"")),
+            }
+        },
+        ["bench", day, ref rest @ ..] => {
+            let days = match day {
+                "all" => Ok(aoc::DAYS.to_vec()),
+                day => day.parse().ok().and_then(aoc::day).map(|solution| vec![solution]).ok_or(format!("there is no day {day}")),
+            };
+            days.and_then(|days| bench(&days, &BenchOptions::parse(rest)?))
+        },
+        _ => {
+            print_usage();
+            return;
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,194 @@
+// Modular arithmetic on u128 for the number theory puzzles.
+// Everything here is careful to never overflow silently: the operations on residues can't overflow at all,
+// and where a result can get too big (lcm, combining congruences) it's reported as an error.
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// the result didn't fit into u128.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the result doesn't fit into u128")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+// greatest common divisor (euclidean algorithm)
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// least common multiple, lcm(0, x) is 0.
+pub fn lcm(a: u128, b: u128) -> Result<u128, Overflow> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / gcd(a, b)).checked_mul(b).ok_or(Overflow)
+}
+
+/// a residue class `value (mod modulus)`, the value is always smaller than the modulus.
+/// The arithmetic operators only work on numbers with the same modulus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Mod {
+    value: u128,
+    modulus: u128,
+}
+
+impl Mod {
+    pub fn new(value: u128, modulus: u128) -> Self {
+        assert!(modulus > 0, "the modulus must be positive");
+        Mod { value: value % modulus, modulus }
+    }
+
+    pub fn value(self) -> u128 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u128 {
+        self.modulus
+    }
+
+    pub fn pow(self, mut exponent: u128) -> Self {
+        let mut base = self;
+        let mut result = Mod::new(1, self.modulus);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// the x with self * x = 1, None if the value and the modulus aren't coprime.
+    pub fn inverse(self) -> Option<Self> {
+        // extended euclidean algorithm, but the coefficients are kept as residues,
+        // so they can't get out of range like signed coefficients can
+        let (mut a, mut b) = (self.modulus, self.value);
+        let (mut x0, mut x1) = (Mod::new(0, self.modulus), Mod::new(1, self.modulus));
+        // invariant: x0 * value = a and x1 * value = b (mod modulus)
+        while b != 0 {
+            let q = Mod::new(a / b, self.modulus);
+            (a, b) = (b, a % b);
+            (x0, x1) = (x1, x0 - q * x1);
+        }
+        (a == 1).then_some(x0)
+    }
+
+    /// the chinese remainder theorem: all x with x = self and x = other, as one residue modulo the lcm.
+    /// None if the two contradict each other.
+    pub fn crt(self, other: Mod) -> Result<Option<Mod>, Overflow> {
+        let g = gcd(self.modulus, other.modulus);
+        // chinese remainder theorem only works for gcd = 1,
+        // however if both are the same (mod gcd) then it can be reduced to
+        // self (mod n1), other (mod n2/gcd) which are coprime.
+        if self.value % g != other.value % g {
+            return Ok(None);
+        }
+        let modulus = lcm(self.modulus, other.modulus)?;
+        let m2 = other.modulus / g;
+        // x = a1 + n1 * k, where n1 * k = a2 - a1 (mod n2), so k = (a2 - a1)/g * (n1/g)^-1 (mod n2/g)
+        let inverse = Mod::new(self.modulus / g, m2).inverse().expect("n1/g and n2/g are coprime");
+        // the difference is divisible by g, but only before reducing it mod n2/g
+        let diff = Mod::new(other.value.abs_diff(self.value) / g, m2);
+        let diff = if other.value >= self.value { diff } else { -diff };
+        let k = diff * inverse;
+        // n1 * k < n1 * m2 = lcm, so this fits
+        Ok(Some(Mod::new(self.value + self.modulus * k.value, modulus)))
+    }
+}
+
+impl fmt::Display for Mod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
+
+impl Add for Mod {
+    type Output = Mod;
+    fn add(self, rhs: Mod) -> Mod {
+        assert_eq!(self.modulus, rhs.modulus, "can't add numbers with different moduli");
+        // value + rhs.value can overflow, so compare with the gap to the modulus first
+        let gap = self.modulus - rhs.value;
+        let value = if self.value >= gap { self.value - gap } else { self.value + rhs.value };
+        Mod { value, modulus: self.modulus }
+    }
+}
+
+impl Neg for Mod {
+    type Output = Mod;
+    fn neg(self) -> Mod {
+        Mod::new(self.modulus - self.value, self.modulus)
+    }
+}
+
+impl Sub for Mod {
+    type Output = Mod;
+    fn sub(self, rhs: Mod) -> Mod {
+        self + -rhs
+    }
+}
+
+impl Mul for Mod {
+    type Output = Mod;
+    fn mul(self, rhs: Mod) -> Mod {
+        assert_eq!(self.modulus, rhs.modulus, "can't multiply numbers with different moduli");
+        if let Some(product) = self.value.checked_mul(rhs.value) {
+            return Mod::new(product, self.modulus);
+        }
+        // double and add, so nothing ever gets bigger than the modulus
+        let mut result = Mod { value: 0, modulus: self.modulus };
+        let mut base = self;
+        let mut factor = rhs.value;
+        while factor > 0 {
+            if factor & 1 == 1 {
+                result = result + base;
+            }
+            base = base + base;
+            factor >>= 1;
+        }
+        result
+    }
+}
+
+#[test]
+pub fn test_arithmetic() {
+    let m = |v| Mod::new(v, 7);
+    assert_eq!(m(5) + m(4), m(2));
+    assert_eq!(m(2) - m(5), m(4));
+    assert_eq!(m(3) * m(5), m(1));
+    assert_eq!(m(3).pow(6), m(1));
+    assert_eq!(m(3).inverse(), Some(m(5)));
+    assert_eq!(Mod::new(4, 12).inverse(), None);
+    assert_eq!(Mod::new(0, 1).inverse(), Some(Mod::new(0, 1)));
+    // close to the u128 limit, where the simple formulas would overflow
+    let p = u128::MAX - 158; // the largest prime below 2^128
+    let big = Mod::new(u128::MAX - 1000, p);
+    assert_eq!(big + big, Mod::new(p - 1684, p));
+    assert_eq!(big * big.inverse().unwrap(), Mod::new(1, p));
+    assert_eq!(big.pow(p - 1), Mod::new(1, p));
+    assert_eq!(gcd(12, 18), 6);
+    assert_eq!(lcm(12, 18), Ok(36));
+    assert_eq!(lcm(u128::MAX, u128::MAX - 1), Err(Overflow));
+}
+
+#[test]
+pub fn test_crt() {
+    assert_eq!(Mod::new(2, 3).crt(Mod::new(3, 5)), Ok(Some(Mod::new(8, 15))));
+    // not coprime
+    assert_eq!(Mod::new(2, 4).crt(Mod::new(4, 6)), Ok(Some(Mod::new(10, 12))));
+    assert_eq!(Mod::new(1, 4).crt(Mod::new(2, 6)), Ok(None));
+    // the i64 version of this used to overflow
+    let (p, q) = (18_446_744_073_709_551_557, 18_446_744_073_709_551_533);
+    let x = Mod::new(5, p).crt(Mod::new(7, q)).unwrap().unwrap();
+    assert_eq!((x.value() % p, x.value() % q, x.modulus()), (5, 7, p * q));
+    assert_eq!(Mod::new(5, u128::MAX).crt(Mod::new(7, u128::MAX - 1)), Err(Overflow));
+}
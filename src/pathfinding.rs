@@ -0,0 +1,111 @@
+// Shortest paths over any state type, for the puzzles where "find the cheapest way" isn't just a BFS.
+// The states are generated on the fly by a successor function, so the graph never needs to be built,
+// which is nice when the state contains more than the position (like a direction or a step counter).
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+/// A shortest path found by the search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<S> {
+    /// sum of the costs of all steps.
+    pub cost: u64,
+    /// all states from the start to the goal, both included.
+    pub states: Vec<S>,
+}
+
+/// Dijkstra's algorithm: find the cheapest path from `start` to a state where `is_goal` is true.
+/// `successors` returns the states which can be reached in one step together with the cost of that step.
+pub fn dijkstra<S, I>(start: S, successors: impl FnMut(&S) -> I, is_goal: impl FnMut(&S) -> bool) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+/// A* search: like [dijkstra], but the `heuristic` estimates the remaining cost to the goal,
+/// so the search goes towards the goal first. The heuristic must never be larger than the real remaining cost,
+/// otherwise the found path might not be the cheapest one.
+pub fn astar<S, I>(start: S, mut successors: impl FnMut(&S) -> I, mut heuristic: impl FnMut(&S) -> u64, mut is_goal: impl FnMut(&S) -> bool) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    // every state that was seen gets an index, so the heap and the parents don't need to clone the states
+    let mut states = vec![start.clone()];
+    let mut indices = HashMap::from([(start.clone(), 0)]);
+    // (best known cost, parent index) per state index
+    let mut best = vec![(0u64, usize::MAX)];
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&start), 0u64, 0usize))]);
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        if cost > best[index].0 {
+            // there was a cheaper way to this state, which was already handled
+            continue;
+        }
+        if is_goal(&states[index]) {
+            let mut path = vec![];
+            let mut i = index;
+            while i != usize::MAX {
+                path.push(states[i].clone());
+                i = best[i].1;
+            }
+            path.reverse();
+            return Some(Path { cost, states: path });
+        }
+        for (next, step_cost) in successors(&states[index]) {
+            let next_cost = cost + step_cost;
+            let next_index = match indices.get(&next) {
+                Some(&i) if best[i].0 <= next_cost => continue,
+                Some(&i) => {
+                    best[i] = (next_cost, index);
+                    i
+                },
+                None => {
+                    let i = states.len();
+                    indices.insert(next.clone(), i);
+                    states.push(next);
+                    best.push((next_cost, index));
+                    i
+                },
+            };
+            let estimate = next_cost + heuristic(&states[next_index]);
+            queue.push(Reverse((estimate, next_cost, next_index)));
+        }
+    }
+    None
+}
+
+#[test]
+pub fn test_dijkstra() {
+    // 0 -> 1 -> 3 is cheaper than the direct way 0 -> 3
+    let edges: [&[(u32, u64)]; 4] = [&[(1, 1), (2, 5), (3, 10)], &[(3, 2)], &[(3, 1)], &[]];
+    let path = dijkstra(0, |&n| edges[n as usize].iter().copied(), |&n| n == 3).unwrap();
+    assert_eq!(path, Path { cost: 3, states: vec![0, 1, 3] });
+    // the start can be the goal
+    assert_eq!(dijkstra(2, |&n| edges[n as usize].iter().copied(), |&n| n == 2).unwrap(), Path { cost: 0, states: vec![2] });
+    // nothing leads back to 0
+    assert_eq!(dijkstra(1, |&n| edges[n as usize].iter().copied(), |&n| n == 0), None);
+}
+
+#[test]
+pub fn test_astar() {
+    use crate::grid::{Grid, Point};
+    let grid = Grid::parse("\
+        ...#....
+        .#.#.##.
+        .#...#..
+        .####.#.
+        ........", "a wall # or free space .", |c| Some(c == '#')).unwrap();
+    let goal = Point(7, 0);
+    let successors = |p: &Point| {
+        grid.neighbors(*p).filter(|&(_, next)| !grid[next]).map(|(_, next)| (next, 1)).collect::<Vec<_>>()
+    };
+    let path = astar(Point(0, 0), successors, |p| p.manhattan(&goal), |&p| p == goal).unwrap();
+    let expected = dijkstra(Point(0, 0), successors, |&p| p == goal).unwrap();
+    assert_eq!(path.cost, 11);
+    assert_eq!(path.cost, expected.cost);
+    assert_eq!(path.states.len(), 12);
+    assert!(path.states.windows(2).all(|w| w[0].manhattan(&w[1]) == 1 && !grid[w[1]]));
+}